@@ -0,0 +1,147 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Sandwich analyzer throughput at mainnet-scale swap rates
+//
+// Run with `cargo bench --bench sandwich`
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use std::sync::{Arc, Mutex};
+use sui_indexer::analyzer::{AddressClusterer, GasPriceMonitor, SandwichAnalyzer};
+use sui_indexer::constants::SIMULATION_PACKAGE_ID;
+use sui_indexer::events::SwapExecuted;
+use sui_indexer::risk::DetectionContext;
+use sui_types::base_types::{ObjectDigest, ObjectID, SequenceNumber, SuiAddress};
+use sui_types::effects::{TransactionEffects, TransactionEvents};
+use sui_types::event::Event;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::{ObjectArg, SharedObjectMutability, TransactionData};
+use sui_types::{parse_sui_struct_tag, Identifier};
+
+// ~100 swaps/s across 2,000 pools, with a few hot pools taking most of the flow
+const SWAPS: u64 = 20_000;
+const POOLS: u64 = 2_000;
+const HOT_POOLS: u64 = 8;
+const SENDERS: u64 = 500;
+const SWAPS_PER_CHECKPOINT: u64 = 25;
+
+fn id(n: u64) -> ObjectID {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&n.to_be_bytes());
+    ObjectID::new(bytes)
+}
+
+/// A `simple_dex::swap` call on a shared pool emitting `SwapExecuted`
+fn swap_transaction(i: u64) -> ExecutedTransaction {
+    let package = ObjectID::from_hex_literal(SIMULATION_PACKAGE_ID).unwrap();
+    let pool = if i % 4 == 0 { i % POOLS } else { i % HOT_POOLS };
+    let pool_id = id(pool + 1);
+    let sender = SuiAddress::from(id(POOLS + 1 + i % SENDERS));
+    let token_in = i % 3 != 0;
+    // Every 50th swap bids up gas, as a front-runner would
+    let gas_price = if i % 50 == 0 { 5_000 } else { 1_000 };
+
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let pool_arg = builder
+        .obj(ObjectArg::SharedObject {
+            id: pool_id,
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutability: SharedObjectMutability::Mutable,
+        })
+        .unwrap();
+    builder.programmable_move_call(
+        package,
+        Identifier::new("simple_dex").unwrap(),
+        Identifier::new("swap").unwrap(),
+        vec![],
+        vec![pool_arg],
+    );
+
+    let gas_coin = (id(u64::MAX - i), SequenceNumber::from_u64(1), ObjectDigest::random());
+    let transaction = TransactionData::new_programmable(
+        sender,
+        vec![gas_coin],
+        builder.finish(),
+        10_000_000,
+        gas_price,
+    );
+
+    let amount_in = 1_000_000 + (i % 97) * 10_000;
+    let swap = SwapExecuted {
+        pool_id,
+        sender,
+        token_in,
+        amount_in,
+        amount_out: amount_in * 99 / 100,
+        fee_amount: amount_in * 3 / 1000,
+        reserve_a: 100_000_000 + (i % 13) * 1_000_000,
+        reserve_b: 99_000_000 - (i % 13) * 1_000_000,
+        price_impact: 100 + i % 200,
+    };
+    let event = Event {
+        package_id: package,
+        transaction_module: Identifier::new("simple_dex").unwrap(),
+        sender,
+        type_: parse_sui_struct_tag(&format!("{}::simple_dex::SwapExecuted", SIMULATION_PACKAGE_ID))
+            .unwrap(),
+        contents: bcs::to_bytes(&swap).unwrap(),
+    };
+
+    ExecutedTransaction {
+        transaction,
+        signatures: vec![],
+        effects: TransactionEffects::default(),
+        events: Some(TransactionEvents { data: vec![event] }),
+        unchanged_loaded_runtime_objects: vec![],
+    }
+}
+
+fn swap_stream() -> Vec<(ExecutedTransaction, DetectionContext)> {
+    (0..SWAPS)
+        .map(|i| {
+            let tx = swap_transaction(i);
+            let context = DetectionContext::new(
+                tx.transaction.digest().to_string(),
+                tx.transaction.sender().to_string(),
+                (i / SWAPS_PER_CHECKPOINT) as i64,
+                (i * 10) as i64,
+            );
+            (tx, context)
+        })
+        .collect()
+}
+
+/// Mirrors the handler: every transaction feeds the clusterer, then the analyzer runs
+fn bench_analyze(c: &mut Criterion) {
+    let stream = swap_stream();
+
+    let mut group = c.benchmark_group("sandwich");
+    group.throughput(Throughput::Elements(SWAPS));
+    group.sample_size(10);
+    group.bench_function("analyze", |b| {
+        b.iter_batched(
+            || {
+                let clusterer = Arc::new(Mutex::new(AddressClusterer::new()));
+                let analyzer = SandwichAnalyzer::new()
+                    .with_clusterer(clusterer.clone())
+                    .with_gas_monitor(Arc::new(GasPriceMonitor::new()));
+                (clusterer, analyzer)
+            },
+            |(clusterer, analyzer)| {
+                let mut detected = 0;
+                for (tx, context) in &stream {
+                    clusterer
+                        .lock()
+                        .unwrap()
+                        .record_transaction(tx, context.timestamp_ms);
+                    detected += analyzer.analyze(tx, context).len();
+                }
+                detected
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_analyze);
+criterion_main!(benches);
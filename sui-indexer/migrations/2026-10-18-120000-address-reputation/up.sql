@@ -0,0 +1,14 @@
+-- Address reputation: labels, allow/blocklists and a decaying detection score
+
+CREATE TABLE address_reputation (
+    address TEXT PRIMARY KEY,
+    label TEXT,
+    list_status TEXT NOT NULL DEFAULT 'unlisted' CHECK (list_status IN ('unlisted', 'allow', 'block')),
+    score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    score_updated_ms BIGINT NOT NULL DEFAULT 0,
+    detection_count BIGINT NOT NULL DEFAULT 0,
+    last_risk_type TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_address_reputation_list_status ON address_reputation(list_status);
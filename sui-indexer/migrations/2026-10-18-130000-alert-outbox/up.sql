@@ -0,0 +1,17 @@
+-- Alert outbox: channel deliveries written by actions and sent by the delivery worker
+
+CREATE TABLE alert_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    channel TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('event', 'digest')),
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'dead')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_alert_outbox_due ON alert_outbox(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX idx_alert_outbox_status ON alert_outbox(status, id);
@@ -1,114 +1,282 @@
 use async_trait::async_trait;
 use anyhow::Result;
+use crate::action::channel::{channels_from_env, parse_level, AlertChannel};
+use crate::action::throttle::{dedup_key, DedupCache, RateLimiter};
 use crate::action::ActionHandler;
+use crate::incident::Incident;
+use crate::outbox::{Delivery, Outbox};
 use crate::risk::{RiskEvent, RiskLevel};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+/// Digest mode: events up to `max_level` are summarized every `interval_ms`
+#[derive(Debug, Clone, Copy)]
+pub struct DigestConfig {
+    pub interval_ms: u64,
+    pub max_level: RiskLevel,
+}
+
+/// Channel with its rate limiter and the events held for its next digest
+struct ChannelState {
+    channel: Arc<dyn AlertChannel>,
+    limiter: Mutex<RateLimiter>,
+    pending: Mutex<Vec<RiskEvent>>,
+}
+
+impl ChannelState {
+    async fn flush(&self, outbox: Option<&Outbox>) {
+        let events = std::mem::take(&mut *self.pending.lock().unwrap());
+        if events.is_empty() {
+            return;
+        }
+
+        dispatch(outbox, self.channel.as_ref(), Delivery::Digest(events)).await;
+    }
+}
+
+/// Queue a delivery in the outbox, or send it directly without one (or if
+/// queueing fails)
+async fn dispatch(outbox: Option<&Outbox>, channel: &dyn AlertChannel, delivery: Delivery) {
+    if let Some(outbox) = outbox {
+        match outbox.enqueue(channel.name(), &delivery).await {
+            Ok(id) => {
+                println!("📮 Queued {} #{} for {}", delivery.kind(), id, channel.name());
+                return;
+            }
+            Err(e) => eprintln!("⚠ Warning: Failed to queue alert for {}, sending directly: {}", channel.name(), e),
+        }
+    }
+
+    match delivery.send(channel).await {
+        Ok(()) => println!("✅ Alert {} sent to {}", delivery.kind(), channel.name()),
+        Err(e) => println!("❌ Failed to send alert {} to {}: {}", delivery.kind(), channel.name(), e),
+    }
+}
+
+/// Fans alerts out to every channel whose minimum level the event meets
+///
+/// Per event:
+/// 1. Repeats of a dedup key (type, sender, pool) within the cooldown are
+///    dropped, unless the level escalates. Incidents skip this step: they only
+///    reach actions when they open, escalate or close, so every one is a change
+/// 2. In digest mode, events up to the digest level are held for the summary
+/// 3. Others are sent at once if the channel's rate limit allows; otherwise
+///    they're held for the digest, or dropped without digest mode
+///
+/// With an outbox, deliveries are queued in Postgres and sent by the
+/// `DeliveryWorker` with retries instead of being sent here.
 pub struct AlertAction {
-    webhook_url: Option<String>,
-    min_level: RiskLevel,
+    channels: Vec<Arc<ChannelState>>,
+    dedup: Mutex<DedupCache>,
+    digest: Option<DigestConfig>,
+    outbox: Option<Arc<Outbox>>,
 }
 
 impl AlertAction {
-    pub fn new(webhook_url: Option<String>, min_level: RiskLevel) -> Self {
-        Self {
-            webhook_url,
-            min_level,
+    pub fn new(channels: Vec<Box<dyn AlertChannel>>) -> Self {
+        let mut action = Self {
+            channels: Vec::new(),
+            dedup: Mutex::new(DedupCache::new(300_000)), // 5 minutes
+            digest: None,
+            outbox: None,
+        };
+        for channel in channels {
+            action.push_channel(channel);
         }
+        action
     }
 
-    fn should_alert(&self, event: &RiskEvent) -> bool {
-        let event_priority = match event.risk_level {
-            RiskLevel::Critical => 4,
-            RiskLevel::High => 3,
-            RiskLevel::Medium => 2,
-            RiskLevel::Low => 1,
-        };
+    /// Channels configured in the environment, sharing one HTTP client
+    pub fn from_env() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        let mut action = Self::new(channels_from_env(&client)?);
 
-        let min_priority = match self.min_level {
-            RiskLevel::Critical => 4,
-            RiskLevel::High => 3,
-            RiskLevel::Medium => 2,
-            RiskLevel::Low => 1,
+        if let Some(ms) = std::env::var("ALERT_DEDUP_COOLDOWN_MS").ok().and_then(|v| v.parse().ok()) {
+            action = action.with_dedup_cooldown_ms(ms);
+        }
+        if let Some(interval_ms) = std::env::var("ALERT_DIGEST_INTERVAL_MS").ok().and_then(|v| v.parse().ok()) {
+            let max_level = std::env::var("ALERT_DIGEST_MAX_LEVEL")
+                .ok()
+                .and_then(|v| parse_level(&v))
+                .unwrap_or(RiskLevel::Medium);
+            action = action.with_digest(interval_ms, max_level);
+        }
+
+        Ok(action)
+    }
+
+    /// Set how long repeats of a dedup key are suppressed (0 disables)
+    pub fn with_dedup_cooldown_ms(mut self, cooldown_ms: i64) -> Self {
+        self.dedup = Mutex::new(DedupCache::new(cooldown_ms));
+        self
+    }
+
+    /// Enable digest mode (an interval of 0 disables it)
+    pub fn with_digest(mut self, interval_ms: u64, max_level: RiskLevel) -> Self {
+        self.digest = (interval_ms > 0).then_some(DigestConfig { interval_ms, max_level });
+        self
+    }
+
+    /// Queue deliveries in the outbox instead of sending them
+    pub fn with_outbox(mut self, outbox: Arc<Outbox>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    pub fn channel_names(&self) -> Vec<&'static str> {
+        self.channels.iter().map(|s| s.channel.name()).collect()
+    }
+
+    /// Channels for the delivery worker
+    pub fn channels(&self) -> Vec<Arc<dyn AlertChannel>> {
+        self.channels.iter().map(|s| s.channel.clone()).collect()
+    }
+
+    /// Spawn the task that sends digests; a no-op without digest mode
+    pub fn start_digest(&self) {
+        let Some(digest) = self.digest else {
+            return;
         };
 
-        event_priority >= min_priority
+        let channels = self.channels.clone();
+        let outbox = self.outbox.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(digest.interval_ms));
+            ticker.tick().await; // First tick is immediate
+            loop {
+                ticker.tick().await;
+                for state in &channels {
+                    state.flush(outbox.as_deref()).await;
+                }
+            }
+        });
     }
 
-    fn get_color(&self, level: &RiskLevel) -> u32 {
-        match level {
-            RiskLevel::Critical => 0xFF0000, // Red
-            RiskLevel::High => 0xE67E22,     // Orange
-            RiskLevel::Medium => 0xF1C40F,   // Yellow
-            RiskLevel::Low => 0x3498DB,      // Blue
+    /// Send or hold an event for every channel whose minimum level it meets
+    async fn deliver(&self, event: &RiskEvent, now_ms: i64) {
+        for state in self.channels.iter().filter(|s| event.risk_level >= s.channel.min_level()) {
+            let name = state.channel.name();
+            let held = self.digest.is_some_and(|d| event.risk_level <= d.max_level);
+            if held {
+                state.pending.lock().unwrap().push(event.clone());
+                continue;
+            }
+
+            if !state.limiter.lock().unwrap().try_acquire(now_ms) {
+                if self.digest.is_some() {
+                    println!("⏳ Alert to {} rate limited, held for digest", name);
+                    state.pending.lock().unwrap().push(event.clone());
+                } else {
+                    eprintln!("⚠ Warning: Alert to {} dropped by rate limit", name);
+                }
+                continue;
+            }
+
+            // One failing channel doesn't hold back the others
+            dispatch(self.outbox.as_deref(), state.channel.as_ref(), Delivery::Event(event.clone())).await;
         }
     }
+
+    fn push_channel(&mut self, channel: Box<dyn AlertChannel>) {
+        self.channels.push(Arc::new(ChannelState {
+            limiter: Mutex::new(RateLimiter::new(channel.rate_per_minute())),
+            pending: Mutex::new(Vec::new()),
+            channel: Arc::from(channel),
+        }));
+    }
 }
 
 #[async_trait]
 impl ActionHandler for AlertAction {
     async fn handle(&self, event: &RiskEvent) -> Result<()> {
-        if !self.should_alert(event) {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+
+        let key = dedup_key(event);
+        if !self.dedup.lock().unwrap().check(&key, event.risk_level, now_ms) {
+            println!("🔇 Suppressed duplicate alert {}", key);
             return Ok(());
         }
 
-        if let Some(url) = &self.webhook_url {
-            let client = reqwest::Client::new();
-            
-            // Format details as fields
-            let mut fields = vec![
-                serde_json::json!({
-                    "name": "Transaction",
-                    "value": format!("[View on Explorer](https://suiscan.xyz/testnet/tx/{})", event.tx_digest),
-                    "inline": true
-                }),
-                serde_json::json!({
-                    "name": "Sender",
-                    "value": format!("`{}`", event.sender),
-                    "inline": true
-                }),
-                serde_json::json!({
-                    "name": "Checkpoint",
-                    "value": event.checkpoint.to_string(),
-                    "inline": true
-                }),
-            ];
-
-            // Add specific details if available
-            for (key, value) in &event.details {
-                fields.push(serde_json::json!({
-                    "name": key,
-                    "value": format!("`{}`", value),
-                    "inline": false
-                }));
-            }
-
-            let payload = serde_json::json!({
-                "username": "Sui Security Bot",
-                "avatar_url": "https://cryptologos.cc/logos/sui-sui-logo.png",
-                "embeds": [{
-                    "title": format!("🚨 {:?} Security Alert Detected!", event.risk_type),
-                    "description": event.description,
-                    "color": self.get_color(&event.risk_level),
-                    "fields": fields,
-                    "footer": {
-                        "text": format!("Risk Level: {:?}", event.risk_level)
-                    },
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                }]
-            });
-
-            match client.post(url).json(&payload).send().await {
-                Ok(_) => println!("✅ Alert sent to Discord"),
-                Err(e) => println!("❌ Failed to send alert to Discord: {}", e),
-            }
-        }
+        self.deliver(event, now_ms).await;
+        Ok(())
+    }
 
+    async fn handle_incident(&self, incident: &Incident) -> Result<()> {
+        // A new risk type at the same level keeps the dedup key, so incident
+        // state changes bypass dedup
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        self.deliver(&incident.summary_event(), now_ms).await;
         Ok(())
     }
 }
 
 impl Default for AlertAction {
     fn default() -> Self {
-        Self::new(None, RiskLevel::High)
+        Self::new(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::incident::IncidentCorrelator;
+    use crate::risk::RiskType;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Channel counting what it was asked to send
+    struct CountingChannel {
+        sent: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl AlertChannel for CountingChannel {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn min_level(&self) -> RiskLevel {
+            RiskLevel::Low
+        }
+
+        fn rate_per_minute(&self) -> u32 {
+            100
+        }
+
+        async fn send(&self, _event: &RiskEvent) -> Result<()> {
+            self.sent.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn send_text(&self, _title: &str, _body: &str) -> Result<()> {
+            self.sent.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    fn event(risk_type: RiskType, tx: &str, timestamp_ms: i64) -> RiskEvent {
+        RiskEvent::new(risk_type, RiskLevel::High, tx.to_string(), "0xa".to_string(), 1000, timestamp_ms, "test".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_incident_escalation_not_deduplicated() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let alerts = AlertAction::new(vec![Box::new(CountingChannel { sent: sent.clone() })]);
+        let correlator = IncidentCorrelator::new();
+
+        // A new risk type at the same level keeps the summary's dedup key
+        let opened = correlator.correlate(vec![event(RiskType::SandwichAttack, "tx1", 1000)]);
+        let escalated = correlator.correlate(vec![event(RiskType::LiquidityDrain, "tx2", 5000)]);
+        assert_eq!(dedup_key(&opened[0].summary_event()), dedup_key(&escalated[0].summary_event()));
+
+        alerts.handle_incident(&opened[0]).await.unwrap();
+        alerts.handle_incident(&escalated[0]).await.unwrap();
+        assert_eq!(sent.load(Ordering::Relaxed), 2);
+
+        // Plain events with that key are still deduplicated
+        alerts.handle(&opened[0].summary_event()).await.unwrap();
+        alerts.handle(&opened[0].summary_event()).await.unwrap();
+        assert_eq!(sent.load(Ordering::Relaxed), 3);
     }
 }
@@ -0,0 +1,131 @@
+use super::{check_response, min_level_from_env, rate_from_env, AlertChannel, ChannelTemplates};
+use crate::risk::{RiskEvent, RiskLevel};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Discord allows 30 requests per minute per webhook
+const DEFAULT_RATE_PER_MINUTE: u32 = 30;
+
+const DEFAULT_TITLE: &str = "{{level_emoji}} {{risk_type}} Security Alert Detected!";
+const DEFAULT_BODY: &str = "{{description}}
+
+**Score:** {{score}}/100
+**Sender:** `{{sender}}`
+**Checkpoint:** {{checkpoint}}
+[View on Explorer]({{explorer_url}})
+
+**Signals**
+{{signals_text}}
+
+**Metrics**
+{{metrics_text}}";
+
+/// Discord webhook with an embed per alert
+pub struct DiscordChannel {
+    client: reqwest::Client,
+    webhook_url: String,
+    username: String,
+    avatar_url: String,
+    templates: ChannelTemplates,
+    min_level: RiskLevel,
+    rate_per_minute: u32,
+}
+
+impl DiscordChannel {
+    pub fn new(client: reqwest::Client, webhook_url: String, templates: ChannelTemplates, min_level: RiskLevel) -> Self {
+        Self {
+            client,
+            webhook_url,
+            username: "Sui Security Bot".to_string(),
+            avatar_url: "https://cryptologos.cc/logos/sui-sui-logo.png".to_string(),
+            templates,
+            min_level,
+            rate_per_minute: DEFAULT_RATE_PER_MINUTE,
+        }
+    }
+
+    /// `DISCORD_WEBHOOK_URL` (or the older `ALERT_WEBHOOK_URL`)
+    pub fn from_env(client: reqwest::Client) -> Result<Option<Self>> {
+        let Ok(webhook_url) = std::env::var("DISCORD_WEBHOOK_URL").or_else(|_| std::env::var("ALERT_WEBHOOK_URL")) else {
+            return Ok(None);
+        };
+
+        let templates = ChannelTemplates::from_env("DISCORD", DEFAULT_TITLE, DEFAULT_BODY)?;
+        let mut channel = Self::new(client, webhook_url, templates, min_level_from_env("DISCORD"));
+        channel.rate_per_minute = rate_from_env("DISCORD", DEFAULT_RATE_PER_MINUTE);
+        if let Ok(username) = std::env::var("DISCORD_USERNAME") {
+            channel.username = username;
+        }
+        if let Ok(avatar_url) = std::env::var("DISCORD_AVATAR_URL") {
+            channel.avatar_url = avatar_url;
+        }
+
+        Ok(Some(channel))
+    }
+
+    fn color(level: RiskLevel) -> u32 {
+        match level {
+            RiskLevel::Critical => 0xFF0000, // Red
+            RiskLevel::High => 0xE67E22,     // Orange
+            RiskLevel::Medium => 0xF1C40F,   // Yellow
+            RiskLevel::Low => 0x3498DB,      // Blue
+        }
+    }
+
+    async fn post_embed(&self, title: &str, description: &str, color: u32, footer: Option<String>) -> Result<()> {
+        // Embed limits: 256 chars of title, 4096 of description
+        let title: String = title.chars().take(256).collect();
+        let description: String = description.chars().take(4096).collect();
+
+        let mut embed = serde_json::json!({
+            "title": title,
+            "description": description,
+            "color": color,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        if let Some(footer) = footer {
+            embed["footer"] = serde_json::json!({ "text": footer });
+        }
+
+        let payload = serde_json::json!({
+            "username": self.username,
+            "avatar_url": self.avatar_url,
+            "embeds": [embed]
+        });
+
+        let response = self
+            .client
+            .post(&self.webhook_url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+        check_response(response).await
+    }
+}
+
+#[async_trait]
+impl AlertChannel for DiscordChannel {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    fn min_level(&self) -> RiskLevel {
+        self.min_level
+    }
+
+    fn rate_per_minute(&self) -> u32 {
+        self.rate_per_minute
+    }
+
+    async fn send(&self, event: &RiskEvent) -> Result<()> {
+        let title = self.templates.title.render(event);
+        let description = self.templates.body.render(event);
+        let footer = format!("Risk Level: {:?}", event.risk_level);
+        self.post_embed(&title, &description, Self::color(event.risk_level), Some(footer)).await
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<()> {
+        self.post_embed(title, body, Self::color(RiskLevel::Low), None).await
+    }
+}
@@ -0,0 +1,125 @@
+use super::{min_level_from_env, rate_from_env, AlertChannel, ChannelTemplates};
+use crate::risk::{RiskEvent, RiskLevel};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+const DEFAULT_RATE_PER_MINUTE: u32 = 10;
+
+const DEFAULT_TITLE: &str = "[{{risk_level}}] {{risk_type}} detected in {{tx_digest}}";
+const DEFAULT_BODY: &str = "{{description}}
+
+Risk level: {{risk_level}}
+Score:      {{score}}/100
+Sender:     {{sender}}
+Checkpoint: {{checkpoint}}
+Explorer:   {{explorer_url}}
+
+Signals
+{{signals_text}}
+
+Metrics
+{{metrics_text}}
+
+Details
+{{details_text}}";
+
+/// Plain-text email over SMTP (STARTTLS)
+pub struct EmailChannel {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    templates: ChannelTemplates,
+    min_level: RiskLevel,
+    rate_per_minute: u32,
+}
+
+impl EmailChannel {
+    pub fn new(
+        transport: AsyncSmtpTransport<Tokio1Executor>,
+        from: Mailbox,
+        to: Vec<Mailbox>,
+        templates: ChannelTemplates,
+        min_level: RiskLevel,
+    ) -> Self {
+        Self {
+            transport,
+            from,
+            to,
+            templates,
+            min_level,
+            rate_per_minute: DEFAULT_RATE_PER_MINUTE,
+        }
+    }
+
+    /// `SMTP_HOST`, `SMTP_FROM`, `SMTP_TO` (comma-separated) and optional
+    /// `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD`
+    pub fn from_env() -> Result<Option<Self>> {
+        let (Ok(host), Ok(from), Ok(to)) = (
+            std::env::var("SMTP_HOST"),
+            std::env::var("SMTP_FROM"),
+            std::env::var("SMTP_TO"),
+        ) else {
+            return Ok(None);
+        };
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .with_context(|| format!("Invalid SMTP host {}", host))?;
+        if let Some(port) = std::env::var("SMTP_PORT").ok().and_then(|v| v.parse().ok()) {
+            builder = builder.port(port);
+        }
+        if let (Ok(username), Ok(password)) = (std::env::var("SMTP_USERNAME"), std::env::var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let from: Mailbox = from.parse().with_context(|| format!("Invalid SMTP_FROM {}", from))?;
+        let to = to
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(|a| a.parse().with_context(|| format!("Invalid SMTP_TO address {}", a)))
+            .collect::<Result<Vec<Mailbox>>>()?;
+
+        let templates = ChannelTemplates::from_env("EMAIL", DEFAULT_TITLE, DEFAULT_BODY)?;
+        let mut channel = Self::new(builder.build(), from, to, templates, min_level_from_env("EMAIL"));
+        channel.rate_per_minute = rate_from_env("EMAIL", DEFAULT_RATE_PER_MINUTE);
+        Ok(Some(channel))
+    }
+}
+
+#[async_trait]
+impl AlertChannel for EmailChannel {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn min_level(&self) -> RiskLevel {
+        self.min_level
+    }
+
+    fn rate_per_minute(&self) -> u32 {
+        self.rate_per_minute
+    }
+
+    async fn send(&self, event: &RiskEvent) -> Result<()> {
+        self.send_text(&self.templates.title.render(event), &self.templates.body.render(event))
+            .await
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<()> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(title)
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        let message = builder.body(body.to_string())?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
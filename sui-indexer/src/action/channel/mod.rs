@@ -0,0 +1,280 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Alert Channels: Discord, Slack, Telegram, JSON Webhook and SMTP Email
+
+mod discord;
+mod email;
+mod slack;
+mod telegram;
+mod webhook;
+
+pub use discord::DiscordChannel;
+pub use email::EmailChannel;
+pub use slack::SlackChannel;
+pub use telegram::TelegramChannel;
+pub use webhook::WebhookChannel;
+
+use super::template::{explorer_url, Template};
+use super::throttle::dedup_key;
+use crate::risk::{RiskEvent, RiskLevel};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// Destination for alert messages
+#[async_trait]
+pub trait AlertChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Lowest risk level sent to this channel
+    fn min_level(&self) -> RiskLevel;
+
+    /// Sends per minute before alerts are held for the digest
+    fn rate_per_minute(&self) -> u32;
+
+    /// Errors are logged and stored in the outbox, so they must not carry the
+    /// channel URL: webhook and bot tokens are part of it
+    async fn send(&self, event: &RiskEvent) -> Result<()>;
+
+    /// Message not tied to one event
+    async fn send_text(&self, title: &str, body: &str) -> Result<()>;
+
+    /// Summary of events held back by digest mode or rate limiting
+    async fn send_digest(&self, events: &[RiskEvent]) -> Result<()> {
+        self.send_text(&digest_title(events), &digest_body(events)).await
+    }
+}
+
+/// Title and body templates of a channel
+#[derive(Debug, Clone)]
+pub struct ChannelTemplates {
+    pub title: Template,
+    pub body: Template,
+}
+
+impl ChannelTemplates {
+    /// `<PREFIX>_TITLE_TEMPLATE` (inline) and `<PREFIX>_TEMPLATE_FILE` override the defaults
+    pub fn from_env(prefix: &str, default_title: &str, default_body: &str) -> Result<Self> {
+        let title = std::env::var(format!("{}_TITLE_TEMPLATE", prefix))
+            .unwrap_or_else(|_| default_title.to_string());
+        let body = match std::env::var(format!("{}_TEMPLATE_FILE", prefix)) {
+            Ok(path) => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {} template {}", prefix, path))?,
+            Err(_) => default_body.to_string(),
+        };
+
+        Ok(Self {
+            title: Template::new(title),
+            body: Template::new(body),
+        })
+    }
+}
+
+/// `<PREFIX>_MIN_LEVEL`, then `ALERT_THRESHOLD`, then Low
+pub fn min_level_from_env(prefix: &str) -> RiskLevel {
+    std::env::var(format!("{}_MIN_LEVEL", prefix))
+        .or_else(|_| std::env::var("ALERT_THRESHOLD"))
+        .ok()
+        .and_then(|v| parse_level(&v))
+        .unwrap_or(RiskLevel::Low)
+}
+
+pub fn parse_level(value: &str) -> Option<RiskLevel> {
+    match value.trim().to_lowercase().as_str() {
+        "low" => Some(RiskLevel::Low),
+        "medium" => Some(RiskLevel::Medium),
+        "high" => Some(RiskLevel::High),
+        "critical" => Some(RiskLevel::Critical),
+        _ => None,
+    }
+}
+
+/// `<PREFIX>_RATE_PER_MINUTE`, else the channel's default
+pub fn rate_from_env(prefix: &str, default: u32) -> u32 {
+    std::env::var(format!("{}_RATE_PER_MINUTE", prefix))
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Channels configured in the environment, sharing one HTTP client
+pub fn channels_from_env(client: &reqwest::Client) -> Result<Vec<Box<dyn AlertChannel>>> {
+    let mut channels: Vec<Box<dyn AlertChannel>> = Vec::new();
+
+    if let Some(channel) = DiscordChannel::from_env(client.clone())? {
+        channels.push(Box::new(channel));
+    }
+    if let Some(channel) = SlackChannel::from_env(client.clone())? {
+        channels.push(Box::new(channel));
+    }
+    if let Some(channel) = TelegramChannel::from_env(client.clone())? {
+        channels.push(Box::new(channel));
+    }
+    if let Some(channel) = WebhookChannel::from_env(client.clone())? {
+        channels.push(Box::new(channel));
+    }
+    if let Some(channel) = EmailChannel::from_env()? {
+        channels.push(Box::new(channel));
+    }
+
+    Ok(channels)
+}
+
+pub fn digest_title(events: &[RiskEvent]) -> String {
+    let highest = events.iter().map(|e| e.risk_level).max().unwrap_or(RiskLevel::Low);
+    format!("📋 Alert digest: {} events (highest {:?})", events.len(), highest)
+}
+
+/// One line per dedup key, in order of first occurrence
+pub fn digest_body(events: &[RiskEvent]) -> String {
+    let mut groups: Vec<(String, Vec<&RiskEvent>)> = Vec::new();
+    for event in events {
+        let key = dedup_key(event);
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => group.push(event),
+            None => groups.push((key, vec![event])),
+        }
+    }
+
+    groups
+        .iter()
+        .map(|(_, group)| {
+            let latest = group[group.len() - 1];
+            let level = group.iter().map(|e| e.risk_level).max().unwrap_or(latest.risk_level);
+            let score = group.iter().map(|e| e.score).max().unwrap_or(0);
+            format!(
+                "- [{:?}] {:?} from {} x{} (max score {}/100) {}",
+                level,
+                latest.risk_type,
+                latest.sender,
+                group.len(),
+                score,
+                explorer_url(&latest.tx_digest)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Longest wait honoured from a 429; larger or unparsable hints are capped here
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// HTTP 429 from a channel; the delivery worker waits `retry_after` before
+/// sending to the channel again
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rate limited, retry after {:.1}s", self.retry_after.as_secs_f64())
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Fail on non-2xx responses, keeping the body for the error
+async fn check_response(response: reqwest::Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let header = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_retry_after(v, chrono::Utc::now()));
+    let body = response.text().await.unwrap_or_default();
+
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = header
+            .or_else(|| retry_after_from_body(&body).and_then(seconds_to_duration))
+            .unwrap_or(Duration::from_secs(60));
+        return Err(RateLimited { retry_after }.into());
+    }
+    anyhow::bail!("HTTP {}: {}", status, body)
+}
+
+/// `Retry-After` header: delay in seconds or an HTTP date
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return seconds_to_duration(seconds);
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let seconds = (date.with_timezone(&chrono::Utc) - now).num_milliseconds() as f64 / 1000.0;
+    seconds_to_duration(seconds)
+}
+
+/// Finite delays clamped to `0..=MAX_RETRY_AFTER`; NaN and infinity are rejected
+fn seconds_to_duration(seconds: f64) -> Option<Duration> {
+    if !seconds.is_finite() {
+        return None;
+    }
+    let seconds = seconds.clamp(0.0, MAX_RETRY_AFTER.as_secs_f64());
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// `retry_after` seconds in a 429 body (Discord at the top level, Telegram
+/// under `parameters`)
+fn retry_after_from_body(body: &str) -> Option<f64> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    json.get("retry_after")
+        .or_else(|| json.get("parameters").and_then(|p| p.get("retry_after")))
+        .and_then(|v| v.as_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskType;
+
+    fn event(risk_type: RiskType, level: RiskLevel, sender: &str, digest: &str) -> RiskEvent {
+        RiskEvent::new(risk_type, level, digest.to_string(), sender.to_string(), 1, 0, "test".to_string())
+    }
+
+    #[test]
+    fn test_digest_groups_by_dedup_key() {
+        let events = vec![
+            event(RiskType::WashTrading, RiskLevel::Low, "0xa", "0x1"),
+            event(RiskType::PriorityGasBidding, RiskLevel::Medium, "0xb", "0x2"),
+            event(RiskType::WashTrading, RiskLevel::Medium, "0xa", "0x3"),
+        ];
+
+        assert_eq!(digest_title(&events), "📋 Alert digest: 3 events (highest Medium)");
+        let body = digest_body(&events);
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("- [Medium] WashTrading from 0xa x2"));
+        assert!(lines[0].ends_with("/tx/0x3"));
+    }
+
+    #[test]
+    fn test_retry_after_from_body() {
+        assert_eq!(retry_after_from_body(r#"{"message":"rate limited","retry_after":1.5}"#), Some(1.5));
+        assert_eq!(retry_after_from_body(r#"{"ok":false,"parameters":{"retry_after":30}}"#), Some(30.0));
+        assert_eq!(retry_after_from_body("Too Many Requests"), None);
+    }
+
+    #[test]
+    fn test_retry_after_bounded() {
+        let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z").unwrap().with_timezone(&chrono::Utc);
+        assert_eq!(parse_retry_after(" 1.5 ", now), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_retry_after("-3", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("inf", now), None);
+        assert_eq!(parse_retry_after("NaN", now), None);
+        assert_eq!(parse_retry_after("1e30", now), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("Thu, 22 Oct 2015 07:28:00 GMT", now), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level(" critical "), Some(RiskLevel::Critical));
+        assert_eq!(parse_level("Medium"), Some(RiskLevel::Medium));
+        assert_eq!(parse_level("severe"), None);
+    }
+}
@@ -0,0 +1,111 @@
+use super::{check_response, min_level_from_env, rate_from_env, AlertChannel, ChannelTemplates};
+use crate::action::template::explorer_url;
+use crate::risk::{RiskEvent, RiskLevel};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Slack allows about one message per second per webhook
+const DEFAULT_RATE_PER_MINUTE: u32 = 60;
+
+const DEFAULT_TITLE: &str = "{{level_emoji}} {{risk_type}} ({{risk_level}})";
+const DEFAULT_BODY: &str = "{{description}}
+
+*Score:* {{score}}/100   *Sender:* `{{sender}}`
+*Signals*
+{{signals_text}}";
+
+/// Slack incoming webhook with Block Kit messages
+pub struct SlackChannel {
+    client: reqwest::Client,
+    webhook_url: String,
+    templates: ChannelTemplates,
+    min_level: RiskLevel,
+    rate_per_minute: u32,
+}
+
+impl SlackChannel {
+    pub fn new(client: reqwest::Client, webhook_url: String, templates: ChannelTemplates, min_level: RiskLevel) -> Self {
+        Self {
+            client,
+            webhook_url,
+            templates,
+            min_level,
+            rate_per_minute: DEFAULT_RATE_PER_MINUTE,
+        }
+    }
+
+    /// `SLACK_WEBHOOK_URL`
+    pub fn from_env(client: reqwest::Client) -> Result<Option<Self>> {
+        let Ok(webhook_url) = std::env::var("SLACK_WEBHOOK_URL") else {
+            return Ok(None);
+        };
+        let templates = ChannelTemplates::from_env("SLACK", DEFAULT_TITLE, DEFAULT_BODY)?;
+        let mut channel = Self::new(client, webhook_url, templates, min_level_from_env("SLACK"));
+        channel.rate_per_minute = rate_from_env("SLACK", DEFAULT_RATE_PER_MINUTE);
+        Ok(Some(channel))
+    }
+
+    async fn post_blocks(&self, title: &str, body: &str, context: Option<String>) -> Result<()> {
+        // Block limits: 150 chars of header, 3000 of section text
+        let title: String = title.chars().take(150).collect();
+        let body: String = body.chars().take(3000).collect();
+
+        let mut blocks = vec![
+            serde_json::json!({
+                "type": "header",
+                "text": {"type": "plain_text", "text": title, "emoji": true}
+            }),
+            serde_json::json!({
+                "type": "section",
+                "text": {"type": "mrkdwn", "text": body}
+            }),
+        ];
+        if let Some(context) = context {
+            blocks.push(serde_json::json!({
+                "type": "context",
+                "elements": [{"type": "mrkdwn", "text": context}]
+            }));
+        }
+
+        // `text` is the fallback for notifications
+        let payload = serde_json::json!({ "text": title, "blocks": blocks });
+        let response = self
+            .client
+            .post(&self.webhook_url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+        check_response(response).await
+    }
+}
+
+#[async_trait]
+impl AlertChannel for SlackChannel {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    fn min_level(&self) -> RiskLevel {
+        self.min_level
+    }
+
+    fn rate_per_minute(&self) -> u32 {
+        self.rate_per_minute
+    }
+
+    async fn send(&self, event: &RiskEvent) -> Result<()> {
+        let context = format!(
+            "Checkpoint {} | <{}|{}>",
+            event.checkpoint,
+            explorer_url(&event.tx_digest),
+            event.tx_digest
+        );
+        self.post_blocks(&self.templates.title.render(event), &self.templates.body.render(event), Some(context))
+            .await
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<()> {
+        self.post_blocks(title, body, None).await
+    }
+}
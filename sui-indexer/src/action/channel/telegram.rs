@@ -0,0 +1,96 @@
+use super::{check_response, min_level_from_env, rate_from_env, AlertChannel, ChannelTemplates};
+use crate::risk::{RiskEvent, RiskLevel};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Telegram allows 20 messages per minute to a group
+const DEFAULT_RATE_PER_MINUTE: u32 = 20;
+
+const DEFAULT_TITLE: &str = "{{level_emoji}} {{risk_type}} ({{risk_level}}, score {{score}}/100)";
+const DEFAULT_BODY: &str = "{{description}}
+
+Sender: {{sender}}
+{{signals_text}}
+
+{{explorer_url}}";
+
+/// Telegram bot messages to one chat
+pub struct TelegramChannel {
+    client: reqwest::Client,
+    bot_token: String,
+    chat_id: String,
+    templates: ChannelTemplates,
+    min_level: RiskLevel,
+    rate_per_minute: u32,
+}
+
+impl TelegramChannel {
+    pub fn new(
+        client: reqwest::Client,
+        bot_token: String,
+        chat_id: String,
+        templates: ChannelTemplates,
+        min_level: RiskLevel,
+    ) -> Self {
+        Self {
+            client,
+            bot_token,
+            chat_id,
+            templates,
+            min_level,
+            rate_per_minute: DEFAULT_RATE_PER_MINUTE,
+        }
+    }
+
+    /// `TELEGRAM_BOT_TOKEN` and `TELEGRAM_CHAT_ID`
+    pub fn from_env(client: reqwest::Client) -> Result<Option<Self>> {
+        let (Ok(bot_token), Ok(chat_id)) = (std::env::var("TELEGRAM_BOT_TOKEN"), std::env::var("TELEGRAM_CHAT_ID")) else {
+            return Ok(None);
+        };
+        let templates = ChannelTemplates::from_env("TELEGRAM", DEFAULT_TITLE, DEFAULT_BODY)?;
+        let mut channel = Self::new(client, bot_token, chat_id, templates, min_level_from_env("TELEGRAM"));
+        channel.rate_per_minute = rate_from_env("TELEGRAM", DEFAULT_RATE_PER_MINUTE);
+        Ok(Some(channel))
+    }
+}
+
+#[async_trait]
+impl AlertChannel for TelegramChannel {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn min_level(&self) -> RiskLevel {
+        self.min_level
+    }
+
+    fn rate_per_minute(&self) -> u32 {
+        self.rate_per_minute
+    }
+
+    async fn send(&self, event: &RiskEvent) -> Result<()> {
+        self.send_text(&self.templates.title.render(event), &self.templates.body.render(event))
+            .await
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<()> {
+        // Plain text: no parse mode, so event content needs no escaping
+        let text: String = format!("{}\n\n{}", title, body).chars().take(4096).collect();
+
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let payload = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text,
+            "disable_web_page_preview": true,
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+        check_response(response).await
+    }
+}
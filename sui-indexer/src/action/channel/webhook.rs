@@ -0,0 +1,92 @@
+use super::{
+    check_response, digest_body, digest_title, min_level_from_env, rate_from_env, AlertChannel, ChannelTemplates,
+};
+use crate::risk::{RiskEvent, RiskLevel};
+use anyhow::Result;
+use async_trait::async_trait;
+
+const DEFAULT_RATE_PER_MINUTE: u32 = 120;
+
+const DEFAULT_TITLE: &str = "{{risk_type}} ({{risk_level}})";
+const DEFAULT_BODY: &str = "{{description}}";
+
+/// Generic JSON webhook: rendered title and message plus the full event
+pub struct WebhookChannel {
+    client: reqwest::Client,
+    url: String,
+    templates: ChannelTemplates,
+    min_level: RiskLevel,
+    rate_per_minute: u32,
+}
+
+impl WebhookChannel {
+    pub fn new(client: reqwest::Client, url: String, templates: ChannelTemplates, min_level: RiskLevel) -> Self {
+        Self {
+            client,
+            url,
+            templates,
+            min_level,
+            rate_per_minute: DEFAULT_RATE_PER_MINUTE,
+        }
+    }
+
+    /// `WEBHOOK_URL`
+    pub fn from_env(client: reqwest::Client) -> Result<Option<Self>> {
+        let Ok(url) = std::env::var("WEBHOOK_URL") else {
+            return Ok(None);
+        };
+        let templates = ChannelTemplates::from_env("WEBHOOK", DEFAULT_TITLE, DEFAULT_BODY)?;
+        let mut channel = Self::new(client, url, templates, min_level_from_env("WEBHOOK"));
+        channel.rate_per_minute = rate_from_env("WEBHOOK", DEFAULT_RATE_PER_MINUTE);
+        Ok(Some(channel))
+    }
+
+    async fn post(&self, payload: serde_json::Value) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| e.without_url())?;
+        check_response(response).await
+    }
+}
+
+#[async_trait]
+impl AlertChannel for WebhookChannel {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn min_level(&self) -> RiskLevel {
+        self.min_level
+    }
+
+    fn rate_per_minute(&self) -> u32 {
+        self.rate_per_minute
+    }
+
+    async fn send(&self, event: &RiskEvent) -> Result<()> {
+        self.post(serde_json::json!({
+            "title": self.templates.title.render(event),
+            "message": self.templates.body.render(event),
+            "event": event,
+        }))
+        .await
+    }
+
+    async fn send_text(&self, title: &str, body: &str) -> Result<()> {
+        self.post(serde_json::json!({ "title": title, "message": body })).await
+    }
+
+    /// Digests carry every held event
+    async fn send_digest(&self, events: &[RiskEvent]) -> Result<()> {
+        self.post(serde_json::json!({
+            "title": digest_title(events),
+            "message": digest_body(events),
+            "events": events,
+        }))
+        .await
+    }
+}
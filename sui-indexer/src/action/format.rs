@@ -0,0 +1,110 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Display Formatting: Raw Metrics and Signals Rendered for Humans
+
+use crate::risk::{RiskEvent, Signal};
+
+/// Amount with thousands separators, e.g. `-1,234,567`
+pub fn format_amount(value: f64) -> String {
+    let digits = (value.abs().round() as u128).to_string();
+    let mut res = String::new();
+    for (i, c) in digits.chars().rev().enumerate() {
+        if i > 0 && i % 3 == 0 {
+            res.insert(0, ',');
+        }
+        res.insert(0, c);
+    }
+    if value <= -0.5 {
+        res.insert(0, '-');
+    }
+    res
+}
+
+/// Basis points as a percentage, e.g. `1250` -> `12.50%`
+pub fn format_bps(bps: f64) -> String {
+    format!("{:.2}%", bps / 100.0)
+}
+
+/// Duration in milliseconds, e.g. `90500` -> `1m 30.5s`
+pub fn format_duration_ms(ms: f64) -> String {
+    let ms = ms.max(0.0);
+    if ms < 1000.0 {
+        format!("{}ms", ms.round())
+    } else if ms < 60_000.0 {
+        format!("{:.1}s", ms / 1000.0)
+    } else {
+        let minutes = (ms / 60_000.0).floor();
+        format!("{}m {:.1}s", minutes, (ms - minutes * 60_000.0) / 1000.0)
+    }
+}
+
+/// Format a metric by its key suffix
+///
+/// `_bps` keys are ratios, `_ms` keys durations (except `_start_ms` / `_end_ms`
+/// timestamps), whole values amounts and anything else a two-decimal number.
+pub fn format_metric(key: &str, value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    if key.ends_with("_bps") {
+        format_bps(value)
+    } else if key.ends_with("_start_ms") || key.ends_with("_end_ms") {
+        format!("{}", value as i64)
+    } else if key.ends_with("_ms") {
+        format_duration_ms(value)
+    } else if value.fract() == 0.0 {
+        format_amount(value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+/// One-line signal breakdown, e.g. `trade_count: 12 vs 10 (+10)`
+pub fn format_signal(signal: &Signal) -> String {
+    // Boolean signals carry no meaningful value
+    if signal.value == 1.0 && signal.threshold == 1.0 {
+        return format!("{} (+{})", signal.name, signal.points);
+    }
+    format!(
+        "{}: {} vs {} (+{})",
+        signal.name,
+        format_metric(&signal.name, signal.value),
+        format_metric(&signal.name, signal.threshold),
+        signal.points
+    )
+}
+
+/// Metrics of an event as sorted `key: value` lines
+pub fn format_metrics(event: &RiskEvent) -> Vec<String> {
+    let mut metrics: Vec<(&String, &f64)> = event.metrics.iter().collect();
+    metrics.sort_by(|a, b| a.0.cmp(b.0));
+    metrics
+        .into_iter()
+        .map(|(key, value)| format!("{}: {}", key, format_metric(key, *value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_metric_by_suffix() {
+        assert_eq!(format_metric("volume_a", 1_234_567.0), "1,234,567");
+        assert_eq!(format_metric("net_change_a", -1_500.0), "-1,500");
+        assert_eq!(format_metric("price_impact_bps", 1250.0), "12.50%");
+        assert_eq!(format_metric("window_ms", 90_500.0), "1m 30.5s");
+        assert_eq!(format_metric("drain_duration_ms", 250.0), "250ms");
+        assert_eq!(format_metric("window_start_ms", 1_700_000_000_000.0), "1700000000000");
+        assert_eq!(format_metric("swap_size_z", 6.456), "6.46");
+    }
+
+    #[test]
+    fn test_format_signal() {
+        assert_eq!(format_signal(&Signal::new("trade_count", 12.0, 10.0, 10)), "trade_count: 12 vs 10 (+10)");
+        assert_eq!(
+            format_signal(&Signal::new("volume_to_depth_bps", 12000.0, 10000.0, 20)),
+            "volume_to_depth_bps: 120.00% vs 100.00% (+20)"
+        );
+        assert_eq!(format_signal(&Signal::flag("has_flash_loan", 40)), "has_flash_loan (+40)");
+    }
+}
@@ -1,10 +1,17 @@
 use async_trait::async_trait;
 use anyhow::Result;
+use crate::incident::Incident;
 use crate::risk::RiskEvent;
 
 #[async_trait]
 pub trait ActionHandler: Send + Sync {
     async fn handle(&self, event: &RiskEvent) -> Result<()>;
+
+    /// Handle an incident that opened, escalated or closed
+    /// Defaults to handling the incident's summary event
+    async fn handle_incident(&self, incident: &Incident) -> Result<()> {
+        self.handle(&incident.summary_event()).await
+    }
 }
 
 pub struct ActionPipeline {
@@ -30,6 +37,14 @@ impl ActionPipeline {
             }
         }
     }
+
+    pub async fn run_incident(&self, incident: &Incident) {
+        for handler in &self.handlers {
+            if let Err(e) = handler.handle_incident(incident).await {
+                eprintln!("⚠ Action handler error: {}", e);
+            }
+        }
+    }
 }
 
 impl Default for ActionPipeline {
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use anyhow::Result;
+use crate::action::format::format_signal;
 use crate::action::ActionHandler;
 use crate::risk::RiskEvent;
 
@@ -22,13 +23,17 @@ impl ActionHandler for LogAction {
         };
 
         println!(
-            "{} [{:?}] {:?} detected: {} (tx: {})",
+            "{} [{:?}] {:?} detected (score {}/100): {} (tx: {})",
             level_emoji,
             event.risk_level,
             event.risk_type,
+            event.score,
             event.description,
             &event.tx_digest[..8]
         );
+        for signal in &event.signals {
+            println!("   - {}", format_signal(signal));
+        }
 
         Ok(())
     }
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use anyhow::Result;
 use crate::action::ActionHandler;
+use crate::incident::{Incident, IncidentState};
 use crate::risk::{RiskEvent, RiskLevel};
 
 pub struct MockDefenseAction {
@@ -37,4 +38,13 @@ impl ActionHandler for MockDefenseAction {
 
         Ok(())
     }
+
+    async fn handle_incident(&self, incident: &Incident) -> Result<()> {
+        // Nothing left to defend against once an incident has gone quiet
+        if incident.state == IncidentState::Closed {
+            return Ok(());
+        }
+
+        self.handle(&incident.summary_event()).await
+    }
 }
@@ -1,4 +1,8 @@
 mod handler;
+pub mod format;
+pub mod template;
+pub mod channel;
+pub mod throttle;
 mod log;
 mod alert;
 mod mock_defense;
@@ -0,0 +1,154 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Message Templates: `{{path}}` Placeholders over RiskEvent Fields
+
+use super::format::{format_metric, format_metrics, format_signal};
+use crate::risk::{RiskEvent, RiskLevel};
+
+/// Message template
+///
+/// `{{path}}` is replaced by the value at a dotted path into the event, e.g.
+/// `{{risk_type}}`, `{{details.pool_id}}`, `{{metrics.volume_a}}` or
+/// `{{signals.0.name}}`. Metrics render formatted by their key suffix. Extra
+/// values prepared for messages:
+/// - `explorer_url`: transaction link (`EXPLORER_TX_URL` with `{tx_digest}`)
+/// - `level_emoji`: emoji for the risk level
+/// - `signals_text`, `metrics_text`, `details_text`: one `- ` line per entry
+/// - `secondary_types_text`: merged classifications, comma-separated
+///
+/// Unknown paths render empty.
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+}
+
+impl Template {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self { source: source.into() }
+    }
+
+    pub fn render(&self, event: &RiskEvent) -> String {
+        let context = context(event);
+        let mut output = String::with_capacity(self.source.len());
+        let mut rest = self.source.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                // Unclosed placeholder stays as written
+                output.push_str(&rest[start..]);
+                return output;
+            };
+
+            let path = after[..end].trim();
+            output.push_str(&lookup(&context, path));
+            rest = &after[end + 2..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Transaction link from `EXPLORER_TX_URL`
+pub fn explorer_url(tx_digest: &str) -> String {
+    std::env::var("EXPLORER_TX_URL")
+        .unwrap_or_else(|_| "https://suiscan.xyz/testnet/tx/{tx_digest}".to_string())
+        .replace("{tx_digest}", tx_digest)
+}
+
+pub fn level_emoji(level: RiskLevel) -> &'static str {
+    match level {
+        RiskLevel::Critical => "🚨",
+        RiskLevel::High => "⚠️",
+        RiskLevel::Medium => "⚡",
+        RiskLevel::Low => "ℹ️",
+    }
+}
+
+fn context(event: &RiskEvent) -> serde_json::Value {
+    let mut context = serde_json::to_value(event).unwrap_or_default();
+
+    let lines = |items: Vec<String>| items.iter().map(|i| format!("- {}", i)).collect::<Vec<_>>().join("\n");
+    let mut details: Vec<String> = event.details.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+    details.sort();
+    let secondary: Vec<String> = event.secondary_types.iter().map(|t| format!("{:?}", t)).collect();
+
+    if let Some(object) = context.as_object_mut() {
+        object.insert("explorer_url".to_string(), explorer_url(&event.tx_digest).into());
+        object.insert("level_emoji".to_string(), level_emoji(event.risk_level).into());
+        object.insert("signals_text".to_string(), lines(event.signals.iter().map(format_signal).collect()).into());
+        object.insert("metrics_text".to_string(), lines(format_metrics(event)).into());
+        object.insert("details_text".to_string(), lines(details).into());
+        object.insert("secondary_types_text".to_string(), secondary.join(", ").into());
+    }
+
+    context
+}
+
+fn lookup(context: &serde_json::Value, path: &str) -> String {
+    let mut value = context;
+    for key in path.split('.') {
+        let next = match value {
+            serde_json::Value::Object(map) => map.get(key),
+            serde_json::Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(next) => value = next,
+            None => return String::new(),
+        }
+    }
+
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => match (path.strip_prefix("metrics."), n.as_f64()) {
+            (Some(key), Some(v)) => format_metric(key, v),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::{RiskType, Signal};
+
+    fn event() -> RiskEvent {
+        RiskEvent::new(
+            RiskType::WashTrading,
+            RiskLevel::High,
+            "0xdigest".to_string(),
+            "0xa".to_string(),
+            1000,
+            0,
+            "Wash trading on pool".to_string(),
+        )
+        .with_detail("pool_id", "0xpool")
+        .with_metric("volume_a", 1_500_000.0)
+        .with_metric("net_change_bps", 25.0)
+        .with_signals(vec![Signal::new("trade_count", 12.0, 10.0, 75)])
+    }
+
+    #[test]
+    fn test_render_event_fields() {
+        let template = Template::new(
+            "{{level_emoji}} {{risk_type}} ({{risk_level}}, {{score}}/100) in {{ details.pool_id }}: {{description}}",
+        );
+        assert_eq!(template.render(&event()), "⚠️ WashTrading (High, 75/100) in 0xpool: Wash trading on pool");
+    }
+
+    #[test]
+    fn test_render_metrics_and_lists() {
+        let template = Template::new("{{metrics.volume_a}} / {{metrics.net_change_bps}}\n{{signals_text}}\n{{signals.0.name}}");
+        assert_eq!(template.render(&event()), "1,500,000 / 0.25%\n- trade_count: 12 vs 10 (+75)\ntrade_count");
+    }
+
+    #[test]
+    fn test_unknown_and_unclosed_placeholders() {
+        let template = Template::new("[{{details.missing}}] {{risk_type");
+        assert_eq!(template.render(&event()), "[] {{risk_type");
+    }
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Alert Throttling: Per-Channel Rate Limits and Deduplication Cooldowns
+
+use crate::risk::{RiskEvent, RiskLevel};
+use std::collections::HashMap;
+
+/// Token bucket allowing `per_minute` sends, with bursts up to the same size
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    tokens: f64,
+    refill_per_ms: f64,
+    last_ms: i64,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32) -> Self {
+        let capacity = per_minute.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_ms: capacity / 60_000.0,
+            last_ms: 0,
+        }
+    }
+
+    /// Take a token if one is available
+    pub fn try_acquire(&mut self, now_ms: i64) -> bool {
+        if self.last_ms > 0 {
+            let elapsed = (now_ms - self.last_ms).max(0) as f64;
+            self.tokens = (self.tokens + elapsed * self.refill_per_ms).min(self.capacity);
+        }
+        self.last_ms = now_ms;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Alerts of the same type, sender and pool are one alert for `cooldown_ms`
+pub fn dedup_key(event: &RiskEvent) -> String {
+    let pool = event
+        .details
+        .get("pool_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("-");
+    format!("{:?}:{}:{}", event.risk_type, event.sender, pool)
+}
+
+/// Last send time and level per dedup key
+#[derive(Debug, Clone)]
+pub struct DedupCache {
+    cooldown_ms: i64,
+    last_sent: HashMap<String, (i64, RiskLevel)>,
+}
+
+impl DedupCache {
+    pub fn new(cooldown_ms: i64) -> Self {
+        Self {
+            cooldown_ms,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// True if the key wasn't alerted within the cooldown, or only at a lower
+    /// level (escalations always go out); records the send
+    pub fn check(&mut self, key: &str, level: RiskLevel, now_ms: i64) -> bool {
+        if self.cooldown_ms <= 0 {
+            return true;
+        }
+
+        // Expired keys are dropped so the cache stays bounded by the alert rate
+        let cooldown_ms = self.cooldown_ms;
+        self.last_sent.retain(|_, (sent, _)| now_ms - *sent < cooldown_ms);
+
+        if let Some((_, sent_level)) = self.last_sent.get(key) {
+            if level <= *sent_level {
+                return false;
+            }
+        }
+        self.last_sent.insert(key.to_string(), (now_ms, level));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::{RiskLevel, RiskType};
+
+    #[test]
+    fn test_rate_limiter_refills() {
+        let mut limiter = RateLimiter::new(2);
+        assert!(limiter.try_acquire(1_000));
+        assert!(limiter.try_acquire(1_000));
+        assert!(!limiter.try_acquire(1_000));
+
+        // One token every 30s
+        assert!(!limiter.try_acquire(20_000));
+        assert!(limiter.try_acquire(31_000));
+    }
+
+    #[test]
+    fn test_dedup_cooldown() {
+        let event = RiskEvent::new(
+            RiskType::WashTrading,
+            RiskLevel::High,
+            "0xdigest".to_string(),
+            "0xa".to_string(),
+            1,
+            0,
+            "wash".to_string(),
+        )
+        .with_detail("pool_id", "0xpool");
+        let key = dedup_key(&event);
+        assert_eq!(key, "WashTrading:0xa:0xpool");
+
+        let mut cache = DedupCache::new(60_000);
+        assert!(cache.check(&key, RiskLevel::High, 1_000));
+        assert!(!cache.check(&key, RiskLevel::High, 30_000));
+        assert!(!cache.check(&key, RiskLevel::Medium, 30_000));
+        assert!(cache.check("SandwichAttack:0xa:0xpool", RiskLevel::High, 30_000));
+
+        // Escalation restarts the cooldown at the new level
+        assert!(cache.check(&key, RiskLevel::Critical, 40_000));
+        assert!(!cache.check(&key, RiskLevel::Critical, 90_000));
+        assert!(cache.check(&key, RiskLevel::High, 101_000));
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Constant-Product Pool Math for Replaying Swaps from SwapExecuted Reserves
+
+/// Basis point denominator used by simple_dex
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Fee rate assumed when it cannot be inferred from the event (simple_dex default: 0.3%)
+pub const DEFAULT_FEE_BPS: u64 = 30;
+
+/// Pool reserves at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reserves {
+    pub a: u64,
+    pub b: u64,
+}
+
+impl Reserves {
+    pub fn new(a: u64, b: u64) -> Self {
+        Self { a, b }
+    }
+
+    /// Reconstruct reserves before a swap from the post-swap reserves in `SwapExecuted`
+    ///
+    /// simple_dex joins the full `amount_in` (fee included) into the input reserve
+    /// and splits `amount_out` from the output reserve, so the swap is exactly reversible.
+    pub fn before_swap(after: Reserves, a_to_b: bool, amount_in: u64, amount_out: u64) -> Self {
+        if a_to_b {
+            Self {
+                a: after.a.saturating_sub(amount_in),
+                b: after.b.saturating_add(amount_out),
+            }
+        } else {
+            Self {
+                a: after.a.saturating_add(amount_out),
+                b: after.b.saturating_sub(amount_in),
+            }
+        }
+    }
+
+    /// Reserves after applying a swap
+    pub fn after_swap(&self, a_to_b: bool, amount_in: u64, amount_out: u64) -> Self {
+        if a_to_b {
+            Self {
+                a: self.a.saturating_add(amount_in),
+                b: self.b.saturating_sub(amount_out),
+            }
+        } else {
+            Self {
+                a: self.a.saturating_sub(amount_out),
+                b: self.b.saturating_add(amount_in),
+            }
+        }
+    }
+
+    /// (input reserve, output reserve) for a swap direction
+    fn oriented(&self, a_to_b: bool) -> (u64, u64) {
+        if a_to_b {
+            (self.a, self.b)
+        } else {
+            (self.b, self.a)
+        }
+    }
+}
+
+/// Infer the pool fee rate (basis points) from a swap's `fee_amount`
+pub fn infer_fee_bps(amount_in: u64, fee_amount: u64) -> u64 {
+    if amount_in == 0 {
+        return DEFAULT_FEE_BPS;
+    }
+
+    // Round to nearest: the contract floors `amount_in * fee_rate / 10000`
+    let fee_bps = (fee_amount as u128 * BPS_DENOMINATOR as u128 + amount_in as u128 / 2)
+        / amount_in as u128;
+
+    (fee_bps as u64).min(BPS_DENOMINATOR)
+}
+
+/// Output amount of a constant-product swap, mirroring simple_dex::swap_a_to_b / swap_b_to_a
+pub fn amount_out(reserves: Reserves, a_to_b: bool, amount_in: u64, fee_bps: u64) -> u64 {
+    let (reserve_in, reserve_out) = reserves.oriented(a_to_b);
+    if reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+
+    let amount_in_with_fee =
+        amount_in as u128 * (BPS_DENOMINATOR.saturating_sub(fee_bps)) as u128;
+    let numerator = amount_in_with_fee * reserve_out as u128;
+    let denominator = reserve_in as u128 * BPS_DENOMINATOR as u128 + amount_in_with_fee;
+
+    (numerator / denominator) as u64
+}
+
+/// Value `amount` of one token in terms of the other at the spot price of `reserves`
+///
+/// `from_a = true` converts token A into token B, `false` converts B into A.
+/// Signed so net positions (which may be negative) can be converted directly.
+pub fn spot_value(amount: i128, from_a: bool, reserves: Reserves) -> i128 {
+    let (reserve_from, reserve_to) = if from_a {
+        (reserves.a, reserves.b)
+    } else {
+        (reserves.b, reserves.a)
+    };
+
+    if reserve_from == 0 {
+        return 0;
+    }
+
+    amount * reserve_to as i128 / reserve_from as i128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_out_matches_contract() {
+        let reserves = Reserves::new(1_000_000, 1_000_000);
+        assert_eq!(amount_out(reserves, true, 100_000, 30), 90_661);
+        assert_eq!(amount_out(reserves, false, 10_000, 30), 9_871);
+    }
+
+    #[test]
+    fn test_before_swap_round_trip() {
+        let before = Reserves::new(1_000_000, 1_000_000);
+        let after = before.after_swap(true, 100_000, 90_661);
+        assert_eq!(after, Reserves::new(1_100_000, 909_339));
+        assert_eq!(Reserves::before_swap(after, true, 100_000, 90_661), before);
+    }
+
+    #[test]
+    fn test_infer_fee_bps() {
+        assert_eq!(infer_fee_bps(100_000, 300), 30);
+        assert_eq!(infer_fee_bps(10_001, 30), 30);
+        assert_eq!(infer_fee_bps(0, 0), DEFAULT_FEE_BPS);
+    }
+}
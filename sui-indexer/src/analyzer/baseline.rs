@@ -0,0 +1,381 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Statistical Baseline Detection: Per-Pool EWMA of Swap Size, Price Impact and Frequency
+
+use crate::action::format::{format_amount, format_bps};
+use crate::events::{EventParser, SwapExecuted};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use super::amm::{self, Reserves};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Level thresholds in points; extreme size and impact in a burst award 95
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 40), (RiskLevel::High, 60)],
+    95,
+);
+
+/// Exponentially weighted mean and variance of a stream
+#[derive(Debug, Clone, Default)]
+struct Ewma {
+    mean: f64,
+    variance: f64,
+    count: u64,
+}
+
+impl Ewma {
+    fn update(&mut self, value: f64, alpha: f64) {
+        if self.count == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let diff = value - self.mean;
+            let increment = alpha * diff;
+            self.mean += increment;
+            self.variance = (1.0 - alpha) * (self.variance + diff * increment);
+        }
+        self.count += 1;
+    }
+
+    /// Standard score of `value`, with a floor on the deviation so a perfectly
+    /// steady pool doesn't turn tiny changes into huge scores
+    fn z_score(&self, value: f64, min_std_dev: f64) -> f64 {
+        (value - self.mean) / self.variance.sqrt().max(min_std_dev)
+    }
+}
+
+/// Streaming statistics for one pool
+#[derive(Debug, Default)]
+struct PoolBaseline {
+    /// ln(1 + swap size in TokenA)
+    size: Ewma,
+    /// ln(1 + price impact bps)
+    impact: Ewma,
+    /// Milliseconds between consecutive swaps
+    gap_ms: Ewma,
+    last_swap_ms: Option<i64>,
+    recent_swaps: VecDeque<i64>,
+}
+
+/// Scores of one swap against its pool's baseline
+#[derive(Debug, Clone)]
+struct SwapScore {
+    pool_id: String,
+    sender: String,
+    size_a: u64,
+    size_z: f64,
+    typical_size_a: u64,
+    price_impact: u64,
+    impact_z: f64,
+    typical_impact: u64,
+    swaps_in_window: usize,
+    expected_swaps: f64,
+    samples: u64,
+}
+
+/// Statistical baseline analyzer
+///
+/// Keeps EWMA mean and variance of swap size, price impact and time between
+/// swaps for each pool, and flags swaps that are extreme for that particular
+/// pool instead of against fixed thresholds. Pools are only scored after a
+/// warm-up period.
+pub struct BaselineAnalyzer {
+    pools: Mutex<HashMap<String, PoolBaseline>>,
+    /// EWMA smoothing factor
+    alpha: f64,
+    /// Swaps a pool needs before it is scored
+    warmup_swaps: u64,
+    /// Standard score to flag swap size or price impact
+    min_z_score: f64,
+    /// Standard score considered extreme
+    extreme_z_score: f64,
+    /// Deviation floor in log space (~5%)
+    min_std_dev: f64,
+    /// Window for counting a burst of swaps
+    burst_window_ms: i64,
+    /// Minimum swaps in the window to call it a burst
+    min_burst_swaps: usize,
+    /// Swaps in the window relative to the pool's usual rate
+    burst_multiplier: f64,
+    /// Pools idle longer than this are dropped
+    max_idle_ms: i64,
+}
+
+impl BaselineAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            pools: Mutex::new(HashMap::new()),
+            alpha: 0.05,
+            warmup_swaps: 30,
+            min_z_score: 4.0,
+            extreme_z_score: 6.0,
+            min_std_dev: 0.05,
+            burst_window_ms: 60 * 1000, // 1 minute
+            min_burst_swaps: 5,
+            burst_multiplier: 5.0,
+            max_idle_ms: 7 * 24 * 60 * 60 * 1000, // 7 days
+        }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let events = match &tx.events {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+
+        let swaps: Vec<SwapExecuted> = events
+            .data
+            .iter()
+            .filter(|e| e.type_.name.as_str() == "SwapExecuted")
+            .filter_map(SwapExecuted::from_event)
+            .collect();
+
+        if swaps.is_empty() {
+            return Vec::new();
+        }
+
+        self.process_swaps(swaps, context)
+    }
+
+    /// Score swaps against their pools' baselines, then fold them in
+    fn process_swaps(&self, swaps: Vec<SwapExecuted>, context: &DetectionContext) -> Vec<RiskEvent> {
+        let mut pools = self.pools.lock().unwrap();
+        let now = context.timestamp_ms;
+
+        pools.retain(|_, pool| pool.last_swap_ms.is_some_and(|ts| now - ts <= self.max_idle_ms));
+
+        // Strongest score per pool within the transaction
+        let mut flagged: Vec<(SwapScore, u32, Vec<&'static str>)> = Vec::new();
+
+        for swap in swaps {
+            let pool_id = swap.pool_id.to_string();
+            let pool = pools.entry(pool_id.clone()).or_default();
+
+            let before = Reserves::before_swap(
+                Reserves::new(swap.reserve_a, swap.reserve_b),
+                swap.token_in,
+                swap.amount_in,
+                swap.amount_out,
+            );
+            let size_a = if swap.token_in {
+                swap.amount_in
+            } else {
+                amm::spot_value(swap.amount_in as i128, false, before).max(0) as u64
+            };
+            let size = (size_a as f64).ln_1p();
+            let impact = (swap.price_impact as f64).ln_1p();
+
+            while pool.recent_swaps.front().is_some_and(|ts| now - ts > self.burst_window_ms) {
+                pool.recent_swaps.pop_front();
+            }
+            pool.recent_swaps.push_back(now);
+
+            if pool.size.count >= self.warmup_swaps {
+                let expected_swaps = if pool.gap_ms.mean > 0.0 {
+                    self.burst_window_ms as f64 / pool.gap_ms.mean
+                } else {
+                    f64::INFINITY
+                };
+
+                let score = SwapScore {
+                    pool_id: pool_id.clone(),
+                    sender: swap.sender.to_string(),
+                    size_a,
+                    size_z: pool.size.z_score(size, self.min_std_dev),
+                    typical_size_a: pool.size.mean.exp_m1().max(0.0) as u64,
+                    price_impact: swap.price_impact,
+                    impact_z: pool.impact.z_score(impact, self.min_std_dev),
+                    typical_impact: pool.impact.mean.exp_m1().max(0.0) as u64,
+                    swaps_in_window: pool.recent_swaps.len(),
+                    expected_swaps,
+                    samples: pool.size.count,
+                };
+
+                let signals = self.score(&score);
+                let risk_score = Signal::total(&signals);
+                if !signals.is_empty() {
+                    match flagged.iter_mut().find(|(s, _, _)| s.pool_id == pool_id) {
+                        Some(existing) if existing.1 >= risk_score => {}
+                        Some(existing) => *existing = (score, risk_score, signals),
+                        None => flagged.push((score, risk_score, signals)),
+                    }
+                }
+            }
+
+            // Fold the swap into the baseline after scoring it
+            pool.size.update(size, self.alpha);
+            pool.impact.update(impact, self.alpha);
+            if let Some(last) = pool.last_swap_ms {
+                pool.gap_ms.update((now - last) as f64, self.alpha);
+            }
+            pool.last_swap_ms = Some(now);
+        }
+
+        flagged
+            .into_iter()
+            .filter(|(_, risk_score, _)| *risk_score >= 25)
+            .map(|(score, risk_score, signals)| self.create_baseline_event(&score, risk_score, signals, context))
+            .collect()
+    }
+
+    fn score(&self, score: &SwapScore) -> Vec<Signal> {
+        let mut signals = Vec::new();
+
+        if score.size_z >= self.extreme_z_score {
+            signals.push(Signal::new("extreme_swap_size", score.size_z, self.extreme_z_score, 35));
+        } else if score.size_z >= self.min_z_score {
+            signals.push(Signal::new("unusual_swap_size", score.size_z, self.min_z_score, 25));
+        }
+
+        if score.impact_z >= self.extreme_z_score {
+            signals.push(Signal::new("extreme_price_impact", score.impact_z, self.extreme_z_score, 35));
+        } else if score.impact_z >= self.min_z_score {
+            signals.push(Signal::new("unusual_price_impact", score.impact_z, self.min_z_score, 25));
+        }
+
+        let burst_threshold = (self.min_burst_swaps as f64).max(score.expected_swaps * self.burst_multiplier);
+        if score.swaps_in_window as f64 >= burst_threshold {
+            signals.push(Signal::new("trade_burst", score.swaps_in_window as f64, burst_threshold, 25));
+        }
+
+        signals
+    }
+
+    /// Create risk event from a pool-relative anomaly
+    fn create_baseline_event(
+        &self,
+        score: &SwapScore,
+        risk_score: u32,
+        signals: Vec<Signal>,
+        context: &DetectionContext,
+    ) -> RiskEvent {
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let description = format!(
+            "Pool baseline anomaly on {}: swap of {} A (typical {}, z={:.1}), impact {} (typical {}, z={:.1}), {} swaps in last {}s",
+            score.pool_id,
+            format_amount(score.size_a as f64),
+            format_amount(score.typical_size_a as f64),
+            score.size_z,
+            format_bps(score.price_impact as f64),
+            format_bps(score.typical_impact as f64),
+            score.impact_z,
+            score.swaps_in_window,
+            self.burst_window_ms / 1000
+        );
+
+        let event = RiskEvent::new(
+            RiskType::StatisticalAnomaly,
+            risk_level,
+            context.tx_digest.clone(),
+            score.sender.clone(),
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("pool_id", serde_json::json!(score.pool_id))
+        .with_metric("swap_size_a", score.size_a as f64)
+        .with_metric("typical_swap_size_a", score.typical_size_a as f64)
+        .with_metric("swap_size_z", score.size_z)
+        .with_metric("price_impact_bps", score.price_impact as f64)
+        .with_metric("typical_price_impact_bps", score.typical_impact as f64)
+        .with_metric("price_impact_z", score.impact_z)
+        .with_metric("swaps_in_window", score.swaps_in_window as f64)
+        .with_metric("baseline_samples", score.samples as f64);
+
+        // No gap baseline yet means no expected rate
+        let event = if score.expected_swaps.is_finite() {
+            event.with_metric("expected_swaps_in_window", score.expected_swaps)
+        } else {
+            event
+        };
+        event.with_scaled_signals(signals, &SCORE_SCALE)
+    }
+}
+
+impl Default for BaselineAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+
+    fn swap(amount_in: u64, price_impact: u64) -> SwapExecuted {
+        SwapExecuted {
+            pool_id: ObjectID::from_str("0x1").unwrap(),
+            sender: SuiAddress::from_str("0xa").unwrap(),
+            token_in: true,
+            amount_in,
+            amount_out: amount_in * 9 / 10,
+            fee_amount: amount_in * 3 / 1000,
+            reserve_a: 100_000_000,
+            reserve_b: 100_000_000,
+            price_impact,
+        }
+    }
+
+    fn context(timestamp_ms: i64) -> DetectionContext {
+        DetectionContext::new(format!("tx{}", timestamp_ms), "0xa".to_string(), 1000, timestamp_ms)
+    }
+
+    /// Alternating ordinary swaps one minute apart
+    fn warm_up(analyzer: &BaselineAnalyzer, count: i64) -> i64 {
+        let mut ts = 0;
+        for i in 0..count {
+            ts = i * 60_000;
+            let (amount, impact) = if i % 2 == 0 { (9_000, 90) } else { (11_000, 110) };
+            assert!(analyzer.process_swaps(vec![swap(amount, impact)], &context(ts)).is_empty());
+        }
+        ts
+    }
+
+    #[test]
+    fn test_new_pool_not_scored_during_warmup() {
+        let analyzer = BaselineAnalyzer::new();
+        warm_up(&analyzer, 10);
+
+        let events = analyzer.process_swaps(vec![swap(5_000_000, 3000)], &context(700_000));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_swap_extreme_for_pool_flagged() {
+        let analyzer = BaselineAnalyzer::new();
+        let ts = warm_up(&analyzer, 40);
+
+        let events = analyzer.process_swaps(vec![swap(500_000, 3000)], &context(ts + 60_000));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].risk_type, RiskType::StatisticalAnomaly);
+        assert_eq!(events[0].risk_level, RiskLevel::High);
+        let names: Vec<&str> = events[0].signals.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["extreme_swap_size", "extreme_price_impact"]);
+    }
+
+    #[test]
+    fn test_trade_burst_flagged() {
+        let analyzer = BaselineAnalyzer::new();
+        let ts = warm_up(&analyzer, 40);
+
+        // Ordinary-sized swaps, but seven within a few seconds on a pool that trades
+        // once a minute (the burst itself pulls the frequency baseline down)
+        let mut events = Vec::new();
+        for i in 1..=7 {
+            events.extend(analyzer.process_swaps(vec![swap(10_000, 100)], &context(ts + 60_000 + i * 1000)));
+        }
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].signals.len(), 1);
+        assert_eq!(events[0].signals[0].name, "trade_burst");
+    }
+}
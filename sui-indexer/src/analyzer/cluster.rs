@@ -0,0 +1,292 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Address Clustering via Gas Sponsorship and Funding Transfers
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use sui_types::object::Owner;
+use sui_types::transaction::TransactionDataAPI;
+
+/// Why two addresses are considered controlled by the same actor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum LinkReason {
+    /// Sender's gas was paid by another address (sponsor / shared gas owner)
+    GasSponsor,
+    /// Sender transferred objects (e.g. coins) to another address
+    FundingTransfer,
+}
+
+/// Observed relationship between two addresses
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressLink {
+    pub from: String,
+    pub to: String,
+    pub reason: LinkReason,
+    pub timestamp_ms: i64,
+}
+
+/// Set of addresses linked to a seed address
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AddressCluster {
+    pub members: Vec<String>,
+    pub links: Vec<AddressLink>,
+}
+
+impl AddressCluster {
+    pub fn contains(&self, address: &str) -> bool {
+        self.members.iter().any(|m| m == address)
+    }
+
+    /// True if the cluster spans more than the seed address
+    pub fn is_multi_address(&self) -> bool {
+        self.members.len() > 1
+    }
+}
+
+/// Link graph of recently related addresses
+///
+/// Hubs (exchanges, public gas stations) touch many unrelated addresses, so an
+/// address with more than `max_fanout` neighbours is never traversed through.
+pub struct AddressClusterer {
+    // Adjacency list keyed by address (each link stored under both ends)
+    links: HashMap<String, Vec<AddressLink>>,
+    // Links older than this are ignored and pruned
+    link_ttl_ms: i64,
+    // Maximum distinct neighbours before an address is treated as a hub
+    max_fanout: usize,
+    // Upper bound on returned cluster size
+    max_cluster_size: usize,
+    // Time between full sweeps of expired links
+    prune_interval_ms: i64,
+    last_prune_ms: i64,
+}
+
+impl AddressClusterer {
+    pub fn new() -> Self {
+        Self {
+            links: HashMap::new(),
+            link_ttl_ms: 24 * 60 * 60 * 1000, // 24 hours
+            max_fanout: 20,
+            max_cluster_size: 50,
+            prune_interval_ms: 60 * 1000, // 1 minute
+            last_prune_ms: 0,
+        }
+    }
+
+    /// Record sponsor and funding links observed in a transaction
+    ///
+    /// Fed with every checkpoint transaction, not only those reaching the
+    /// detectors: the transfer funding a second attacker address usually
+    /// doesn't touch the monitored packages.
+    pub fn record_transaction(&mut self, tx: &ExecutedTransaction, timestamp_ms: i64) {
+        let sender = tx.transaction.sender();
+        let gas_owner = tx.transaction.gas_data().owner;
+
+        if gas_owner != sender {
+            self.add_link(
+                &sender.to_string(),
+                &gas_owner.to_string(),
+                LinkReason::GasSponsor,
+                timestamp_ms,
+            );
+        }
+
+        // Objects that end up owned by another address were sent there by the sender
+        let mut recipients = HashSet::new();
+        for (_, owner, _) in tx.effects.all_changed_objects() {
+            if let Owner::AddressOwner(recipient) = owner {
+                if recipient != sender && recipient != gas_owner {
+                    recipients.insert(recipient);
+                }
+            }
+        }
+
+        for recipient in recipients {
+            self.add_link(
+                &sender.to_string(),
+                &recipient.to_string(),
+                LinkReason::FundingTransfer,
+                timestamp_ms,
+            );
+        }
+
+        self.prune_if_due(timestamp_ms);
+    }
+
+    /// Add a link between two addresses
+    pub fn add_link(&mut self, from: &str, to: &str, reason: LinkReason, timestamp_ms: i64) {
+        if from == to {
+            return;
+        }
+
+        let link = AddressLink {
+            from: from.to_string(),
+            to: to.to_string(),
+            reason,
+            timestamp_ms,
+        };
+
+        for key in [from, to] {
+            let entry = self.links.entry(key.to_string()).or_default();
+            // Refresh an existing link instead of duplicating it
+            if let Some(existing) = entry.iter_mut().find(|l| {
+                l.reason == reason && l.from == link.from && l.to == link.to
+            }) {
+                existing.timestamp_ms = existing.timestamp_ms.max(timestamp_ms);
+            } else {
+                entry.push(link.clone());
+            }
+        }
+    }
+
+    /// Breadth-first walk of recent links starting at `address`
+    pub fn cluster_of(&self, address: &str, now_ms: i64) -> AddressCluster {
+        let mut members = vec![address.to_string()];
+        let mut visited: HashSet<String> = HashSet::from([address.to_string()]);
+        let mut cluster_links = Vec::new();
+        let mut queue = VecDeque::from([address.to_string()]);
+
+        while let Some(current) = queue.pop_front() {
+            let neighbours = self.recent_links(&current, now_ms);
+
+            // Don't expand through hubs (the seed itself is always expanded)
+            if current != address && self.distinct_neighbours(&neighbours, &current) > self.max_fanout {
+                continue;
+            }
+
+            for link in neighbours {
+                let other = if link.from == current { &link.to } else { &link.from };
+                if visited.contains(other) {
+                    continue;
+                }
+                if members.len() >= self.max_cluster_size {
+                    return AddressCluster {
+                        members,
+                        links: cluster_links,
+                    };
+                }
+
+                visited.insert(other.clone());
+                members.push(other.clone());
+                cluster_links.push(link.clone());
+                queue.push_back(other.clone());
+            }
+        }
+
+        AddressCluster {
+            members,
+            links: cluster_links,
+        }
+    }
+
+    /// Check whether two addresses belong to the same cluster
+    pub fn are_linked(&self, a: &str, b: &str, now_ms: i64) -> bool {
+        a == b || self.cluster_of(a, now_ms).contains(b)
+    }
+
+    /// Drop links older than the TTL
+    pub fn prune(&mut self, now_ms: i64) {
+        self.last_prune_ms = now_ms;
+        let ttl = self.link_ttl_ms;
+        self.links.retain(|_, links| {
+            links.retain(|l| now_ms - l.timestamp_ms <= ttl);
+            !links.is_empty()
+        });
+    }
+
+    /// Sweep at most once per `prune_interval_ms`; expired links are already
+    /// ignored by lookups, so sweeping only bounds memory
+    fn prune_if_due(&mut self, now_ms: i64) {
+        if now_ms - self.last_prune_ms >= self.prune_interval_ms {
+            self.prune(now_ms);
+        }
+    }
+
+    /// Number of addresses with recorded links (for monitoring)
+    pub fn tracked_addresses(&self) -> usize {
+        self.links.len()
+    }
+
+    fn recent_links(&self, address: &str, now_ms: i64) -> Vec<&AddressLink> {
+        self.links
+            .get(address)
+            .map(|links| {
+                links
+                    .iter()
+                    .filter(|l| now_ms - l.timestamp_ms <= self.link_ttl_ms)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn distinct_neighbours(&self, links: &[&AddressLink], address: &str) -> usize {
+        links
+            .iter()
+            .map(|l| if l.from == address { &l.to } else { &l.from })
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+impl Default for AddressClusterer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_through_shared_sponsor() {
+        let mut clusterer = AddressClusterer::new();
+        clusterer.add_link("front", "sponsor", LinkReason::GasSponsor, 1000);
+        clusterer.add_link("back", "sponsor", LinkReason::GasSponsor, 1000);
+
+        let cluster = clusterer.cluster_of("back", 2000);
+        assert!(cluster.contains("front"));
+        assert!(cluster.contains("sponsor"));
+        assert!(cluster.is_multi_address());
+        assert!(clusterer.are_linked("front", "back", 2000));
+    }
+
+    #[test]
+    fn test_hub_is_not_traversed() {
+        let mut clusterer = AddressClusterer::new();
+        for i in 0..=clusterer.max_fanout {
+            clusterer.add_link(&format!("user{}", i), "exchange", LinkReason::FundingTransfer, 1000);
+        }
+
+        assert!(!clusterer.are_linked("user0", "user1", 2000));
+    }
+
+    #[test]
+    fn test_links_expire() {
+        let mut clusterer = AddressClusterer::new();
+        clusterer.add_link("a", "b", LinkReason::FundingTransfer, 0);
+
+        let later = clusterer.link_ttl_ms + 1;
+        assert!(!clusterer.are_linked("a", "b", later));
+
+        clusterer.prune(later);
+        assert_eq!(clusterer.tracked_addresses(), 0);
+    }
+
+    #[test]
+    fn test_prune_runs_on_interval() {
+        let mut clusterer = AddressClusterer::new();
+        let ttl = clusterer.link_ttl_ms;
+        clusterer.add_link("a", "b", LinkReason::FundingTransfer, 0);
+        clusterer.prune_if_due(ttl);
+        assert_eq!(clusterer.tracked_addresses(), 2);
+
+        // Expired, but the last sweep was too recent
+        clusterer.prune_if_due(ttl + 1);
+        assert_eq!(clusterer.tracked_addresses(), 2);
+
+        clusterer.prune_if_due(ttl + clusterer.prune_interval_ms);
+        assert_eq!(clusterer.tracked_addresses(), 0);
+    }
+}
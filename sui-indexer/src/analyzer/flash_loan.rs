@@ -3,9 +3,16 @@
 
 use sui_types::full_checkpoint_content::ExecutedTransaction;
 use std::collections::HashSet;
-use crate::risk::{RiskEvent, RiskLevel, RiskType, DetectionContext};
+use crate::risk::{RiskEvent, RiskLevel, RiskType, DetectionContext, ScoreScale, Signal};
 use crate::events::{FlashLoanTaken, SwapExecuted, EventParser};
 
+/// Level thresholds in points; every signal at its top tier awards 115
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+    115,
+);
+
 /// Flash loan information extracted from events
 #[derive(Debug, Clone)]
 struct FlashLoanInfo {
@@ -76,57 +83,72 @@ impl FlashLoanAnalyzer {
         let total_price_impact = self.calculate_total_price_impact(&swaps);
         let max_single_impact = self.calculate_max_price_impact(&swaps);
 
+        let total_borrowed: u64 = flash_loan_info.iter().map(|fl| fl.amount).sum();
+        let max_borrowed = flash_loan_info.iter().map(|fl| fl.amount).max().unwrap_or(0);
+
         // Step 4: Calculate risk score using weighted multi-signal approach
-        let mut risk_score = 0u32;
+        let mut signals = Vec::new();
 
         // Circular trading is highly suspicious
         if circular_trading {
-            risk_score += 30;
+            signals.push(Signal::flag("circular_trading", 30));
         }
 
         // Multiple swaps indicate complex arbitrage
         if swaps.len() >= 3 {
-            risk_score += 20;
+            signals.push(Signal::new("swap_count", swaps.len() as f64, 3.0, 20));
         } else if swaps.len() >= 2 {
-            risk_score += 10;
+            signals.push(Signal::new("swap_count", swaps.len() as f64, 2.0, 10));
         }
 
         // High cumulative price impact
         if total_price_impact > self.high_price_impact_threshold * 2 {
-            risk_score += 25;
+            signals.push(Signal::new(
+                "total_price_impact_bps",
+                total_price_impact as f64,
+                (self.high_price_impact_threshold * 2) as f64,
+                25,
+            ));
         } else if total_price_impact > self.high_price_impact_threshold {
-            risk_score += 15;
+            signals.push(Signal::new(
+                "total_price_impact_bps",
+                total_price_impact as f64,
+                self.high_price_impact_threshold as f64,
+                15,
+            ));
         }
 
         // Single high-impact swap
         if max_single_impact > self.price_impact_threshold {
-            risk_score += 15;
+            signals.push(Signal::new(
+                "max_price_impact_bps",
+                max_single_impact as f64,
+                self.price_impact_threshold as f64,
+                15,
+            ));
         }
 
         // Multi-pool arbitrage
         if unique_pools >= 3 {
-            risk_score += 15;
+            signals.push(Signal::new("unique_pools", unique_pools as f64, 3.0, 15));
         } else if unique_pools >= 2 {
-            risk_score += 10;
+            signals.push(Signal::new("unique_pools", unique_pools as f64, 2.0, 10));
         }
 
         // Large flash loan amount (relative)
-        if flash_loan_info.iter().any(|fl| fl.amount > 1_000_000_000) {
-            risk_score += 10;
+        if max_borrowed > 1_000_000_000 {
+            signals.push(Signal::new("flash_loan_amount", max_borrowed as f64, 1_000_000_000.0, 10));
         }
 
+        let risk_score = Signal::total(&signals);
+
         // Step 5: Classify risk level based on score
         if risk_score < 30 {
             // Below threshold, likely legitimate
             return None;
         }
 
-        let risk_level = match risk_score {
-            30..=49 => RiskLevel::Low,
-            50..=69 => RiskLevel::Medium,
-            70..=84 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
+        let risk_level = SCORE_SCALE.level(risk_score);
 
         // Step 6: Create detailed risk event
         let description = format!(
@@ -153,16 +175,14 @@ impl FlashLoanAnalyzer {
 
         // Add detailed metrics
         event = event
-            .with_detail("flash_loan_count", serde_json::json!(flash_loan_info.len()))
-            .with_detail("total_borrowed", serde_json::json!(
-                format_currency(flash_loan_info.iter().map(|fl| fl.amount).sum::<u64>())
-            ))
-            .with_detail("swap_count", serde_json::json!(swaps.len()))
-            .with_detail("unique_pools", serde_json::json!(unique_pools))
             .with_detail("circular_trading", serde_json::json!(circular_trading))
-            .with_detail("total_price_impact", serde_json::json!(format_bps(total_price_impact)))
-            .with_detail("max_price_impact", serde_json::json!(format_bps(max_single_impact)))
-            .with_detail("risk_score", serde_json::json!(risk_score));
+            .with_metric("flash_loan_count", flash_loan_info.len() as f64)
+            .with_metric("total_borrowed", total_borrowed as f64)
+            .with_metric("swap_count", swaps.len() as f64)
+            .with_metric("unique_pools", unique_pools as f64)
+            .with_metric("total_price_impact_bps", total_price_impact as f64)
+            .with_metric("max_price_impact_bps", max_single_impact as f64)
+            .with_scaled_signals(signals, &SCORE_SCALE);
 
         Some(event)
     }
@@ -278,22 +298,6 @@ impl FlashLoanAnalyzer {
     }
 }
 
-fn format_currency(amount: u64) -> String {
-    let s = amount.to_string();
-    let mut res = String::new();
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            res.insert(0, ',');
-        }
-        res.insert(0, c);
-    }
-    res
-}
-
-fn format_bps(bps: u64) -> String {
-    format!("{:.2}%", bps as f64 / 100.0)
-}
-
 impl Default for FlashLoanAnalyzer {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,326 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Gas Price Priority Detection: Priority-Gas Bidding on Contended Shared Objects
+
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use sui_types::base_types::ObjectID;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use sui_types::transaction::{CallArg, ObjectArg, TransactionDataAPI, TransactionKind};
+use sui_types::{SUI_CLOCK_OBJECT_ID, SUI_RANDOMNESS_STATE_OBJECT_ID, SUI_SYSTEM_STATE_OBJECT_ID};
+
+/// Level thresholds in points; a 10x outlier on a swap awards 70
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70)],
+    70,
+);
+
+/// Gas price of a transaction compared with its shared objects' baselines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasObservation {
+    pub gas_price: u64,
+    /// Shared objects touched (system objects excluded)
+    pub shared_objects: Vec<String>,
+    /// Object whose baseline the price deviates from most
+    pub baseline_object: Option<String>,
+    /// Median gas price of that object's baseline
+    pub baseline_median: Option<u64>,
+    /// Gas price relative to the baseline median (bps, 10000 = equal)
+    pub ratio_bps: u64,
+    /// Samples behind the baseline
+    pub sample_count: usize,
+    pub is_outlier: bool,
+}
+
+#[derive(Debug, Default)]
+struct GasBaselineState {
+    // Shared object → (timestamp_ms, gas_price) of recent transactions touching it
+    objects: HashMap<String, VecDeque<(i64, u64)>>,
+    // Observations by tx digest so several detectors share one result per tx
+    observed: HashMap<String, GasObservation>,
+    observed_order: VecDeque<String>,
+}
+
+/// Rolling gas price baselines per shared object
+///
+/// Shared between detectors: `GasPriceAnalyzer` reports outliers and
+/// `SandwichAnalyzer` uses them in its scoring. Each transaction is recorded
+/// once; later calls for the same digest return the cached observation.
+pub struct GasPriceMonitor {
+    state: Mutex<GasBaselineState>,
+    /// Prices kept per shared object
+    baseline_size: usize,
+    /// Maximum age of a baseline sample
+    baseline_age_ms: i64,
+    /// Samples required before an object's baseline is trusted
+    min_samples: usize,
+    /// Gas price relative to median to count as an outlier (bps)
+    min_outlier_ratio_bps: u64,
+    /// Observations cached by digest
+    max_cached_observations: usize,
+}
+
+impl GasPriceMonitor {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(GasBaselineState::default()),
+            baseline_size: 200,
+            baseline_age_ms: 60 * 60 * 1000, // 1 hour
+            min_samples: 20,
+            min_outlier_ratio_bps: 20000, // 2x median
+            max_cached_observations: 1024,
+        }
+    }
+
+    /// Compare the transaction's gas price with its shared objects' baselines, then record it
+    pub fn observe(&self, tx: &ExecutedTransaction, context: &DetectionContext) -> GasObservation {
+        let gas_price = tx.transaction.gas_data().price;
+        let shared_objects = shared_objects(tx);
+        self.observe_price(&context.tx_digest, gas_price, shared_objects, context.timestamp_ms)
+    }
+
+    fn observe_price(
+        &self,
+        tx_digest: &str,
+        gas_price: u64,
+        shared_objects: Vec<String>,
+        timestamp_ms: i64,
+    ) -> GasObservation {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(observation) = state.observed.get(tx_digest) {
+            return observation.clone();
+        }
+
+        let mut observation = GasObservation {
+            gas_price,
+            shared_objects: shared_objects.clone(),
+            baseline_object: None,
+            baseline_median: None,
+            ratio_bps: 0,
+            sample_count: 0,
+            is_outlier: false,
+        };
+
+        // Evaluate against baselines before this transaction joins them
+        for object_id in &shared_objects {
+            let Some(samples) = state.objects.get_mut(object_id) else {
+                continue;
+            };
+            while samples.front().is_some_and(|(ts, _)| timestamp_ms - ts > self.baseline_age_ms) {
+                samples.pop_front();
+            }
+            if samples.len() < self.min_samples {
+                continue;
+            }
+
+            let mut prices: Vec<u64> = samples.iter().map(|(_, p)| *p).collect();
+            prices.sort_unstable();
+            let median = prices[prices.len() / 2].max(1);
+            let p90 = prices[prices.len() * 9 / 10];
+            let ratio_bps = (gas_price as u128 * 10000 / median as u128).min(u64::MAX as u128) as u64;
+
+            if ratio_bps > observation.ratio_bps {
+                observation.baseline_object = Some(object_id.clone());
+                observation.baseline_median = Some(median);
+                observation.ratio_bps = ratio_bps;
+                observation.sample_count = prices.len();
+            }
+            if ratio_bps >= self.min_outlier_ratio_bps && gas_price > p90 {
+                observation.is_outlier = true;
+            }
+        }
+
+        for object_id in shared_objects {
+            let samples = state.objects.entry(object_id).or_default();
+            if samples.len() >= self.baseline_size {
+                samples.pop_front();
+            }
+            samples.push_back((timestamp_ms, gas_price));
+        }
+
+        if state.observed_order.len() >= self.max_cached_observations {
+            if let Some(oldest) = state.observed_order.pop_front() {
+                state.observed.remove(&oldest);
+            }
+        }
+        state.observed_order.push_back(tx_digest.to_string());
+        state.observed.insert(tx_digest.to_string(), observation.clone());
+
+        observation
+    }
+}
+
+impl Default for GasPriceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shared objects in the PTB inputs, excluding system objects every tx may touch
+fn shared_objects(tx: &ExecutedTransaction) -> Vec<String> {
+    let mut objects = Vec::new();
+
+    if let TransactionKind::ProgrammableTransaction(pt) = tx.transaction.kind() {
+        for input in pt.inputs.iter() {
+            if let CallArg::Object(ObjectArg::SharedObject { id, .. }) = input {
+                if !is_system_object(id) {
+                    let id = id.to_string();
+                    if !objects.contains(&id) {
+                        objects.push(id);
+                    }
+                }
+            }
+        }
+    }
+
+    objects
+}
+
+fn is_system_object(id: &ObjectID) -> bool {
+    *id == SUI_CLOCK_OBJECT_ID || *id == SUI_SYSTEM_STATE_OBJECT_ID || *id == SUI_RANDOMNESS_STATE_OBJECT_ID
+}
+
+/// Gas price priority analyzer
+///
+/// Flags transactions bidding far above the usual gas price for the shared
+/// objects they touch, which buys ordering priority on those objects (MEV,
+/// front-running, sandwiching).
+pub struct GasPriceAnalyzer {
+    monitor: Arc<GasPriceMonitor>,
+}
+
+impl GasPriceAnalyzer {
+    pub fn new(monitor: Arc<GasPriceMonitor>) -> Self {
+        Self { monitor }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let observation = self.monitor.observe(tx, context);
+        let has_swap = tx
+            .events
+            .as_ref()
+            .is_some_and(|e| e.data.iter().any(|ev| ev.type_.name.as_str() == "SwapExecuted"));
+
+        self.create_priority_gas_event(&observation, has_swap, context)
+    }
+
+    /// Create risk event for a priority-gas outlier
+    fn create_priority_gas_event(
+        &self,
+        observation: &GasObservation,
+        has_swap: bool,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        if !observation.is_outlier {
+            return None;
+        }
+
+        // Outlier against an established baseline
+        let ratio = observation.ratio_bps as f64;
+        let mut signals = vec![Signal::flag("gas_price_outlier", 30)];
+
+        if observation.ratio_bps >= 100000 {
+            signals.push(Signal::new("gas_price_ratio_bps", ratio, 100000.0, 30)); // >= 10x
+        } else if observation.ratio_bps >= 50000 {
+            signals.push(Signal::new("gas_price_ratio_bps", ratio, 50000.0, 20)); // >= 5x
+        }
+
+        // Ordering priority on a swap is the classic MEV use
+        if has_swap {
+            signals.push(Signal::flag("swap_priority", 10));
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let description = format!(
+            "Priority gas bidding: gas price {} is {:.1}x the median {} of {} recent txs on shared object {}",
+            observation.gas_price,
+            observation.ratio_bps as f64 / 10000.0,
+            observation.baseline_median.unwrap_or_default(),
+            observation.sample_count,
+            observation.baseline_object.as_deref().unwrap_or_default()
+        );
+
+        let event = RiskEvent::new(
+            RiskType::PriorityGasBidding,
+            risk_level,
+            context.tx_digest.clone(),
+            context.sender.clone(),
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("baseline_object", serde_json::json!(observation.baseline_object))
+        .with_detail("shared_objects", serde_json::json!(observation.shared_objects))
+        .with_detail("has_swap", serde_json::json!(has_swap))
+        .with_metric("gas_price", observation.gas_price as f64)
+        .with_metric("baseline_median", observation.baseline_median.unwrap_or_default() as f64)
+        .with_metric("gas_price_ratio_bps", ratio)
+        .with_metric("baseline_samples", observation.sample_count as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE);
+
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warm_up(monitor: &GasPriceMonitor, object: &str) {
+        for i in 0..20 {
+            monitor.observe_price(&format!("warm{}", i), 1000, vec![object.to_string()], i * 1000);
+        }
+    }
+
+    #[test]
+    fn test_outlier_needs_baseline() {
+        let monitor = GasPriceMonitor::new();
+        let cold = monitor.observe_price("cold", 50_000, vec!["0xpool".to_string()], 0);
+        assert!(!cold.is_outlier);
+
+        warm_up(&monitor, "0xpool");
+        let normal = monitor.observe_price("normal", 1000, vec!["0xpool".to_string()], 30_000);
+        assert!(!normal.is_outlier);
+        assert_eq!(normal.ratio_bps, 10000);
+
+        let bid = monitor.observe_price("bid", 5000, vec!["0xpool".to_string()], 31_000);
+        assert!(bid.is_outlier);
+        assert_eq!(bid.ratio_bps, 50000);
+        assert_eq!(bid.baseline_median, Some(1000));
+    }
+
+    #[test]
+    fn test_observation_cached_per_digest() {
+        let monitor = GasPriceMonitor::new();
+        warm_up(&monitor, "0xpool");
+
+        let first = monitor.observe_price("bid", 5000, vec!["0xpool".to_string()], 30_000);
+        let second = monitor.observe_price("bid", 5000, vec!["0xpool".to_string()], 30_000);
+        assert_eq!(first, second);
+        assert_eq!(monitor.state.lock().unwrap().objects["0xpool"].len(), 21);
+    }
+
+    #[test]
+    fn test_priority_gas_event_scoring() {
+        let monitor = Arc::new(GasPriceMonitor::new());
+        warm_up(&monitor, "0xpool");
+        let observation = monitor.observe_price("bid", 10_000, vec!["0xpool".to_string()], 30_000);
+
+        let analyzer = GasPriceAnalyzer::new(monitor);
+        let context = DetectionContext::new("bid".to_string(), "0xa".to_string(), 1000, 30_000);
+        let event = analyzer.create_priority_gas_event(&observation, true, &context).unwrap();
+
+        assert_eq!(event.risk_type, RiskType::PriorityGasBidding);
+        assert_eq!(event.risk_level, RiskLevel::High);
+    }
+}
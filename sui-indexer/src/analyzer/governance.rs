@@ -0,0 +1,517 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Governance Monitoring: Package Upgrades and Admin Capability Changes
+
+use crate::constants::SIMULATION_PACKAGE_ID;
+use crate::models::{EsChangedObject, EsFlattener, EsRemovedObject};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use sui_types::base_types::ObjectID;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use sui_types::transaction::{Command, TransactionData, TransactionDataAPI, TransactionKind};
+
+/// Level thresholds in points; an upgrade and a dependent publish with every
+/// kind of capability change award 180
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 30), (RiskLevel::High, 50), (RiskLevel::Critical, 80)],
+    180,
+);
+
+/// Packages and capability objects whose governance actions are monitored
+#[derive(Debug, Clone, Default)]
+pub struct GovernanceWatchlist {
+    /// Watched package IDs
+    packages: HashSet<String>,
+    /// Watched capability object ID → label (e.g. `UPGRADE_CAP`)
+    capabilities: HashMap<String, String>,
+}
+
+impl GovernanceWatchlist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from the environment
+    ///
+    /// - `SIMULATION_PACKAGE_ID` plus comma-separated `WATCHED_PACKAGES`
+    /// - `UPGRADE_CAP`, every `*_TREASURY_CAP`, plus comma-separated `WATCHED_CAPABILITIES`
+    ///
+    /// Fails on IDs that aren't valid object IDs, so a typo can't silently disable monitoring.
+    pub fn from_env() -> Result<Self> {
+        let mut watchlist = Self::new().with_package(SIMULATION_PACKAGE_ID)?;
+
+        if let Ok(packages) = std::env::var("WATCHED_PACKAGES") {
+            for package in packages.split(',').filter(|p| !p.trim().is_empty()) {
+                watchlist = watchlist
+                    .with_package(package)
+                    .context("Invalid package in WATCHED_PACKAGES")?;
+            }
+        }
+
+        for (key, value) in std::env::vars() {
+            if key == "UPGRADE_CAP" || key.ends_with("_TREASURY_CAP") {
+                watchlist = watchlist
+                    .with_capability(&value, &key)
+                    .with_context(|| format!("Invalid object ID in {}", key))?;
+            }
+        }
+
+        if let Ok(capabilities) = std::env::var("WATCHED_CAPABILITIES") {
+            for capability in capabilities.split(',').filter(|c| !c.trim().is_empty()) {
+                watchlist = watchlist
+                    .with_capability(capability, "WATCHED_CAPABILITY")
+                    .context("Invalid object ID in WATCHED_CAPABILITIES")?;
+            }
+        }
+
+        Ok(watchlist)
+    }
+
+    pub fn with_package(mut self, package_id: &str) -> Result<Self> {
+        self.packages.insert(normalize_id(package_id)?);
+        Ok(self)
+    }
+
+    pub fn with_capability(mut self, object_id: &str, label: &str) -> Result<Self> {
+        self.capabilities.insert(normalize_id(object_id)?, label.to_string());
+        Ok(self)
+    }
+
+    pub fn is_watched_package(&self, package_id: &str) -> bool {
+        normalize_id(package_id).is_ok_and(|id| self.packages.contains(&id))
+    }
+
+    pub fn capability_label(&self, object_id: &str) -> Option<&str> {
+        let id = normalize_id(object_id).ok()?;
+        self.capabilities.get(&id).map(|l| l.as_str())
+    }
+
+    /// True if the transaction calls, publishes, upgrades or touches anything watched
+    ///
+    /// Governance transactions and aborted calls usually emit no events from the
+    /// watched package, so they must be selected for detection independently of
+    /// the event filter.
+    pub fn touches(&self, tx: &ExecutedTransaction) -> bool {
+        if calls_watched_package(&tx.transaction, self) || !find_package_actions(&tx.transaction, self).is_empty() {
+            return true;
+        }
+
+        let changed = tx
+            .effects
+            .all_changed_objects()
+            .into_iter()
+            .any(|((id, _, _), _, _)| self.capability_label(&id.to_string()).is_some());
+        let removed = tx
+            .effects
+            .all_removed_objects()
+            .into_iter()
+            .any(|((id, _, _), _)| self.capability_label(&id.to_string()).is_some());
+
+        changed || removed
+    }
+}
+
+/// `Upgrade` / `Publish` PTB command touching a watched package
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PackageAction {
+    /// Upgrade of a watched package
+    Upgrade { package_id: String, command_index: usize },
+    /// New package depending on a watched package
+    Publish { dependencies: Vec<String>, command_index: usize },
+}
+
+/// Change to a watched capability object
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CapabilityChange {
+    /// Owner differs before and after the transaction
+    OwnershipChanged { object_id: String, label: String, from: Option<String>, to: Option<String> },
+    /// Capability used or mutated in place
+    Mutated { object_id: String, label: String, owner: Option<String> },
+    /// Capability wrapped into another object or deleted
+    Removed { object_id: String, label: String, remove_kind: String },
+}
+
+/// Governance analyzer
+///
+/// Flags:
+/// 1. `Upgrade` commands on watched packages (rogue upgrade)
+/// 2. `Publish` commands depending on watched packages
+/// 3. Ownership changes, mutations and removals of watched capabilities
+///    (UpgradeCap, TreasuryCaps), which indicate use or compromise of admin keys
+pub struct GovernanceAnalyzer {
+    watchlist: GovernanceWatchlist,
+}
+
+impl GovernanceAnalyzer {
+    pub fn new(watchlist: GovernanceWatchlist) -> Self {
+        Self { watchlist }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let package_actions = find_package_actions(&tx.transaction, &self.watchlist);
+        let effects = EsFlattener::extract_effects(&tx.effects);
+        let capability_changes = find_capability_changes(
+            &effects.all_changed_objects,
+            &effects.all_removed_objects,
+            &self.watchlist,
+        );
+
+        if package_actions.is_empty() && capability_changes.is_empty() {
+            return None;
+        }
+
+        Some(self.create_governance_event(&package_actions, &capability_changes, context))
+    }
+
+    /// Create risk event from governance findings
+    fn create_governance_event(
+        &self,
+        package_actions: &[PackageAction],
+        capability_changes: &[CapabilityChange],
+        context: &DetectionContext,
+    ) -> RiskEvent {
+        let mut signals = Vec::new();
+        let mut upgrades = Vec::new();
+        let mut publishes = Vec::new();
+        let mut capabilities = Vec::new();
+
+        for action in package_actions {
+            match action {
+                PackageAction::Upgrade { package_id, command_index } => {
+                    upgrades.push(serde_json::json!({
+                        "package_id": package_id,
+                        "command_index": command_index,
+                    }));
+                }
+                PackageAction::Publish { dependencies, command_index } => {
+                    publishes.push(serde_json::json!({
+                        "watched_dependencies": dependencies,
+                        "command_index": command_index,
+                    }));
+                }
+            }
+        }
+
+        // Watched package code replaced
+        if !upgrades.is_empty() {
+            signals.push(Signal::new("package_upgrade", upgrades.len() as f64, 1.0, 50));
+        }
+
+        // New code built against watched package
+        if !publishes.is_empty() {
+            signals.push(Signal::new("dependent_publish", publishes.len() as f64, 1.0, 20));
+        }
+
+        let mut ownership_changed = false;
+        let mut removed = false;
+        let mut mutated = false;
+        for change in capability_changes {
+            match change {
+                CapabilityChange::OwnershipChanged { object_id, label, from, to } => {
+                    ownership_changed = true;
+                    capabilities.push(serde_json::json!({
+                        "object_id": object_id,
+                        "label": label,
+                        "change": "ownership_changed",
+                        "from_owner": from,
+                        "to_owner": to,
+                    }));
+                }
+                CapabilityChange::Removed { object_id, label, remove_kind } => {
+                    removed = true;
+                    capabilities.push(serde_json::json!({
+                        "object_id": object_id,
+                        "label": label,
+                        "change": "removed",
+                        "remove_kind": remove_kind,
+                    }));
+                }
+                CapabilityChange::Mutated { object_id, label, owner } => {
+                    mutated = true;
+                    capabilities.push(serde_json::json!({
+                        "object_id": object_id,
+                        "label": label,
+                        "change": "mutated",
+                        "owner": owner,
+                    }));
+                }
+            }
+        }
+
+        // Capability transferred, shared or frozen
+        if ownership_changed {
+            signals.push(Signal::flag("capability_ownership_changed", 50));
+        }
+
+        // Capability wrapped or destroyed
+        if removed {
+            signals.push(Signal::flag("capability_removed", 40));
+        }
+
+        // Capability used (e.g. minting with a TreasuryCap)
+        if mutated {
+            signals.push(Signal::flag("capability_mutated", 20));
+        }
+
+        let risk_score = Signal::total(&signals);
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let description = format!(
+            "Governance change: {} upgrade(s), {} dependent publish(es), {} capability change(s) by {}",
+            upgrades.len(),
+            publishes.len(),
+            capabilities.len(),
+            context.sender
+        );
+
+        RiskEvent::new(
+            RiskType::GovernanceChange,
+            risk_level,
+            context.tx_digest.clone(),
+            context.sender.clone(),
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("upgrades", serde_json::json!(upgrades))
+        .with_detail("publishes", serde_json::json!(publishes))
+        .with_detail("capabilities", serde_json::json!(capabilities))
+        .with_metric("upgrade_count", upgrades.len() as f64)
+        .with_metric("publish_count", publishes.len() as f64)
+        .with_metric("capability_change_count", capabilities.len() as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE)
+    }
+}
+
+/// Collect `Upgrade` and `Publish` commands that touch watched packages
+/// `MoveCall` into a watched package
+fn calls_watched_package(transaction_data: &TransactionData, watchlist: &GovernanceWatchlist) -> bool {
+    match transaction_data.kind() {
+        TransactionKind::ProgrammableTransaction(pt) => pt.commands.iter().any(|cmd| {
+            matches!(cmd, Command::MoveCall(call) if watchlist.is_watched_package(&call.package.to_string()))
+        }),
+        _ => false,
+    }
+}
+
+fn find_package_actions(
+    transaction_data: &TransactionData,
+    watchlist: &GovernanceWatchlist,
+) -> Vec<PackageAction> {
+    let mut actions = Vec::new();
+
+    if let TransactionKind::ProgrammableTransaction(pt) = transaction_data.kind() {
+        for (command_index, cmd) in pt.commands.iter().enumerate() {
+            match cmd {
+                Command::Upgrade(_, _, package_id, _) => {
+                    let package_id = package_id.to_string();
+                    if watchlist.is_watched_package(&package_id) {
+                        actions.push(PackageAction::Upgrade { package_id, command_index });
+                    }
+                }
+                Command::Publish(_, dependencies) => {
+                    let dependencies: Vec<String> = dependencies
+                        .iter()
+                        .map(|d| d.to_string())
+                        .filter(|d| watchlist.is_watched_package(d))
+                        .collect();
+                    if !dependencies.is_empty() {
+                        actions.push(PackageAction::Publish { dependencies, command_index });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    actions
+}
+
+/// Compare input and output owners of watched capabilities
+fn find_capability_changes(
+    changed_objects: &[EsChangedObject],
+    removed_objects: &[EsRemovedObject],
+    watchlist: &GovernanceWatchlist,
+) -> Vec<CapabilityChange> {
+    let mut changes = Vec::new();
+
+    for object in changed_objects {
+        let Some(label) = watchlist.capability_label(&object.object_id) else {
+            continue;
+        };
+
+        if object.input_owner != object.output_owner {
+            changes.push(CapabilityChange::OwnershipChanged {
+                object_id: object.object_id.clone(),
+                label: label.to_string(),
+                from: object.input_owner.clone(),
+                to: object.output_owner.clone(),
+            });
+        } else {
+            changes.push(CapabilityChange::Mutated {
+                object_id: object.object_id.clone(),
+                label: label.to_string(),
+                owner: object.output_owner.clone(),
+            });
+        }
+    }
+
+    for object in removed_objects {
+        if let Some(label) = watchlist.capability_label(&object.object_id) {
+            changes.push(CapabilityChange::Removed {
+                object_id: object.object_id.clone(),
+                label: label.to_string(),
+                remove_kind: object.remove_kind.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Canonical full-length `0x` form so env values and on-chain IDs compare equal
+///
+/// Accepts short IDs (`0x2`) and a missing `0x` prefix.
+fn normalize_id(id: &str) -> Result<String> {
+    let id = id.trim();
+    let literal = if id.starts_with("0x") || id.starts_with("0X") {
+        format!("0x{}", &id[2..])
+    } else {
+        format!("0x{}", id)
+    };
+    let object_id = ObjectID::from_hex_literal(&literal)
+        .with_context(|| format!("Invalid object ID: {}", id))?;
+    Ok(object_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAP: &str = "0x89b117e2d81fcfbb963a5f1f378315066f83a8285122f4e0e56ac115f45ef27e";
+
+    fn watchlist() -> GovernanceWatchlist {
+        GovernanceWatchlist::new()
+            .with_package(SIMULATION_PACKAGE_ID)
+            .unwrap()
+            .with_capability(CAP, "UPGRADE_CAP")
+            .unwrap()
+    }
+
+    fn changed(object_id: &str, input_owner: &str, output_owner: &str) -> EsChangedObject {
+        EsChangedObject {
+            object_id: object_id.to_string(),
+            input_version: None,
+            input_digest: None,
+            input_owner: Some(input_owner.to_string()),
+            input_state_type: "Exist".to_string(),
+            output_version: Some(2),
+            output_digest: Some("digest".to_string()),
+            output_owner: Some(output_owner.to_string()),
+            output_state_type: "Mutate".to_string(),
+            id_operation: "Mutated".to_string(),
+        }
+    }
+
+    fn context() -> DetectionContext {
+        DetectionContext::new("tx".to_string(), "admin".to_string(), 1000, 1_000_000)
+    }
+
+    #[test]
+    fn test_capability_transfer_detected() {
+        let changes = find_capability_changes(
+            &[changed(CAP, "0xadmin", "0xattacker"), changed("0x1234", "0xa", "0xb")],
+            &[],
+            &watchlist(),
+        );
+
+        assert_eq!(
+            changes,
+            vec![CapabilityChange::OwnershipChanged {
+                object_id: CAP.to_string(),
+                label: "UPGRADE_CAP".to_string(),
+                from: Some("0xadmin".to_string()),
+                to: Some("0xattacker".to_string()),
+            }]
+        );
+
+        let event = GovernanceAnalyzer::new(watchlist()).create_governance_event(&[], &changes, &context());
+        assert_eq!(event.risk_type, RiskType::GovernanceChange);
+        assert_eq!(event.risk_level, RiskLevel::High);
+    }
+
+    #[test]
+    fn test_capability_mutation_and_removal() {
+        let removed = EsRemovedObject {
+            object_id: CAP.to_uppercase().replace("0X", ""),
+            version: 3,
+            digest: "digest".to_string(),
+            remove_kind: "Delete".to_string(),
+        };
+
+        let changes = find_capability_changes(&[changed(CAP, "0xadmin", "0xadmin")], &[removed], &watchlist());
+        assert!(matches!(changes[0], CapabilityChange::Mutated { .. }));
+        assert!(matches!(changes[1], CapabilityChange::Removed { .. }));
+    }
+
+    #[test]
+    fn test_upgrade_with_cap_transfer_is_critical() {
+        let actions = vec![PackageAction::Upgrade {
+            package_id: SIMULATION_PACKAGE_ID.to_string(),
+            command_index: 1,
+        }];
+        let changes = find_capability_changes(&[changed(CAP, "0xadmin", "0xattacker")], &[], &watchlist());
+
+        let event = GovernanceAnalyzer::new(watchlist()).create_governance_event(&actions, &changes, &context());
+        assert_eq!(event.risk_level, RiskLevel::Critical);
+        let names: Vec<&str> = event.signals.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["package_upgrade", "capability_ownership_changed"]);
+        assert_eq!(event.risk_score(), 100);
+        assert_eq!(event.score, 88);
+    }
+
+    #[test]
+    fn test_calls_into_watched_packages_selected() {
+        use sui_types::base_types::{random_object_ref, SuiAddress};
+        use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+        use sui_types::Identifier;
+
+        let call = |package: &str| {
+            let mut builder = ProgrammableTransactionBuilder::new();
+            builder.programmable_move_call(
+                ObjectID::from_hex_literal(package).unwrap(),
+                Identifier::new("vault").unwrap(),
+                Identifier::new("withdraw").unwrap(),
+                vec![],
+                vec![],
+            );
+            TransactionData::new_programmable(SuiAddress::ZERO, vec![random_object_ref()], builder.finish(), 1_000_000, 1_000)
+        };
+
+        // A package from WATCHED_PACKAGES, not the simulation package
+        let watchlist = watchlist().with_package("0xabc").unwrap();
+        assert!(calls_watched_package(&call("0xabc"), &watchlist));
+        assert!(!calls_watched_package(&call("0xdef"), &watchlist));
+        assert!(find_package_actions(&call("0xabc"), &watchlist).is_empty());
+    }
+
+    #[test]
+    fn test_ids_canonicalized_and_invalid_rejected() {
+        let watchlist = GovernanceWatchlist::new().with_package("0x2").unwrap();
+        assert!(watchlist.is_watched_package(
+            "0x0000000000000000000000000000000000000000000000000000000000000002"
+        ));
+        assert!(watchlist.is_watched_package("2"));
+        assert!(!watchlist.is_watched_package("0x3"));
+
+        assert!(GovernanceWatchlist::new().with_package("0xnot-an-id").is_err());
+        assert!(GovernanceWatchlist::new().with_capability(&format!("{}00", CAP), "UPGRADE_CAP").is_err());
+    }
+}
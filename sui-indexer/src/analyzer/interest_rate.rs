@@ -0,0 +1,434 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Interest Rate Manipulation Detection: Utilization-Driven Rate Spikes and Index Jumps
+
+use crate::action::format::format_bps;
+use crate::events::{AccrueInterestEvent, ParsedEvents};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Level thresholds in points; a dominant 200% spike with an index jump awards 110
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+    110,
+);
+
+/// Rates and borrow index are scaled by 1e9 in the lending market
+const RATE_PRECISION: u128 = 1_000_000_000;
+
+/// Market state reported by the last `AccrueInterestEvent`
+#[derive(Debug, Clone)]
+struct AccrualSnapshot {
+    tx_digest: String,
+    borrow_rate: u64,
+    supply_rate: u64,
+    total_borrows: u64,
+    borrow_index: u64,
+    /// Seconds, as emitted by the market
+    timestamp: u64,
+}
+
+/// Borrow or supply that changes utilization after an accrual
+#[derive(Debug, Clone)]
+struct MarketAction {
+    tx_digest: String,
+    kind: &'static str,
+    actor: String,
+    amount: u64,
+}
+
+#[derive(Debug, Default)]
+struct MarketState {
+    last_accrual: Option<AccrualSnapshot>,
+    // Actions since the last accrual; the next accrual's rate reflects them
+    pending_actions: Vec<MarketAction>,
+}
+
+/// Findings for one market in one transaction
+#[derive(Debug)]
+struct RateAnomaly {
+    market_id: String,
+    previous: AccrualSnapshot,
+    current: AccrualSnapshot,
+    elapsed_secs: u64,
+    rate_change_bps: u64,
+    rate_spike: Option<MarketAction>,
+    action_share_bps: u64,
+    expected_index: u128,
+    index_deviation_bps: u64,
+}
+
+/// Interest rate manipulation analyzer
+///
+/// Tracks `borrow_rate`, `supply_rate`, `total_borrows` and `borrow_index` per
+/// market from `AccrueInterestEvent`. Every market action accrues first, so the
+/// rate in an accrual reflects the utilization left by the actions since the
+/// previous accrual. Flags:
+/// 1. Rate spikes attributable to a single large borrow or supply
+/// 2. `borrow_index` values that don't match `index * rate * elapsed`
+pub struct InterestRateAnalyzer {
+    markets: Mutex<HashMap<String, MarketState>>,
+    /// Minimum relative borrow rate change (bps)
+    min_rate_change_bps: u64,
+    /// Minimum single action size relative to total borrows (bps)
+    min_action_share_bps: u64,
+    /// Maximum tolerated index deviation relative to the previous index (bps)
+    max_index_deviation_bps: u64,
+    /// Actions kept per market between accruals
+    max_pending_actions: usize,
+}
+
+impl InterestRateAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            markets: Mutex::new(HashMap::new()),
+            min_rate_change_bps: 5000,  // Rate moved by 50%
+            min_action_share_bps: 1000, // Action >= 10% of total borrows
+            max_index_deviation_bps: 1, // 0.01% of index
+            max_pending_actions: 64,
+        }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let parsed = ParsedEvents::from_transaction(tx);
+        self.analyze_events(&parsed, context)
+    }
+
+    fn analyze_events(&self, parsed: &ParsedEvents, context: &DetectionContext) -> Vec<RiskEvent> {
+        if parsed.accruals.is_empty() && parsed.borrows.is_empty() && parsed.supplies.is_empty() {
+            return Vec::new();
+        }
+
+        let mut markets = self.markets.lock().unwrap();
+        let mut anomalies = Vec::new();
+
+        // Each action accrues before it executes, so accruals precede actions
+        // on the same market within a transaction
+        for accrual in &parsed.accruals {
+            let state = markets.entry(accrual.market_id.to_string()).or_default();
+            let current = snapshot(accrual, &context.tx_digest);
+
+            if let Some(previous) = state.last_accrual.take() {
+                if let Some(anomaly) = self.evaluate(
+                    accrual.market_id.to_string(),
+                    previous,
+                    current.clone(),
+                    &state.pending_actions,
+                ) {
+                    anomalies.push(anomaly);
+                }
+            }
+
+            state.last_accrual = Some(current);
+            state.pending_actions.clear();
+        }
+
+        let actions = parsed
+            .borrows
+            .iter()
+            .map(|b| (b.market_id.to_string(), "borrow", b.borrower.to_string(), b.borrow_amount))
+            .chain(
+                parsed
+                    .supplies
+                    .iter()
+                    .map(|s| (s.market_id.to_string(), "supply", s.supplier.to_string(), s.amount)),
+            );
+
+        for (market_id, kind, actor, amount) in actions {
+            let state = markets.entry(market_id).or_default();
+            if state.pending_actions.len() >= self.max_pending_actions {
+                state.pending_actions.remove(0);
+            }
+            state.pending_actions.push(MarketAction {
+                tx_digest: context.tx_digest.clone(),
+                kind,
+                actor,
+                amount,
+            });
+        }
+
+        anomalies
+            .into_iter()
+            .filter_map(|a| self.create_rate_event(a, context))
+            .collect()
+    }
+
+    /// Compare an accrual with the previous one on the same market
+    fn evaluate(
+        &self,
+        market_id: String,
+        previous: AccrualSnapshot,
+        current: AccrualSnapshot,
+        pending_actions: &[MarketAction],
+    ) -> Option<RateAnomaly> {
+        // Out of order or duplicate accrual
+        if current.timestamp <= previous.timestamp {
+            return None;
+        }
+        let elapsed_secs = current.timestamp - previous.timestamp;
+
+        // Index grows by the rate in effect over the elapsed period, which is
+        // the rate reported by this accrual
+        let prev_index = previous.borrow_index as u128;
+        let expected_index = prev_index
+            + prev_index * current.borrow_rate as u128 * elapsed_secs as u128 / RATE_PRECISION;
+        let index_deviation = (current.borrow_index as u128).abs_diff(expected_index);
+        let index_deviation_bps = ratio_bps(index_deviation, prev_index);
+
+        let rate_change = current.borrow_rate.abs_diff(previous.borrow_rate) as u128;
+        let rate_change_bps = ratio_bps(rate_change, previous.borrow_rate as u128);
+
+        // Largest single action since the previous accrual
+        let largest_action = pending_actions.iter().max_by_key(|a| a.amount).cloned();
+        let action_share_bps = largest_action
+            .as_ref()
+            .map(|a| ratio_bps(a.amount as u128, previous.total_borrows as u128))
+            .unwrap_or(0);
+
+        let rate_spike = largest_action.filter(|_| {
+            rate_change_bps >= self.min_rate_change_bps && action_share_bps >= self.min_action_share_bps
+        });
+        let index_jump = index_deviation_bps >= self.max_index_deviation_bps;
+
+        if rate_spike.is_none() && !index_jump {
+            return None;
+        }
+
+        Some(RateAnomaly {
+            market_id,
+            previous,
+            current,
+            elapsed_secs,
+            rate_change_bps,
+            rate_spike,
+            action_share_bps,
+            expected_index,
+            index_deviation_bps,
+        })
+    }
+
+    /// Create risk event from a market anomaly
+    fn create_rate_event(&self, anomaly: RateAnomaly, context: &DetectionContext) -> Option<RiskEvent> {
+        let mut signals = Vec::new();
+
+        if anomaly.rate_spike.is_some() {
+            let rate_change = anomaly.rate_change_bps as f64;
+            signals.push(Signal::new("rate_spike", rate_change, self.min_rate_change_bps as f64, 30));
+
+            if anomaly.rate_change_bps >= 20000 {
+                signals.push(Signal::new("rate_change_bps", rate_change, 20000.0, 20));
+            } else if anomaly.rate_change_bps >= 10000 {
+                signals.push(Signal::new("rate_change_bps", rate_change, 10000.0, 10));
+            }
+
+            // Single action dominating the market's borrows
+            if anomaly.action_share_bps >= 5000 {
+                signals.push(Signal::new("action_share_bps", anomaly.action_share_bps as f64, 5000.0, 10));
+            }
+        }
+
+        if anomaly.index_deviation_bps >= self.max_index_deviation_bps {
+            signals.push(Signal::new(
+                "borrow_index_jump",
+                anomaly.index_deviation_bps as f64,
+                self.max_index_deviation_bps as f64,
+                50,
+            ));
+        }
+
+        let risk_score = Signal::total(&signals);
+        if risk_score < 30 {
+            return None;
+        }
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        // Attribute to whoever moved utilization, if known
+        let sender = anomaly
+            .rate_spike
+            .as_ref()
+            .map(|a| a.actor.clone())
+            .unwrap_or_else(|| context.sender.clone());
+
+        let description = format!(
+            "Interest rate anomaly on market {}: borrow rate {} -> {} ({}) over {}s, borrow index deviation {}",
+            anomaly.market_id,
+            anomaly.previous.borrow_rate,
+            anomaly.current.borrow_rate,
+            format_bps(anomaly.rate_change_bps as f64),
+            anomaly.elapsed_secs,
+            format_bps(anomaly.index_deviation_bps as f64)
+        );
+
+        let trigger = anomaly.rate_spike.as_ref().map(|a| serde_json::json!({
+            "tx_digest": a.tx_digest,
+            "kind": a.kind,
+            "actor": a.actor,
+            "amount": a.amount,
+            "share_of_borrows_bps": anomaly.action_share_bps,
+        }));
+
+        let event = RiskEvent::new(
+            RiskType::InterestRateManipulation,
+            risk_level,
+            context.tx_digest.clone(),
+            sender,
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("market_id", serde_json::json!(anomaly.market_id))
+        .with_detail("previous_accrual_tx", serde_json::json!(anomaly.previous.tx_digest))
+        .with_detail("borrow_index_before", serde_json::json!(anomaly.previous.borrow_index))
+        .with_detail("borrow_index_after", serde_json::json!(anomaly.current.borrow_index))
+        .with_detail("borrow_index_expected", serde_json::json!(anomaly.expected_index.to_string()))
+        .with_detail("trigger_action", serde_json::json!(trigger))
+        .with_metric("elapsed_ms", (anomaly.elapsed_secs * 1000) as f64)
+        .with_metric("borrow_rate_before", anomaly.previous.borrow_rate as f64)
+        .with_metric("borrow_rate_after", anomaly.current.borrow_rate as f64)
+        .with_metric("borrow_rate_change_bps", anomaly.rate_change_bps as f64)
+        .with_metric("supply_rate_before", anomaly.previous.supply_rate as f64)
+        .with_metric("supply_rate_after", anomaly.current.supply_rate as f64)
+        .with_metric("total_borrows_before", anomaly.previous.total_borrows as f64)
+        .with_metric("total_borrows_after", anomaly.current.total_borrows as f64)
+        .with_metric("borrow_index_deviation_bps", anomaly.index_deviation_bps as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE);
+
+        Some(event)
+    }
+}
+
+fn snapshot(accrual: &AccrueInterestEvent, tx_digest: &str) -> AccrualSnapshot {
+    AccrualSnapshot {
+        tx_digest: tx_digest.to_string(),
+        borrow_rate: accrual.borrow_rate,
+        supply_rate: accrual.supply_rate,
+        total_borrows: accrual.total_borrows,
+        borrow_index: accrual.borrow_index,
+        timestamp: accrual.timestamp,
+    }
+}
+
+/// `part / whole` in basis points (`u64::MAX` if whole is zero and part isn't)
+fn ratio_bps(part: u128, whole: u128) -> u64 {
+    if whole == 0 {
+        return if part == 0 { 0 } else { u64::MAX };
+    }
+    (part * 10000 / whole).min(u64::MAX as u128) as u64
+}
+
+impl Default for InterestRateAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::BorrowEvent;
+    use std::str::FromStr;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+
+    const INDEX: u64 = 1_000_000_000;
+
+    fn accrual(borrow_rate: u64, total_borrows: u64, borrow_index: u64, timestamp: u64) -> AccrueInterestEvent {
+        AccrueInterestEvent {
+            market_id: ObjectID::from_str("0x1").unwrap(),
+            borrow_rate,
+            supply_rate: borrow_rate / 2,
+            total_borrows,
+            total_reserves: 0,
+            borrow_index,
+            timestamp,
+        }
+    }
+
+    fn borrow(amount: u64) -> BorrowEvent {
+        BorrowEvent {
+            market_id: ObjectID::from_str("0x1").unwrap(),
+            borrower: SuiAddress::from_str("0xa").unwrap(),
+            position_id: ObjectID::from_str("0x100").unwrap(),
+            borrow_amount: amount,
+            collateral_value: amount * 2,
+            oracle_price: 1_000_000_000,
+            health_factor: 15000,
+            total_borrows: 0,
+            timestamp: 0,
+        }
+    }
+
+    fn context(tx_digest: &str) -> DetectionContext {
+        DetectionContext::new(tx_digest.to_string(), "0xb".to_string(), 1000, 1_000_000)
+    }
+
+    /// Index after `secs` at `rate`, as the market computes it
+    fn grown(index: u64, rate: u64, secs: u64) -> u64 {
+        index + (index as u128 * rate as u128 * secs as u128 / RATE_PRECISION) as u64
+    }
+
+    #[test]
+    fn test_steady_market_not_flagged() {
+        let analyzer = InterestRateAnalyzer::new();
+        let first = ParsedEvents { accruals: vec![accrual(10, 1_000_000, INDEX, 1000)], ..Default::default() };
+        let second = ParsedEvents {
+            accruals: vec![accrual(11, 1_000_000, grown(INDEX, 11, 60), 1060)],
+            ..Default::default()
+        };
+
+        assert!(analyzer.analyze_events(&first, &context("tx1")).is_empty());
+        assert!(analyzer.analyze_events(&second, &context("tx2")).is_empty());
+    }
+
+    #[test]
+    fn test_rate_spike_after_large_borrow() {
+        let analyzer = InterestRateAnalyzer::new();
+
+        // Accrual then a borrow of half the outstanding borrows
+        let borrow_tx = ParsedEvents {
+            accruals: vec![accrual(10, 1_000_000, INDEX, 1000)],
+            borrows: vec![borrow(500_000)],
+            ..Default::default()
+        };
+        assert!(analyzer.analyze_events(&borrow_tx, &context("borrow")).is_empty());
+
+        // Next accrual reports the tripled rate
+        let next_tx = ParsedEvents {
+            accruals: vec![accrual(30, 1_500_000, grown(INDEX, 30, 60), 1060)],
+            ..Default::default()
+        };
+        let events = analyzer.analyze_events(&next_tx, &context("next"));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].risk_type, RiskType::InterestRateManipulation);
+        assert_eq!(events[0].risk_level, RiskLevel::Medium);
+        assert_eq!(events[0].sender, SuiAddress::from_str("0xa").unwrap().to_string());
+        assert_eq!(events[0].signals[0].name, "rate_spike");
+        assert!(events[0].signals.iter().all(|s| s.name != "borrow_index_jump"));
+    }
+
+    #[test]
+    fn test_borrow_index_jump_flagged() {
+        let analyzer = InterestRateAnalyzer::new();
+        let first = ParsedEvents { accruals: vec![accrual(10, 1_000_000, INDEX, 1000)], ..Default::default() };
+        // Index grew 0.1% in a minute at a rate that implies ~0.00006%
+        let second = ParsedEvents {
+            accruals: vec![accrual(10, 1_000_000, INDEX + 1_000_000, 1060)],
+            ..Default::default()
+        };
+
+        analyzer.analyze_events(&first, &context("tx1"));
+        let events = analyzer.analyze_events(&second, &context("tx2"));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].signals.len(), 1);
+        assert_eq!(events[0].signals[0].name, "borrow_index_jump");
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Just-In-Time Liquidity Detection: Add → Large Swap → Remove on the Same Pool
+
+use crate::action::format::format_amount;
+use crate::events::{EventParser, LiquidityAdded, LiquidityRemoved, SwapExecuted};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use super::amm::{self, Reserves};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Level thresholds in points; every signal at its top tier awards 70
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70)],
+    70,
+);
+
+/// DEX event relevant to JIT detection, in emission order
+#[derive(Debug, Clone)]
+enum DexActivity {
+    Added(LiquidityAdded),
+    Swap(SwapExecuted),
+    Removed(LiquidityRemoved),
+}
+
+/// Swap observed while a liquidity position was open
+#[derive(Debug, Clone)]
+struct ObservedSwap {
+    tx_digest: String,
+    sender: String,
+    a_to_b: bool,
+    amount_in: u64,
+    fee_amount: u64,
+    reserves_before: Reserves,
+}
+
+/// Liquidity addition waiting for a matching removal
+#[derive(Debug, Clone)]
+struct OpenPosition {
+    provider: String,
+    tx_digest: String,
+    checkpoint: i64,
+    timestamp_ms: i64,
+    amount_a: u64,
+    amount_b: u64,
+    liquidity_minted: u64,
+    swaps: Vec<ObservedSwap>,
+}
+
+/// Detected JIT liquidity pattern
+#[derive(Debug, Clone)]
+struct JitMatch {
+    pool_id: String,
+    position: OpenPosition,
+    removal: LiquidityRemoved,
+    remove_tx: String,
+    remove_checkpoint: i64,
+    remove_timestamp_ms: i64,
+    /// Provider's share of the pool when the first swap executed (bps)
+    pool_share_bps: u64,
+    /// Largest swap input relative to its input reserve (bps)
+    max_swap_share_bps: u64,
+    fees_captured_a: u64,
+    fees_captured_b: u64,
+}
+
+/// Just-in-time liquidity analyzer
+///
+/// Detects providers that add a large share of a pool's liquidity right before
+/// a big swap from another address and remove it right after, capturing most
+/// of the swap fees without carrying inventory risk.
+pub struct JitLiquidityAnalyzer {
+    // Open liquidity additions per pool
+    open_positions: Mutex<HashMap<String, Vec<OpenPosition>>>,
+    /// Maximum time between add and remove
+    max_position_age_ms: i64,
+    /// Minimum provider share of the pool at swap time (bps)
+    min_pool_share_bps: u64,
+    /// Minimum swap input relative to the input reserve (bps)
+    min_swap_share_bps: u64,
+}
+
+impl JitLiquidityAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            open_positions: Mutex::new(HashMap::new()),
+            max_position_age_ms: 60 * 1000, // 1 minute
+            min_pool_share_bps: 2500,       // 25% of the pool
+            min_swap_share_bps: 100,        // 1% of the input reserve
+        }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let activity = self.extract_activity(tx);
+        if activity.is_empty() {
+            return Vec::new();
+        }
+
+        self.process_activity(activity, context)
+    }
+
+    /// Extract liquidity and swap events in emission order
+    fn extract_activity(&self, tx: &ExecutedTransaction) -> Vec<DexActivity> {
+        let events = match &tx.events {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+
+        let mut activity = Vec::new();
+
+        for event in &events.data {
+            match event.type_.name.as_str() {
+                "LiquidityAdded" => {
+                    if let Some(e) = LiquidityAdded::from_event(event) {
+                        activity.push(DexActivity::Added(e));
+                    }
+                }
+                "SwapExecuted" => {
+                    if let Some(e) = SwapExecuted::from_event(event) {
+                        activity.push(DexActivity::Swap(e));
+                    }
+                }
+                "LiquidityRemoved" => {
+                    if let Some(e) = LiquidityRemoved::from_event(event) {
+                        activity.push(DexActivity::Removed(e));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        activity
+    }
+
+    /// Apply activity to open positions and report completed JIT patterns
+    fn process_activity(&self, activity: Vec<DexActivity>, context: &DetectionContext) -> Vec<RiskEvent> {
+        let mut open_positions = self.open_positions.lock().unwrap();
+        let mut events = Vec::new();
+
+        // Expire positions that stayed open too long to be JIT
+        open_positions.retain(|_, positions| {
+            positions.retain(|p| context.timestamp_ms - p.timestamp_ms <= self.max_position_age_ms);
+            !positions.is_empty()
+        });
+
+        for item in activity {
+            match item {
+                DexActivity::Added(added) => {
+                    open_positions
+                        .entry(added.pool_id.to_string())
+                        .or_default()
+                        .push(OpenPosition {
+                            provider: added.provider.to_string(),
+                            tx_digest: context.tx_digest.clone(),
+                            checkpoint: context.checkpoint,
+                            timestamp_ms: context.timestamp_ms,
+                            amount_a: added.amount_a,
+                            amount_b: added.amount_b,
+                            liquidity_minted: added.liquidity_minted,
+                            swaps: Vec::new(),
+                        });
+                }
+                DexActivity::Swap(swap) => {
+                    let Some(positions) = open_positions.get_mut(&swap.pool_id.to_string()) else {
+                        continue;
+                    };
+
+                    let sender = swap.sender.to_string();
+                    let reserves_before = Reserves::before_swap(
+                        Reserves::new(swap.reserve_a, swap.reserve_b),
+                        swap.token_in,
+                        swap.amount_in,
+                        swap.amount_out,
+                    );
+
+                    // Provider's own swaps don't pay it fees from someone else
+                    for position in positions.iter_mut().filter(|p| p.provider != sender) {
+                        position.swaps.push(ObservedSwap {
+                            tx_digest: context.tx_digest.clone(),
+                            sender: sender.clone(),
+                            a_to_b: swap.token_in,
+                            amount_in: swap.amount_in,
+                            fee_amount: swap.fee_amount,
+                            reserves_before,
+                        });
+                    }
+                }
+                DexActivity::Removed(removed) => {
+                    let pool_id = removed.pool_id.to_string();
+                    let provider = removed.provider.to_string();
+
+                    let Some(positions) = open_positions.get_mut(&pool_id) else {
+                        continue;
+                    };
+                    // Most recent addition by this provider
+                    let Some(index) = positions.iter().rposition(|p| p.provider == provider) else {
+                        continue;
+                    };
+                    let position = positions.remove(index);
+                    if positions.is_empty() {
+                        open_positions.remove(&pool_id);
+                    }
+
+                    if let Some(jit) = self.match_jit(pool_id, position, removed, context) {
+                        events.push(self.create_jit_event(&jit));
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Check whether a closed position qualifies as JIT liquidity
+    fn match_jit(
+        &self,
+        pool_id: String,
+        position: OpenPosition,
+        removal: LiquidityRemoved,
+        context: &DetectionContext,
+    ) -> Option<JitMatch> {
+        let first_swap = position.swaps.first()?;
+
+        // Added liquidity is already part of the reserves the first swap saw
+        let pool_share_bps = share_bps(position.amount_a, first_swap.reserves_before.a)
+            .max(share_bps(position.amount_b, first_swap.reserves_before.b));
+        if pool_share_bps < self.min_pool_share_bps {
+            return None;
+        }
+
+        let max_swap_share_bps = position
+            .swaps
+            .iter()
+            .map(|s| {
+                let reserve_in = if s.a_to_b { s.reserves_before.a } else { s.reserves_before.b };
+                share_bps(s.amount_in, reserve_in)
+            })
+            .max()
+            .unwrap_or(0);
+        if max_swap_share_bps < self.min_swap_share_bps {
+            return None;
+        }
+
+        // Fees accrue in each swap's input token, pro rata to the provider's share
+        let mut fees_captured_a = 0u64;
+        let mut fees_captured_b = 0u64;
+        for swap in &position.swaps {
+            let fee = (swap.fee_amount as u128 * pool_share_bps as u128
+                / amm::BPS_DENOMINATOR as u128) as u64;
+            if swap.a_to_b {
+                fees_captured_a += fee;
+            } else {
+                fees_captured_b += fee;
+            }
+        }
+
+        Some(JitMatch {
+            pool_id,
+            position,
+            removal,
+            remove_tx: context.tx_digest.clone(),
+            remove_checkpoint: context.checkpoint,
+            remove_timestamp_ms: context.timestamp_ms,
+            pool_share_bps,
+            max_swap_share_bps,
+            fees_captured_a,
+            fees_captured_b,
+        })
+    }
+
+    /// Create risk event from detected JIT pattern
+    fn create_jit_event(&self, jit: &JitMatch) -> RiskEvent {
+        let window_ms = jit.remove_timestamp_ms - jit.position.timestamp_ms;
+
+        // Add → swap → remove sequence itself, within the position age limit
+        let mut signals = vec![Signal::new("jit_round_trip_ms", window_ms as f64, self.max_position_age_ms as f64, 30)];
+
+        let pool_share = jit.pool_share_bps as f64;
+        if jit.pool_share_bps >= 5000 {
+            signals.push(Signal::new("pool_share_bps", pool_share, 5000.0, 20));
+        } else {
+            signals.push(Signal::new("pool_share_bps", pool_share, self.min_pool_share_bps as f64, 10));
+        }
+
+        if jit.max_swap_share_bps >= 500 {
+            signals.push(Signal::new("swap_share_bps", jit.max_swap_share_bps as f64, 500.0, 10));
+        }
+
+        // Whole round trip inside one checkpoint
+        if jit.position.checkpoint == jit.remove_checkpoint {
+            signals.push(Signal::flag("same_checkpoint", 10));
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let swap_txs: Vec<&String> = jit.position.swaps.iter().map(|s| &s.tx_digest).collect();
+        let swap_senders: Vec<&String> = jit.position.swaps.iter().map(|s| &s.sender).collect();
+
+        let description = format!(
+            "JIT liquidity: provider held {:.2}% of pool around {} swap(s), captured fees {} A / {} B in {}ms",
+            jit.pool_share_bps as f64 / 100.0,
+            jit.position.swaps.len(),
+            format_amount(jit.fees_captured_a as f64),
+            format_amount(jit.fees_captured_b as f64),
+            window_ms
+        );
+
+        RiskEvent::new(
+            RiskType::JitLiquidity,
+            risk_level,
+            jit.remove_tx.clone(),
+            jit.position.provider.clone(),
+            jit.remove_checkpoint,
+            jit.remove_timestamp_ms,
+            description,
+        )
+        .with_detail("provider", serde_json::json!(jit.position.provider))
+        .with_detail("pool_id", serde_json::json!(jit.pool_id))
+        .with_detail("add_tx", serde_json::json!(jit.position.tx_digest))
+        .with_detail("remove_tx", serde_json::json!(jit.remove_tx))
+        .with_detail("swap_txs", serde_json::json!(swap_txs))
+        .with_detail("swap_senders", serde_json::json!(swap_senders))
+        .with_metric("pool_share_bps", jit.pool_share_bps as f64)
+        .with_metric("max_swap_share_bps", jit.max_swap_share_bps as f64)
+        .with_metric("amount_a_added", jit.position.amount_a as f64)
+        .with_metric("amount_b_added", jit.position.amount_b as f64)
+        .with_metric("amount_a_removed", jit.removal.amount_a as f64)
+        .with_metric("amount_b_removed", jit.removal.amount_b as f64)
+        .with_metric("liquidity_minted", jit.position.liquidity_minted as f64)
+        .with_metric("liquidity_burned", jit.removal.liquidity_burned as f64)
+        .with_metric("fees_captured_a", jit.fees_captured_a as f64)
+        .with_metric("fees_captured_b", jit.fees_captured_b as f64)
+        .with_metric("window_ms", window_ms as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE)
+    }
+}
+
+/// `part` as a share of `whole` in basis points
+fn share_bps(part: u64, whole: u64) -> u64 {
+    if whole == 0 {
+        return 0;
+    }
+    (part as u128 * amm::BPS_DENOMINATOR as u128 / whole as u128).min(u64::MAX as u128) as u64
+}
+
+impl Default for JitLiquidityAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+
+    fn pool() -> ObjectID {
+        ObjectID::from_str("0x1").unwrap()
+    }
+
+    fn address(hex: &str) -> SuiAddress {
+        SuiAddress::from_str(hex).unwrap()
+    }
+
+    fn context(tx_digest: &str, timestamp_ms: i64) -> DetectionContext {
+        DetectionContext::new(tx_digest.to_string(), "0xa".to_string(), 1000, timestamp_ms)
+    }
+
+    fn added(provider: &str, amount: u64) -> DexActivity {
+        DexActivity::Added(LiquidityAdded {
+            pool_id: pool(),
+            provider: address(provider),
+            amount_a: amount,
+            amount_b: amount,
+            liquidity_minted: amount,
+        })
+    }
+
+    fn removed(provider: &str, amount: u64) -> DexActivity {
+        DexActivity::Removed(LiquidityRemoved {
+            pool_id: pool(),
+            provider: address(provider),
+            amount_a: amount,
+            amount_b: amount,
+            liquidity_burned: amount,
+        })
+    }
+
+    /// 100,000 A swapped into a pool of 3,000,000 / 3,000,000 (reserves after)
+    fn big_swap(sender: &str) -> DexActivity {
+        DexActivity::Swap(SwapExecuted {
+            pool_id: pool(),
+            sender: address(sender),
+            token_in: true,
+            amount_in: 100_000,
+            amount_out: 96_500,
+            fee_amount: 300,
+            reserve_a: 3_100_000,
+            reserve_b: 2_903_500,
+            price_impact: 321,
+        })
+    }
+
+    #[test]
+    fn test_jit_add_swap_remove() {
+        let analyzer = JitLiquidityAnalyzer::new();
+
+        // Provider adds 2M on top of a 1M / 1M pool, victim swaps, provider leaves
+        assert!(analyzer.process_activity(vec![added("0xa", 2_000_000)], &context("add", 1_000_000)).is_empty());
+        assert!(analyzer.process_activity(vec![big_swap("0xb")], &context("swap", 1_000_100)).is_empty());
+        let events = analyzer.process_activity(vec![removed("0xa", 2_000_000)], &context("remove", 1_000_200));
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.risk_type, RiskType::JitLiquidity);
+        // 2M of 3M reserves before the swap
+        assert_eq!(event.metrics["pool_share_bps"], 6666.0);
+        // 300 fee * 66.66%
+        assert_eq!(event.metrics["fees_captured_a"], 199.0);
+    }
+
+    #[test]
+    fn test_slow_position_not_jit() {
+        let analyzer = JitLiquidityAnalyzer::new();
+
+        analyzer.process_activity(vec![added("0xa", 2_000_000)], &context("add", 1_000_000));
+        analyzer.process_activity(vec![big_swap("0xb")], &context("swap", 1_000_100));
+
+        let late = 1_000_000 + analyzer.max_position_age_ms + 1;
+        assert!(analyzer.process_activity(vec![removed("0xa", 2_000_000)], &context("remove", late)).is_empty());
+    }
+
+    #[test]
+    fn test_own_swap_does_not_count() {
+        let analyzer = JitLiquidityAnalyzer::new();
+
+        let events = analyzer.process_activity(
+            vec![added("0xa", 2_000_000), big_swap("0xa"), removed("0xa", 2_000_000)],
+            &context("self", 1_000_000),
+        );
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,470 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Liquidation Anomaly Detection: Bad Debt, Manipulated Liquidations and Cascades
+
+use crate::action::format::format_amount;
+use crate::events::{LiquidationEvent, ParsedEvents};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Level thresholds in points; bad debt, a severe health factor, a same-tx
+/// swap by the liquidator and a large cascade together award 160
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+    160,
+);
+
+/// Price-moving swap kept to correlate with later liquidations
+#[derive(Debug, Clone)]
+struct RecentSwap {
+    tx_digest: String,
+    sender: String,
+    pool_id: String,
+    price_impact: u64,
+    timestamp_ms: i64,
+}
+
+/// Liquidation kept to detect cascades
+#[derive(Debug, Clone)]
+struct RecentLiquidation {
+    market_id: String,
+    position_id: String,
+    timestamp_ms: i64,
+}
+
+#[derive(Debug, Default)]
+struct LiquidationState {
+    recent_swaps: VecDeque<RecentSwap>,
+    recent_liquidations: VecDeque<RecentLiquidation>,
+}
+
+/// Liquidation anomaly analyzer
+///
+/// Flags liquidations that indicate the lending market is under attack:
+/// 1. Bad debt (`protocol_loss > 0`)
+/// 2. Positions liquidated far below the liquidation threshold (abnormal health factor)
+/// 3. Liquidations right after a price-moving swap (manipulated liquidation);
+///    earlier swaps only count on pools swapped in the liquidation transaction
+///    or by the liquidator, since markets don't name the pools pricing them
+/// 4. Many positions liquidated in a short window (cascade)
+pub struct LiquidationAnalyzer {
+    state: Mutex<LiquidationState>,
+    /// Health factor (bps, 10000 = 1.0) below which a liquidation is abnormal
+    abnormal_health_factor: u64,
+    /// Health factor below which a liquidation is severely abnormal
+    severe_health_factor: u64,
+    /// Minimum swap price impact to count as price-moving (bps)
+    min_swap_price_impact: u64,
+    /// How long a price-moving swap stays correlated with liquidations
+    manipulation_window_ms: i64,
+    /// Window for counting liquidations towards a cascade
+    cascade_window_ms: i64,
+    /// Distinct positions liquidated in the window to call it a cascade
+    cascade_min_positions: usize,
+}
+
+impl LiquidationAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(LiquidationState::default()),
+            abnormal_health_factor: 5000, // 0.5
+            severe_health_factor: 2500,   // 0.25
+            min_swap_price_impact: 500,   // 5%
+            manipulation_window_ms: 2 * 60 * 1000, // 2 minutes
+            cascade_window_ms: 10 * 60 * 1000,    // 10 minutes
+            cascade_min_positions: 5,
+        }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let parsed = ParsedEvents::from_transaction(tx);
+        self.analyze_events(&parsed, context)
+    }
+
+    fn analyze_events(&self, parsed: &ParsedEvents, context: &DetectionContext) -> Option<RiskEvent> {
+        let mut state = self.state.lock().unwrap();
+        self.evict(&mut state, context.timestamp_ms);
+
+        // Step 1: Swaps in this transaction that moved price
+        let same_tx_swaps: Vec<RecentSwap> = parsed
+            .swaps
+            .iter()
+            .filter(|s| s.price_impact >= self.min_swap_price_impact)
+            .map(|s| RecentSwap {
+                tx_digest: context.tx_digest.clone(),
+                sender: s.sender.to_string(),
+                pool_id: s.pool_id.to_string(),
+                price_impact: s.price_impact,
+                timestamp_ms: context.timestamp_ms,
+            })
+            .collect();
+
+        if parsed.liquidations.is_empty() {
+            state.recent_swaps.extend(same_tx_swaps);
+            return None;
+        }
+
+        // Step 2: Record liquidations for cascade tracking
+        for liquidation in &parsed.liquidations {
+            state.recent_liquidations.push_back(RecentLiquidation {
+                market_id: liquidation.market_id.to_string(),
+                position_id: liquidation.position_id.to_string(),
+                timestamp_ms: context.timestamp_ms,
+            });
+        }
+
+        let tx_pools: HashSet<String> = parsed.swaps.iter().map(|s| s.pool_id.to_string()).collect();
+        let event = self.score_liquidations(
+            &parsed.liquidations,
+            &same_tx_swaps,
+            &tx_pools,
+            &state,
+            context,
+        );
+
+        state.recent_swaps.extend(same_tx_swaps);
+        event
+    }
+
+    /// Score all liquidations in a transaction and build a single event
+    fn score_liquidations(
+        &self,
+        liquidations: &[LiquidationEvent],
+        same_tx_swaps: &[RecentSwap],
+        tx_pools: &HashSet<String>,
+        state: &LiquidationState,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let total_protocol_loss: u64 = liquidations.iter().map(|l| l.protocol_loss).sum();
+        let total_debt_repaid: u64 = liquidations.iter().map(|l| l.debt_repaid).sum();
+        let min_health_factor = liquidations
+            .iter()
+            .map(|l| l.health_factor_before)
+            .min()
+            .unwrap_or(u64::MAX);
+
+        let liquidators: HashSet<String> = liquidations.iter().map(|l| l.liquidator.to_string()).collect();
+        let in_window = |s: &&RecentSwap| context.timestamp_ms - s.timestamp_ms <= self.manipulation_window_ms;
+
+        // Pools linked to these liquidations: swapped in this tx or by a liquidator
+        let linked_pools: HashSet<&str> = tx_pools
+            .iter()
+            .map(String::as_str)
+            .chain(
+                state
+                    .recent_swaps
+                    .iter()
+                    .filter(in_window)
+                    .filter(|s| liquidators.contains(&s.sender))
+                    .map(|s| s.pool_id.as_str()),
+            )
+            .collect();
+
+        // Price-moving swaps from earlier transactions on linked pools (same tx handled separately)
+        let recent_swaps: Vec<&RecentSwap> = state
+            .recent_swaps
+            .iter()
+            .filter(in_window)
+            .filter(|s| linked_pools.contains(s.pool_id.as_str()))
+            .collect();
+        let liquidator_moved_price = same_tx_swaps
+            .iter()
+            .chain(recent_swaps.iter().copied())
+            .any(|s| liquidators.contains(&s.sender));
+
+        // Cascade: distinct positions liquidated in the window on the same markets
+        let markets: HashSet<String> = liquidations.iter().map(|l| l.market_id.to_string()).collect();
+        let cascade_positions = state
+            .recent_liquidations
+            .iter()
+            .filter(|l| {
+                markets.contains(&l.market_id)
+                    && context.timestamp_ms - l.timestamp_ms <= self.cascade_window_ms
+            })
+            .map(|l| &l.position_id)
+            .collect::<HashSet<_>>()
+            .len();
+
+        // Risk scoring
+        let mut signals = Vec::new();
+
+        if total_protocol_loss > 0 {
+            signals.push(Signal::new("bad_debt", total_protocol_loss as f64, 0.0, 50));
+        }
+
+        // Health factor fires below its threshold
+        let health_factor = min_health_factor as f64;
+        if min_health_factor < self.severe_health_factor {
+            signals.push(Signal::new("abnormal_health_factor", health_factor, self.severe_health_factor as f64, 25));
+        } else if min_health_factor < self.abnormal_health_factor {
+            signals.push(Signal::new("abnormal_health_factor", health_factor, self.abnormal_health_factor as f64, 15));
+        }
+
+        if !same_tx_swaps.is_empty() {
+            signals.push(Signal::new("price_moving_swap_same_tx", same_tx_swaps.len() as f64, 1.0, 30));
+        } else if !recent_swaps.is_empty() {
+            signals.push(Signal::new("price_moving_swap_recent_tx", recent_swaps.len() as f64, 1.0, 20));
+        }
+
+        if liquidator_moved_price {
+            signals.push(Signal::flag("liquidator_moved_price", 10));
+        }
+
+        // A cascade alone reaches the alert threshold
+        let positions = cascade_positions as f64;
+        if cascade_positions >= self.cascade_min_positions * 2 {
+            signals.push(Signal::new("liquidation_cascade", positions, (self.cascade_min_positions * 2) as f64, 45));
+        } else if cascade_positions >= self.cascade_min_positions {
+            signals.push(Signal::new("liquidation_cascade", positions, self.cascade_min_positions as f64, 30));
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        if risk_score < 30 {
+            return None; // Ordinary liquidation
+        }
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let description = format!(
+            "Liquidation anomaly: {} liquidation(s), {} bad debt, min health factor {:.2}, {} positions liquidated in window",
+            liquidations.len(),
+            format_amount(total_protocol_loss as f64),
+            min_health_factor as f64 / 10000.0,
+            cascade_positions
+        );
+
+        let details: Vec<serde_json::Value> = liquidations
+            .iter()
+            .map(|l| serde_json::json!({
+                "market_id": l.market_id.to_string(),
+                "position_id": l.position_id.to_string(),
+                "borrower": l.borrower.to_string(),
+                "liquidator": l.liquidator.to_string(),
+                "debt_repaid": l.debt_repaid,
+                "collateral_seized": l.collateral_seized,
+                "health_factor_before": l.health_factor_before,
+                "protocol_loss": l.protocol_loss,
+            }))
+            .collect();
+
+        let price_moving_swaps: Vec<serde_json::Value> = same_tx_swaps
+            .iter()
+            .chain(recent_swaps.iter().copied())
+            .map(|s| serde_json::json!({
+                "tx_digest": s.tx_digest,
+                "sender": s.sender,
+                "pool_id": s.pool_id,
+                "price_impact_bps": s.price_impact,
+            }))
+            .collect();
+
+        let event = RiskEvent::new(
+            RiskType::LiquidationAnomaly,
+            risk_level,
+            context.tx_digest.clone(),
+            context.sender.clone(),
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("liquidations", serde_json::json!(details))
+        .with_detail("price_moving_swaps", serde_json::json!(price_moving_swaps))
+        .with_metric("liquidation_count", liquidations.len() as f64)
+        .with_metric("total_debt_repaid", total_debt_repaid as f64)
+        .with_metric("protocol_loss", total_protocol_loss as f64)
+        .with_metric("min_health_factor_bps", min_health_factor as f64)
+        .with_metric("cascade_positions", cascade_positions as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE);
+
+        Some(event)
+    }
+
+    /// Drop swaps and liquidations that fell out of their windows
+    fn evict(&self, state: &mut LiquidationState, now_ms: i64) {
+        while state
+            .recent_swaps
+            .front()
+            .is_some_and(|s| now_ms - s.timestamp_ms > self.manipulation_window_ms)
+        {
+            state.recent_swaps.pop_front();
+        }
+        while state
+            .recent_liquidations
+            .front()
+            .is_some_and(|l| now_ms - l.timestamp_ms > self.cascade_window_ms)
+        {
+            state.recent_liquidations.pop_front();
+        }
+    }
+}
+
+impl Default for LiquidationAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::SwapExecuted;
+    use std::str::FromStr;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+
+    fn liquidation(position: &str, health_factor_before: u64, protocol_loss: u64) -> LiquidationEvent {
+        LiquidationEvent {
+            market_id: ObjectID::from_str("0x1").unwrap(),
+            liquidator: SuiAddress::from_str("0xa").unwrap(),
+            borrower: SuiAddress::from_str("0xb").unwrap(),
+            position_id: ObjectID::from_str(position).unwrap(),
+            debt_repaid: 1_000_000_000,
+            collateral_seized: 1_050_000_000,
+            liquidation_incentive: 500,
+            health_factor_before,
+            protocol_loss,
+            timestamp: 0,
+        }
+    }
+
+    fn context(tx_digest: &str, timestamp_ms: i64) -> DetectionContext {
+        DetectionContext::new(tx_digest.to_string(), "0xa".to_string(), 1000, timestamp_ms)
+    }
+
+    fn signal_names(event: &RiskEvent) -> Vec<&str> {
+        event.signals.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ordinary_liquidation_not_flagged() {
+        let analyzer = LiquidationAnalyzer::new();
+        let parsed = ParsedEvents {
+            liquidations: vec![liquidation("0x100", 9500, 0)],
+            ..Default::default()
+        };
+
+        assert!(analyzer.analyze_events(&parsed, &context("tx1", 1_000_000)).is_none());
+    }
+
+    #[test]
+    fn test_bad_debt_flagged() {
+        let analyzer = LiquidationAnalyzer::new();
+        let parsed = ParsedEvents {
+            liquidations: vec![liquidation("0x100", 9000, 250_000_000)],
+            ..Default::default()
+        };
+
+        let event = analyzer.analyze_events(&parsed, &context("tx1", 1_000_000)).unwrap();
+        assert_eq!(event.risk_type, RiskType::LiquidationAnomaly);
+        assert_eq!(signal_names(&event), vec!["bad_debt"]);
+    }
+
+    #[test]
+    fn test_liquidation_after_recent_price_move() {
+        let analyzer = LiquidationAnalyzer::new();
+
+        // Liquidator pumps the oracle pool in one transaction...
+        let swap_tx = ParsedEvents {
+            swaps: vec![SwapExecuted {
+                pool_id: ObjectID::from_str("0x2").unwrap(),
+                sender: SuiAddress::from_str("0xa").unwrap(),
+                token_in: true,
+                amount_in: 1_000_000_000,
+                amount_out: 700_000_000,
+                fee_amount: 3_000_000,
+                reserve_a: 3_000_000_000,
+                reserve_b: 2_300_000_000,
+                price_impact: 2300,
+            }],
+            ..Default::default()
+        };
+        assert!(analyzer.analyze_events(&swap_tx, &context("swap", 1_000_000)).is_none());
+
+        // ...then liquidates a position in the next one
+        let liquidation_tx = ParsedEvents {
+            liquidations: vec![liquidation("0x100", 4000, 0)],
+            ..Default::default()
+        };
+        let event = analyzer
+            .analyze_events(&liquidation_tx, &context("liquidate", 1_030_000))
+            .unwrap();
+
+        assert_eq!(
+            signal_names(&event),
+            vec![
+                "abnormal_health_factor",
+                "price_moving_swap_recent_tx",
+                "liquidator_moved_price"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_price_move_on_unrelated_pool_ignored() {
+        let analyzer = LiquidationAnalyzer::new();
+        let swap = |pool: &str, sender: &str| ParsedEvents {
+            swaps: vec![SwapExecuted {
+                pool_id: ObjectID::from_str(pool).unwrap(),
+                sender: SuiAddress::from_str(sender).unwrap(),
+                token_in: true,
+                amount_in: 1_000_000_000,
+                amount_out: 700_000_000,
+                fee_amount: 3_000_000,
+                reserve_a: 3_000_000_000,
+                reserve_b: 2_300_000_000,
+                price_impact: 2300,
+            }],
+            ..Default::default()
+        };
+        let bad_debt = ParsedEvents {
+            liquidations: vec![liquidation("0x100", 9000, 250_000_000)],
+            ..Default::default()
+        };
+
+        // Someone else moves an unrelated pool
+        analyzer.analyze_events(&swap("0x3", "0xc"), &context("other", 1_000_000));
+        let event = analyzer.analyze_events(&bad_debt, &context("liquidate1", 1_010_000)).unwrap();
+        assert_eq!(signal_names(&event), vec!["bad_debt"]);
+
+        // Only the liquidator's pool counts once it moved one
+        analyzer.analyze_events(&swap("0x2", "0xa"), &context("swap", 1_020_000));
+        let event = analyzer.analyze_events(&bad_debt, &context("liquidate2", 1_030_000)).unwrap();
+        assert_eq!(signal_names(&event), vec!["bad_debt", "price_moving_swap_recent_tx", "liquidator_moved_price"]);
+        assert_eq!(event.signals[1].value, 1.0);
+    }
+
+    #[test]
+    fn test_liquidation_cascade() {
+        let analyzer = LiquidationAnalyzer::new();
+
+        let mut last = None;
+        for i in 0..analyzer.cascade_min_positions {
+            let parsed = ParsedEvents {
+                liquidations: vec![liquidation(&format!("0x{}", 100 + i), 8000, 0)],
+                ..Default::default()
+            };
+            last = analyzer.analyze_events(&parsed, &context(&format!("tx{}", i), 1_000_000 + i as i64 * 10_000));
+        }
+
+        // Healthy-looking liquidations still alert once they form a cascade
+        let event = last.unwrap();
+        assert_eq!(event.risk_level, RiskLevel::Low);
+        assert_eq!(event.metrics["cascade_positions"], 5.0);
+        assert_eq!(signal_names(&event), vec!["liquidation_cascade"]);
+
+        let parsed = ParsedEvents {
+            liquidations: vec![liquidation("0x200", 4000, 0)],
+            ..Default::default()
+        };
+        let event = analyzer.analyze_events(&parsed, &context("tx_final", 1_100_000)).unwrap();
+        assert_eq!(event.metrics["cascade_positions"], 6.0);
+        assert_eq!(signal_names(&event), vec!["abnormal_health_factor", "liquidation_cascade"]);
+    }
+}
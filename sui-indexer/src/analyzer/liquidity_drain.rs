@@ -0,0 +1,577 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Liquidity Drain Detection: Rapid Liquidity Loss and Creator Rug Pulls
+
+use crate::action::format::format_bps;
+use crate::events::{EventParser, LiquidityAdded, LiquidityRemoved, PoolCreated, SwapExecuted};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use super::amm::Reserves;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Any drain or rug pull is at least Medium; both at their top tiers award 130
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Medium,
+    &[(RiskLevel::High, 60), (RiskLevel::Critical, 80)],
+    130,
+);
+
+/// Pool event in emission order
+#[derive(Debug, Clone)]
+enum PoolActivity {
+    Created(PoolCreated),
+    Added(LiquidityAdded),
+    Swap(SwapExecuted),
+    Removed(LiquidityRemoved),
+}
+
+/// Pool liquidity after an activity
+#[derive(Debug, Clone)]
+struct LiquiditySample {
+    timestamp_ms: i64,
+    liquidity: u128,
+}
+
+/// Swap by an address other than the pool creator
+#[derive(Debug, Clone)]
+struct UserSwap {
+    tx_digest: String,
+    sender: String,
+    timestamp_ms: i64,
+}
+
+/// Removal seen in the current transaction
+#[derive(Debug, Clone)]
+struct Removal {
+    provider: String,
+    amount_a: u64,
+    amount_b: u64,
+    /// Share of pool liquidity removed (bps)
+    share_bps: u64,
+}
+
+#[derive(Debug, Default)]
+struct PoolTrack {
+    creator: Option<String>,
+    created_ms: Option<i64>,
+    /// None until the pool is created or swapped in while we watch
+    reserves: Option<Reserves>,
+    samples: VecDeque<LiquiditySample>,
+    user_swaps: VecDeque<UserSwap>,
+    last_alert_ms: Option<i64>,
+}
+
+/// Liquidity drain analyzer
+///
+/// Tracks pool reserves from `PoolCreated`, `SwapExecuted` and liquidity events
+/// and measures liquidity as `sqrt(reserve_a * reserve_b)`, which swaps don't
+/// reduce. Flags:
+/// 1. A pool losing a large share of its peak liquidity within a short window
+/// 2. The pool creator removing liquidity soon after users swapped in (rug pull)
+pub struct LiquidityDrainAnalyzer {
+    pools: Mutex<HashMap<String, PoolTrack>>,
+    /// Window over which peak liquidity is compared to current
+    drain_window_ms: i64,
+    /// Share of peak liquidity lost in the window to flag a drain (bps)
+    min_drain_bps: u64,
+    /// How long user swaps count towards a creator rug pull
+    rug_window_ms: i64,
+    /// Minimum share of liquidity removed by the creator (bps)
+    min_creator_removal_bps: u64,
+    /// Pools younger than this are treated as new
+    new_pool_age_ms: i64,
+    /// Samples and swaps kept per pool
+    max_history_per_pool: usize,
+}
+
+impl LiquidityDrainAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            pools: Mutex::new(HashMap::new()),
+            drain_window_ms: 10 * 60 * 1000, // 10 minutes
+            min_drain_bps: 5000,             // 50% of liquidity
+            rug_window_ms: 60 * 60 * 1000,   // 1 hour
+            min_creator_removal_bps: 2000,   // 20% of liquidity
+            new_pool_age_ms: 24 * 60 * 60 * 1000, // 24 hours
+            max_history_per_pool: 256,
+        }
+    }
+
+    /// Build with thresholds overridden from the environment
+    ///
+    /// - `LIQUIDITY_DRAIN_MIN_BPS`: share of peak liquidity lost to flag a drain
+    /// - `LIQUIDITY_DRAIN_WINDOW_MS`: window over which the loss is measured
+    pub fn from_env() -> Self {
+        let mut analyzer = Self::new();
+
+        if let Some(bps) = std::env::var("LIQUIDITY_DRAIN_MIN_BPS").ok().and_then(|v| v.parse().ok()) {
+            analyzer = analyzer.with_min_drain_bps(bps);
+        }
+        if let Some(ms) = std::env::var("LIQUIDITY_DRAIN_WINDOW_MS").ok().and_then(|v| v.parse().ok()) {
+            analyzer = analyzer.with_drain_window_ms(ms);
+        }
+
+        analyzer
+    }
+
+    /// Set the share of liquidity (bps) a pool must lose within the window
+    pub fn with_min_drain_bps(mut self, min_drain_bps: u64) -> Self {
+        self.min_drain_bps = min_drain_bps;
+        self
+    }
+
+    /// Set the drain window
+    pub fn with_drain_window_ms(mut self, drain_window_ms: i64) -> Self {
+        self.drain_window_ms = drain_window_ms;
+        self
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let activity = self.extract_activity(tx);
+        if activity.is_empty() {
+            return Vec::new();
+        }
+
+        self.process_activity(activity, context)
+    }
+
+    /// Extract pool events in emission order
+    fn extract_activity(&self, tx: &ExecutedTransaction) -> Vec<PoolActivity> {
+        let events = match &tx.events {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+
+        let mut activity = Vec::new();
+
+        for event in &events.data {
+            match event.type_.name.as_str() {
+                "PoolCreated" => {
+                    if let Some(e) = PoolCreated::from_event(event) {
+                        activity.push(PoolActivity::Created(e));
+                    }
+                }
+                "LiquidityAdded" => {
+                    if let Some(e) = LiquidityAdded::from_event(event) {
+                        activity.push(PoolActivity::Added(e));
+                    }
+                }
+                "SwapExecuted" => {
+                    if let Some(e) = SwapExecuted::from_event(event) {
+                        activity.push(PoolActivity::Swap(e));
+                    }
+                }
+                "LiquidityRemoved" => {
+                    if let Some(e) = LiquidityRemoved::from_event(event) {
+                        activity.push(PoolActivity::Removed(e));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        activity
+    }
+
+    /// Apply activity to tracked reserves and evaluate pools that lost liquidity
+    fn process_activity(&self, activity: Vec<PoolActivity>, context: &DetectionContext) -> Vec<RiskEvent> {
+        let mut pools = self.pools.lock().unwrap();
+        let mut removals: Vec<(String, Removal)> = Vec::new();
+        let now = context.timestamp_ms;
+
+        for item in activity {
+            match item {
+                PoolActivity::Created(created) => {
+                    let pool = pools.entry(created.pool_id.to_string()).or_default();
+                    pool.creator = Some(created.creator.to_string());
+                    pool.created_ms = Some(now);
+                    pool.reserves = Some(Reserves::new(created.initial_a, created.initial_b));
+                    self.record_sample(pool, now);
+                }
+                PoolActivity::Added(added) => {
+                    let pool = pools.entry(added.pool_id.to_string()).or_default();
+                    if let Some(reserves) = pool.reserves.as_mut() {
+                        reserves.a = reserves.a.saturating_add(added.amount_a);
+                        reserves.b = reserves.b.saturating_add(added.amount_b);
+                        self.record_sample(pool, now);
+                    }
+                }
+                PoolActivity::Swap(swap) => {
+                    let pool = pools.entry(swap.pool_id.to_string()).or_default();
+                    // Swap events carry absolute reserves, which resync tracking
+                    pool.reserves = Some(Reserves::new(swap.reserve_a, swap.reserve_b));
+                    self.record_sample(pool, now);
+
+                    let sender = swap.sender.to_string();
+                    if pool.creator.as_deref() != Some(sender.as_str()) {
+                        if pool.user_swaps.len() >= self.max_history_per_pool {
+                            pool.user_swaps.pop_front();
+                        }
+                        pool.user_swaps.push_back(UserSwap {
+                            tx_digest: context.tx_digest.clone(),
+                            sender,
+                            timestamp_ms: now,
+                        });
+                    }
+                }
+                PoolActivity::Removed(removed) => {
+                    let pool_id = removed.pool_id.to_string();
+                    let pool = pools.entry(pool_id.clone()).or_default();
+                    let Some(before) = pool.reserves else {
+                        continue;
+                    };
+                    // Samples of a pool idle longer than the window have aged out;
+                    // the pre-removal level keeps a baseline to measure the drain against
+                    self.record_sample(pool, now);
+
+                    let after = Reserves::new(
+                        before.a.saturating_sub(removed.amount_a),
+                        before.b.saturating_sub(removed.amount_b),
+                    );
+                    pool.reserves = Some(after);
+                    self.record_sample(pool, now);
+
+                    let before_liquidity = liquidity(before);
+                    let share_bps = ratio_bps(before_liquidity - liquidity(after).min(before_liquidity), before_liquidity);
+                    removals.push((
+                        pool_id,
+                        Removal {
+                            provider: removed.provider.to_string(),
+                            amount_a: removed.amount_a,
+                            amount_b: removed.amount_b,
+                            share_bps,
+                        },
+                    ));
+                }
+            }
+        }
+
+        // Evaluate each pool once, after all of the transaction's activity
+        let mut pool_ids: Vec<String> = removals.iter().map(|(id, _)| id.clone()).collect();
+        pool_ids.sort();
+        pool_ids.dedup();
+
+        let mut events = Vec::new();
+        for pool_id in pool_ids {
+            let Some(pool) = pools.get_mut(&pool_id) else {
+                continue;
+            };
+            let pool_removals: Vec<&Removal> = removals
+                .iter()
+                .filter(|(id, _)| *id == pool_id)
+                .map(|(_, r)| r)
+                .collect();
+
+            if let Some(event) = self.evaluate_pool(&pool_id, pool, &pool_removals, context) {
+                pool.last_alert_ms = Some(now);
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    fn record_sample(&self, pool: &mut PoolTrack, now: i64) {
+        let Some(reserves) = pool.reserves else {
+            return;
+        };
+
+        let window_start = now - self.drain_window_ms.max(self.rug_window_ms);
+        while pool.samples.front().is_some_and(|s| s.timestamp_ms < now - self.drain_window_ms) {
+            pool.samples.pop_front();
+        }
+        while pool.user_swaps.front().is_some_and(|s| s.timestamp_ms < window_start) {
+            pool.user_swaps.pop_front();
+        }
+        if pool.samples.len() >= self.max_history_per_pool {
+            pool.samples.pop_front();
+        }
+
+        pool.samples.push_back(LiquiditySample {
+            timestamp_ms: now,
+            liquidity: liquidity(reserves),
+        });
+    }
+
+    /// Score a pool after the transaction's removals
+    fn evaluate_pool(
+        &self,
+        pool_id: &str,
+        pool: &PoolTrack,
+        removals: &[&Removal],
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let now = context.timestamp_ms;
+
+        // Don't re-alert on every step of the same drain
+        if pool.last_alert_ms.is_some_and(|ts| now - ts < self.drain_window_ms) {
+            return None;
+        }
+
+        let current = pool.samples.back()?;
+        let peak = pool
+            .samples
+            .iter()
+            .max_by_key(|s| s.liquidity)?;
+        let drain_bps = ratio_bps(peak.liquidity - current.liquidity.min(peak.liquidity), peak.liquidity);
+        let drain_duration_ms = now - peak.timestamp_ms;
+
+        let creator_removal_bps: u64 = removals
+            .iter()
+            .filter(|r| pool.creator.as_deref() == Some(r.provider.as_str()))
+            .map(|r| r.share_bps)
+            .sum();
+        let user_swaps: Vec<&UserSwap> = pool
+            .user_swaps
+            .iter()
+            .filter(|s| now - s.timestamp_ms <= self.rug_window_ms)
+            .collect();
+        let pool_age_ms = pool.created_ms.map(|created| now - created);
+
+        let mut signals = Vec::new();
+
+        if drain_bps >= self.min_drain_bps {
+            signals.push(Signal::new("liquidity_drain", drain_bps as f64, self.min_drain_bps as f64, 40));
+
+            if drain_bps >= 8000 {
+                signals.push(Signal::new("drain_bps", drain_bps as f64, 8000.0, 20));
+            }
+            // Duration fires at or below its threshold
+            if drain_duration_ms <= 5 * 60 * 1000 {
+                signals.push(Signal::new("drain_duration_ms", drain_duration_ms as f64, (5 * 60 * 1000) as f64, 10));
+            }
+        }
+
+        let creator_rug = creator_removal_bps >= self.min_creator_removal_bps && !user_swaps.is_empty();
+        if creator_rug {
+            signals.push(Signal::new(
+                "creator_pulled_liquidity",
+                creator_removal_bps as f64,
+                self.min_creator_removal_bps as f64,
+                40,
+            ));
+
+            if let Some(age) = pool_age_ms.filter(|age| *age <= self.new_pool_age_ms) {
+                signals.push(Signal::new("new_pool", age as f64, self.new_pool_age_ms as f64, 10));
+            }
+            if user_swaps.len() >= 5 {
+                signals.push(Signal::new("user_swap_count", user_swaps.len() as f64, 5.0, 10));
+            }
+        }
+
+        if signals.is_empty() {
+            return None;
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        // Attribute to the largest remover in the transaction
+        let remover = removals
+            .iter()
+            .max_by_key(|r| r.share_bps)
+            .map(|r| r.provider.clone())
+            .unwrap_or_else(|| context.sender.clone());
+
+        let description = if creator_rug {
+            format!(
+                "Rug pull on pool {}: creator removed {} of liquidity after {} user swap(s), {} lost in {}ms",
+                pool_id,
+                format_bps(creator_removal_bps as f64),
+                user_swaps.len(),
+                format_bps(drain_bps as f64),
+                drain_duration_ms
+            )
+        } else {
+            format!(
+                "Liquidity drain on pool {}: {} of liquidity removed in {}ms",
+                pool_id,
+                format_bps(drain_bps as f64),
+                drain_duration_ms
+            )
+        };
+
+        let removal_details: Vec<serde_json::Value> = removals
+            .iter()
+            .map(|r| serde_json::json!({
+                "provider": r.provider,
+                "amount_a": r.amount_a,
+                "amount_b": r.amount_b,
+                "liquidity_share_bps": r.share_bps,
+            }))
+            .collect();
+        let victim_txs: Vec<&str> = user_swaps.iter().map(|s| s.tx_digest.as_str()).collect();
+        let mut victims: Vec<&str> = user_swaps.iter().map(|s| s.sender.as_str()).collect();
+        victims.sort();
+        victims.dedup();
+
+        let event = RiskEvent::new(
+            RiskType::LiquidityDrain,
+            risk_level,
+            context.tx_digest.clone(),
+            remover,
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("pool_id", serde_json::json!(pool_id))
+        .with_detail("removals", serde_json::json!(removal_details))
+        .with_detail("pool_creator", serde_json::json!(pool.creator))
+        .with_detail("user_swap_txs", serde_json::json!(victim_txs))
+        .with_detail("user_swappers", serde_json::json!(victims))
+        .with_metric("liquidity_lost_bps", drain_bps as f64)
+        .with_metric("drain_duration_ms", drain_duration_ms as f64)
+        .with_metric("peak_liquidity", peak.liquidity as f64)
+        .with_metric("current_liquidity", current.liquidity as f64)
+        .with_metric("creator_removed_bps", creator_removal_bps as f64)
+        .with_metric("user_swap_count", user_swaps.len() as f64);
+        let event = match pool_age_ms {
+            Some(age) => event.with_metric("pool_age_ms", age as f64),
+            None => event,
+        }
+        .with_scaled_signals(signals, &SCORE_SCALE);
+
+        Some(event)
+    }
+}
+
+/// Pool liquidity as the geometric mean of reserves
+fn liquidity(reserves: Reserves) -> u128 {
+    ((reserves.a as f64) * (reserves.b as f64)).sqrt() as u128
+}
+
+/// `part / whole` in basis points
+fn ratio_bps(part: u128, whole: u128) -> u64 {
+    if whole == 0 {
+        return 0;
+    }
+    (part * 10000 / whole) as u64
+}
+
+impl Default for LiquidityDrainAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+
+    fn pool_id() -> ObjectID {
+        ObjectID::from_str("0x1").unwrap()
+    }
+
+    fn created(creator: &str) -> PoolActivity {
+        PoolActivity::Created(PoolCreated {
+            pool_id: pool_id(),
+            initial_a: 1_000_000,
+            initial_b: 1_000_000,
+            creator: SuiAddress::from_str(creator).unwrap(),
+        })
+    }
+
+    fn added(provider: &str, amount: u64) -> PoolActivity {
+        PoolActivity::Added(LiquidityAdded {
+            pool_id: pool_id(),
+            provider: SuiAddress::from_str(provider).unwrap(),
+            amount_a: amount,
+            amount_b: amount,
+            liquidity_minted: amount,
+        })
+    }
+
+    fn removed(provider: &str, amount: u64) -> PoolActivity {
+        PoolActivity::Removed(LiquidityRemoved {
+            pool_id: pool_id(),
+            provider: SuiAddress::from_str(provider).unwrap(),
+            amount_a: amount,
+            amount_b: amount,
+            liquidity_burned: amount,
+        })
+    }
+
+    fn swap(sender: &str) -> PoolActivity {
+        // 10,000 A in on a 1,000,000 / 1,000,000 pool
+        PoolActivity::Swap(SwapExecuted {
+            pool_id: pool_id(),
+            sender: SuiAddress::from_str(sender).unwrap(),
+            token_in: true,
+            amount_in: 10_000,
+            amount_out: 9_871,
+            fee_amount: 30,
+            reserve_a: 1_010_000,
+            reserve_b: 990_129,
+            price_impact: 99,
+        })
+    }
+
+    fn context(tx: &str, timestamp_ms: i64) -> DetectionContext {
+        DetectionContext::new(tx.to_string(), "0xa".to_string(), 1000, timestamp_ms)
+    }
+
+    #[test]
+    fn test_creator_rug_after_user_swap() {
+        let analyzer = LiquidityDrainAnalyzer::new();
+        assert!(analyzer.process_activity(vec![created("0xc")], &context("create", 1_000_000)).is_empty());
+        assert!(analyzer.process_activity(vec![swap("0xb")], &context("swap", 1_060_000)).is_empty());
+
+        let events = analyzer.process_activity(vec![removed("0xc", 900_000)], &context("rug", 1_120_000));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].risk_type, RiskType::LiquidityDrain);
+        assert_eq!(events[0].risk_level, RiskLevel::Critical);
+        let names: Vec<&str> = events[0].signals.iter().map(|s| s.name.as_str()).collect();
+        for name in ["liquidity_drain", "creator_pulled_liquidity", "new_pool"] {
+            assert!(names.contains(&name), "missing signal {}", name);
+        }
+    }
+
+    #[test]
+    fn test_partial_removal_not_flagged() {
+        let analyzer = LiquidityDrainAnalyzer::new();
+        analyzer.process_activity(vec![created("0xc"), added("0xd", 1_000_000)], &context("setup", 1_000_000));
+
+        // Another provider withdraws a quarter of the pool
+        let events = analyzer.process_activity(vec![removed("0xd", 500_000)], &context("exit", 1_060_000));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_drain_across_transactions() {
+        let analyzer = LiquidityDrainAnalyzer::new().with_min_drain_bps(6000);
+        analyzer.process_activity(vec![swap("0xb")], &context("swap", 1_000_000));
+
+        // Two 35% withdrawals stay below the threshold individually
+        assert!(analyzer.process_activity(vec![removed("0xd", 353_500)], &context("r1", 1_010_000)).is_empty());
+        let events = analyzer.process_activity(vec![removed("0xd", 353_500)], &context("r2", 1_020_000));
+
+        assert_eq!(events.len(), 1);
+        let names: Vec<&str> = events[0].signals.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["liquidity_drain", "drain_duration_ms"]);
+
+        // Same drain doesn't alert again within the window
+        assert!(analyzer.process_activity(vec![removed("0xd", 10_000)], &context("r3", 1_030_000)).is_empty());
+    }
+
+    #[test]
+    fn test_idle_pool_drained() {
+        let analyzer = LiquidityDrainAnalyzer::new();
+        analyzer.process_activity(vec![swap("0xb")], &context("swap", 1_000_000));
+
+        // Last activity is long outside the drain window when the pool is emptied
+        let events = analyzer.process_activity(vec![removed("0xd", 900_000)], &context("drain", 1_000_000 + 60 * 60 * 1000));
+
+        assert_eq!(events.len(), 1);
+        assert!(events[0].metrics["liquidity_lost_bps"] >= 8000.0);
+        let names: Vec<&str> = events[0].signals.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["liquidity_drain", "drain_bps", "drain_duration_ms"]);
+    }
+}
@@ -1,9 +1,30 @@
+mod amm;
+mod baseline;
+mod cluster;
 mod flash_loan;
+mod gas_price;
+mod governance;
+mod interest_rate;
+mod jit_liquidity;
+mod liquidation;
+mod liquidity_drain;
 mod price;
+mod probing;
 mod sandwich;
 mod oracle_manipulation;
+mod wash_trading;
 
+pub use baseline::BaselineAnalyzer;
+pub use cluster::AddressClusterer;
 pub use flash_loan::FlashLoanAnalyzer;
+pub use gas_price::{GasPriceAnalyzer, GasPriceMonitor};
+pub use governance::{GovernanceAnalyzer, GovernanceWatchlist};
+pub use interest_rate::InterestRateAnalyzer;
+pub use jit_liquidity::JitLiquidityAnalyzer;
+pub use liquidation::LiquidationAnalyzer;
+pub use liquidity_drain::LiquidityDrainAnalyzer;
 pub use price::PriceAnalyzer;
+pub use probing::ProbingAnalyzer;
 pub use sandwich::SandwichAnalyzer;
 pub use oracle_manipulation::OracleManipulationAnalyzer;
+pub use wash_trading::WashTradingAnalyzer;
@@ -1,10 +1,18 @@
 // Copyright (c) 2024 DeFi Protocol Indexer
 // Oracle Manipulation Attack Detection via Lending Protocol Exploitation
 
+use crate::action::format::format_amount;
 use crate::events::{BorrowEvent, EventParser, FlashLoanTaken, SwapExecuted};
-use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
 use sui_types::full_checkpoint_content::ExecutedTransaction;
 
+/// Events start at 40 points, all Medium; the top tier of every signal awards 110
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Medium,
+    &[(RiskLevel::High, 60), (RiskLevel::Critical, 80)],
+    110,
+);
+
 /// Oracle manipulation analyzer
 ///
 /// Detects attacks that manipulate DEX oracle prices to exploit lending protocols:
@@ -92,64 +100,63 @@ impl OracleManipulationAnalyzer {
         };
 
         // Step 7: Risk scoring
-        let mut risk_score = 0u32;
+        let mut signals = Vec::new();
 
         // Flash loan presence
-        risk_score += 20;
+        signals.push(Signal::new("flash_loan_amount", flash_loan_info.amount as f64, 0.0, 20));
 
         // Price deviation scoring
+        let deviation = price_deviation as f64;
         if price_deviation >= 5000 {
             // 50%+
-            risk_score += 40;
+            signals.push(Signal::new("price_deviation_bps", deviation, 5000.0, 40));
         } else if price_deviation >= 2000 {
             // 20%+
-            risk_score += 30;
+            signals.push(Signal::new("price_deviation_bps", deviation, 2000.0, 30));
         } else if price_deviation >= 1000 {
             // 10%+
-            risk_score += 20;
+            signals.push(Signal::new("price_deviation_bps", deviation, 1000.0, 20));
         }
 
         // Borrow amount scoring
         if borrow_amount > 10_000_000_000 {
             // > 10k tokens
-            risk_score += 20;
+            signals.push(Signal::new("borrow_amount", borrow_amount as f64, 10_000_000_000.0, 20));
         } else if borrow_amount > 1_000_000_000 {
             // > 1k tokens
-            risk_score += 15;
+            signals.push(Signal::new("borrow_amount", borrow_amount as f64, 1_000_000_000.0, 15));
         }
 
         // Protocol loss scoring
         if protocol_loss > borrow_amount / 2 {
             // > 50% loss
-            risk_score += 20;
+            signals.push(Signal::new("protocol_loss", protocol_loss as f64, (borrow_amount / 2) as f64, 20));
         } else if protocol_loss > 0 {
-            risk_score += 10;
+            signals.push(Signal::new("protocol_loss", protocol_loss as f64, 0.0, 10));
         }
 
         // Health factor analysis
         let health_factor = lending_borrows[0].health_factor;
         if health_factor > 15000 {
             // Abnormally high (1.5x)
-            risk_score += 10;
+            signals.push(Signal::new("health_factor_bps", health_factor as f64, 15000.0, 10));
         }
 
+        let risk_score = Signal::total(&signals);
+
         // Classify
         if risk_score < 40 {
             return None; // Below threshold
         }
 
-        let risk_level = match risk_score {
-            40..=59 => RiskLevel::Medium,
-            60..=79 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
+        let risk_level = SCORE_SCALE.level(risk_score);
 
         // Step 8: Create event
         let description = format!(
             "Oracle manipulation: {:.2}% price inflation, ${} borrow, ${} potential protocol loss",
             price_deviation as f64 / 100.0,
-            format_currency(borrow_amount / 1_000_000),
-            format_currency(protocol_loss / 1_000_000)
+            format_amount((borrow_amount / 1_000_000) as f64),
+            format_amount((protocol_loss / 1_000_000) as f64)
         );
 
         let mut event = RiskEvent::new(
@@ -164,23 +171,17 @@ impl OracleManipulationAnalyzer {
 
         // Add details
         event = event
-            .with_detail(
-                "flash_loan_amount",
-                serde_json::json!(format_currency(flash_loan_info.amount)),
-            )
-            .with_detail("swap_count", serde_json::json!(large_swaps.len()))
-            .with_detail("oracle_price", serde_json::json!(format_currency(oracle_price)))
-            .with_detail("normal_price", serde_json::json!(format_currency(normal_price)))
-            .with_detail("price_deviation", serde_json::json!(format_bps(price_deviation)))
-            .with_detail("borrow_amount", serde_json::json!(format_currency(borrow_amount)))
-            .with_detail("collateral_value", serde_json::json!(format_currency(collateral_value)))
-            .with_detail(
-                "real_collateral_value",
-                serde_json::json!(format_currency(real_collateral_value)),
-            )
-            .with_detail("protocol_loss", serde_json::json!(format_currency(protocol_loss)))
-            .with_detail("health_factor", serde_json::json!(health_factor))
-            .with_detail("risk_score", serde_json::json!(risk_score));
+            .with_metric("flash_loan_amount", flash_loan_info.amount as f64)
+            .with_metric("swap_count", large_swaps.len() as f64)
+            .with_metric("oracle_price", oracle_price as f64)
+            .with_metric("normal_price", normal_price as f64)
+            .with_metric("price_deviation_bps", price_deviation as f64)
+            .with_metric("borrow_amount", borrow_amount as f64)
+            .with_metric("collateral_value", collateral_value as f64)
+            .with_metric("real_collateral_value", real_collateral_value as f64)
+            .with_metric("protocol_loss", protocol_loss as f64)
+            .with_metric("health_factor_bps", health_factor as f64)
+            .with_scaled_signals(signals, &SCORE_SCALE);
 
         Some(event)
     }
@@ -323,22 +324,6 @@ impl OracleManipulationAnalyzer {
     }
 }
 
-fn format_currency(amount: u64) -> String {
-    let s = amount.to_string();
-    let mut res = String::new();
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            res.insert(0, ',');
-        }
-        res.insert(0, c);
-    }
-    res
-}
-
-fn format_bps(bps: u64) -> String {
-    format!("{:.2}%", bps as f64 / 100.0)
-}
-
 impl Default for OracleManipulationAnalyzer {
     fn default() -> Self {
         Self::new()
@@ -1,17 +1,77 @@
 // Copyright (c) 2024 DeFi Protocol Indexer
 // Price Manipulation Detection using TWAP Deviation Analysis + Trade Impact Scoring
 
-use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType};
+use crate::action::format::format_amount;
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
 use crate::events::{SwapExecuted, TWAPUpdated, EventParser};
+use super::amm::Reserves;
+use super::cluster::{AddressCluster, AddressClusterer};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use sui_types::full_checkpoint_content::ExecutedTransaction;
 
+/// Single-transaction level thresholds in points; every signal at its top tier awards 120
+const PRICE_SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+    120,
+);
+
+/// Pump-and-dump level thresholds in points; every signal at its top tier awards 100
+const PUMP_SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+    100,
+);
+
 /// TWAP information from oracle update events
 #[derive(Debug, Clone)]
 struct TWAPInfo {
     pool_id: String,
-    twap_price: u64,
-    spot_price: u64,
-    deviation_bps: u64,
+    twap_price_a: u64,
+    spot_price_a: u64,
+    twap_price_b: u64,
+    spot_price_b: u64,
+    reported_deviation_bps: u64,
+}
+
+impl TWAPInfo {
+    /// Spot vs TWAP deviation of the A/B price
+    fn deviation_a_bps(&self) -> u64 {
+        deviation_bps(self.spot_price_a, self.twap_price_a)
+    }
+
+    /// Spot vs TWAP deviation of the B/A price
+    fn deviation_b_bps(&self) -> u64 {
+        deviation_bps(self.spot_price_b, self.twap_price_b)
+    }
+
+    /// Largest deviation in either direction (including the oracle's own figure)
+    fn max_deviation_bps(&self) -> u64 {
+        self.deviation_a_bps()
+            .max(self.deviation_b_bps())
+            .max(self.reported_deviation_bps)
+    }
+}
+
+/// TWAP deviation of one pool across all updates in a transaction,
+/// correlated with the swaps on that pool
+#[derive(Debug, Clone)]
+struct PoolTwapDeviation {
+    pool_id: String,
+    update_count: usize,
+    deviation_a_bps: u64,
+    deviation_b_bps: u64,
+    /// Update with the largest deviation
+    worst_update: TWAPInfo,
+    swap_count: usize,
+    max_price_impact: u64,
+}
+
+impl PoolTwapDeviation {
+    fn max_deviation_bps(&self) -> u64 {
+        self.worst_update.max_deviation_bps()
+    }
 }
 
 /// Swap information for price impact analysis
@@ -25,6 +85,43 @@ struct SwapImpact {
     reserve_b: u64,    // After swap
 }
 
+/// Swap recorded in the rolling per-pool price history
+#[derive(Debug, Clone)]
+struct PricePoint {
+    tx_digest: String,
+    sender: String,
+    checkpoint: i64,
+    timestamp_ms: i64,
+    a_to_b: bool,
+    amount_in: u64,
+    amount_out: u64,
+    reserves_before: Reserves,
+    reserves_after: Reserves,
+}
+
+/// Accumulation over several transactions followed by a reversal sell
+#[derive(Debug, Clone)]
+struct PumpAndDump {
+    pool_id: String,
+    pump_swaps: Vec<PricePoint>,
+    dump: PricePoint,
+    /// Price of the pumped token before the first buy (scaled 1e9)
+    price_before: u128,
+    /// Highest price of the pumped token reached before the dump (scaled 1e9)
+    peak_price: u128,
+    cumulative_move_bps: u64,
+    /// Dump proceeds minus pro-rata cost of the tokens sold, in the pump's input token
+    realized_profit: i128,
+    cluster: AddressCluster,
+}
+
+/// Rolling per-pool swap history for cross-transaction patterns
+#[derive(Debug, Default)]
+struct PriceHistory {
+    pools: HashMap<String, VecDeque<PricePoint>>,
+    last_sweep_ms: i64,
+}
+
 /// Price manipulation analyzer with TWAP deviation and impact scoring
 pub struct PriceAnalyzer {
     // Thresholds for detection
@@ -33,6 +130,15 @@ pub struct PriceAnalyzer {
     twap_deviation_threshold: u64,        // 5% (500 bps)
     high_twap_deviation_threshold: u64,   // 10% (1000 bps)
     large_trade_ratio: f64,               // 0.15 (15% of pool depth)
+    // Cross-transaction pump-and-dump state and thresholds
+    history: Mutex<PriceHistory>,
+    // Shared address links, fed with every checkpoint transaction by the handler
+    clusterer: Arc<Mutex<AddressClusterer>>,
+    pump_window_ms: i64,
+    max_history_per_pool: usize,
+    min_pump_transactions: usize,
+    min_pump_move_bps: u64,
+    min_dump_share_bps: u64,
 }
 
 impl PriceAnalyzer {
@@ -43,28 +149,55 @@ impl PriceAnalyzer {
             twap_deviation_threshold: 500,         // 5%
             high_twap_deviation_threshold: 1000,   // 10%
             large_trade_ratio: 0.15,               // 15% of pool
+            history: Mutex::new(PriceHistory::default()),
+            clusterer: Arc::new(Mutex::new(AddressClusterer::new())),
+            pump_window_ms: 60 * 60 * 1000,        // 1 hour
+            max_history_per_pool: 512,
+            min_pump_transactions: 3,
+            min_pump_move_bps: 1000,               // 10% cumulative move
+            min_dump_share_bps: 5000,              // Sells >= 50% of accumulated tokens
         }
     }
 
-    /// Main analysis function implementing TWAP deviation + trade impact scoring
+    /// Use an address clusterer shared with other detectors
+    pub fn with_clusterer(mut self, clusterer: Arc<Mutex<AddressClusterer>>) -> Self {
+        self.clusterer = clusterer;
+        self
+    }
+
+    /// Run single-transaction and cross-transaction price manipulation checks
     pub fn analyze(
         &self,
         tx: &ExecutedTransaction,
         context: &DetectionContext,
-    ) -> Option<RiskEvent> {
-        // Step 1: Check for TWAP deviation signals (from oracle)
-        let twap_info = self.extract_twap_info(tx);
+    ) -> Vec<RiskEvent> {
+        let mut events = Vec::new();
+        events.extend(self.analyze_transaction(tx, context));
+        events.extend(self.analyze_price_history(tx, context));
+        events
+    }
 
-        // Step 2: Extract swap events for direct price impact analysis
+    /// Single-transaction analysis implementing TWAP deviation + trade impact scoring
+    fn analyze_transaction(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        // Step 1: Extract swap events for direct price impact analysis
         let swaps = self.extract_swap_impacts(tx);
 
+        // Step 2: Check for TWAP deviation signals (from oracle), per pool and direction
+        let twap_updates = self.extract_twap_updates(tx);
+        let twap_pools = self.correlate_twap_deviations(&twap_updates, &swaps);
+        let twap_info = twap_pools.first();
+
         // Need at least one signal to proceed
         if twap_info.is_none() && swaps.is_empty() {
             return None;
         }
 
         // Step 3: Calculate risk score using multiple signals
-        let mut risk_score = 0u32;
+        let mut signals = Vec::new();
         let mut max_price_impact = 0u64;
         let mut max_swap_to_depth_ratio = 0.0f64;
         let mut twap_deviation = 0u64;
@@ -83,57 +216,66 @@ impl PriceAnalyzer {
             }
 
             // Score based on price impact
+            let impact = max_price_impact as f64;
             if max_price_impact >= self.critical_price_impact_threshold {
-                risk_score += 40;
+                signals.push(Signal::new("price_impact_bps", impact, self.critical_price_impact_threshold as f64, 40));
             } else if max_price_impact >= self.high_price_impact_threshold {
-                risk_score += 30;
+                signals.push(Signal::new("price_impact_bps", impact, self.high_price_impact_threshold as f64, 30));
             } else if max_price_impact >= 500 {
-                risk_score += 15;
+                signals.push(Signal::new("price_impact_bps", impact, 500.0, 15));
             }
 
             // Score based on trade size relative to pool
+            let depth_bps = max_swap_to_depth_ratio * 10000.0;
             if max_swap_to_depth_ratio > 0.3 {
-                risk_score += 25;
+                signals.push(Signal::new("swap_to_depth_bps", depth_bps, 3000.0, 25));
             } else if max_swap_to_depth_ratio > self.large_trade_ratio {
-                risk_score += 15;
+                signals.push(Signal::new("swap_to_depth_bps", depth_bps, self.large_trade_ratio * 10000.0, 15));
             }
         }
 
-        // Signal 2: TWAP deviation (if oracle exists)
-        if let Some(twap) = &twap_info {
-            twap_deviation = twap.deviation_bps;
+        // Signal 2: TWAP deviation (if oracle exists) on the worst pool
+        if let Some(twap) = twap_info {
+            twap_deviation = twap.max_deviation_bps();
 
+            let deviation = twap_deviation as f64;
             if twap_deviation >= self.critical_price_impact_threshold {
-                risk_score += 25;
+                signals.push(Signal::new("twap_deviation_bps", deviation, self.critical_price_impact_threshold as f64, 25));
             } else if twap_deviation >= self.high_twap_deviation_threshold {
-                risk_score += 15;
+                signals.push(Signal::new("twap_deviation_bps", deviation, self.high_twap_deviation_threshold as f64, 15));
             } else if twap_deviation >= self.twap_deviation_threshold {
-                risk_score += 5;
+                signals.push(Signal::new("twap_deviation_bps", deviation, self.twap_deviation_threshold as f64, 5));
             }
         }
 
+        // Several pools pushed away from their TWAP in one transaction
+        let deviated_pools = twap_pools
+            .iter()
+            .filter(|p| p.max_deviation_bps() >= self.twap_deviation_threshold)
+            .count();
+        if deviated_pools >= 2 {
+            signals.push(Signal::new("twap_deviated_pools", deviated_pools as f64, 2.0, 10));
+        }
+
         // Signal 3: Check for explicit deviation detection from oracle
         if self.has_deviation_detected_event(tx) {
-            risk_score += 10;
+            signals.push(Signal::flag("oracle_deviation_event", 10));
         }
 
         // Signal 4: Multiple large swaps in same direction (pump pattern)
         if swaps.len() >= 2 && self.is_pump_pattern(&swaps) {
-            risk_score += 10;
+            signals.push(Signal::flag("pump_pattern", 10));
         }
 
+        let risk_score = Signal::total(&signals);
+
         // Step 4: Classify risk level
         if risk_score < 25 {
             // Below threshold, likely normal volatility
             return None;
         }
 
-        let risk_level = match risk_score {
-            25..=49 => RiskLevel::Low,
-            50..=69 => RiskLevel::Medium,
-            70..=84 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
+        let risk_level = PRICE_SCORE_SCALE.level(risk_score);
 
         // Step 5: Create detailed risk event
         let description = if twap_info.is_some() {
@@ -163,43 +305,369 @@ impl PriceAnalyzer {
 
         // Add detailed metrics
         event = event
-            .with_detail("max_price_impact", serde_json::json!(format_bps(max_price_impact)))
-            .with_detail("swap_count", serde_json::json!(swaps.len()))
-            .with_detail(
-                "swap_to_depth_ratio",
-                serde_json::json!(format!("{:.2}%", max_swap_to_depth_ratio * 100.0)),
-            )
-            .with_detail("risk_score", serde_json::json!(risk_score));
+            .with_metric("max_price_impact_bps", max_price_impact as f64)
+            .with_metric("swap_count", swaps.len() as f64)
+            .with_metric("swap_to_depth_bps", max_swap_to_depth_ratio * 10000.0)
+            .with_scaled_signals(signals, &PRICE_SCORE_SCALE);
 
         if let Some(twap) = twap_info {
+            // Report the worst pool in the direction that deviated most
+            let worst = &twap.worst_update;
+            let (spot_price, twap_price) = if worst.deviation_b_bps() > worst.deviation_a_bps() {
+                (worst.spot_price_b, worst.twap_price_b)
+            } else {
+                (worst.spot_price_a, worst.twap_price_a)
+            };
+
+            let pools: Vec<serde_json::Value> = twap_pools
+                .iter()
+                .map(|p| serde_json::json!({
+                    "pool_id": p.pool_id,
+                    "twap_updates": p.update_count,
+                    "deviation_a_bps": p.deviation_a_bps,
+                    "deviation_b_bps": p.deviation_b_bps,
+                    "twap_price_a": p.worst_update.twap_price_a,
+                    "spot_price_a": p.worst_update.spot_price_a,
+                    "twap_price_b": p.worst_update.twap_price_b,
+                    "spot_price_b": p.worst_update.spot_price_b,
+                    "swap_count": p.swap_count,
+                    "max_price_impact_bps": p.max_price_impact,
+                }))
+                .collect();
+
             event = event
-                .with_detail("twap_deviation", serde_json::json!(format_bps(twap.deviation_bps)))
-                .with_detail("spot_price", serde_json::json!(format_currency(twap.spot_price)))
-                .with_detail("twap_price", serde_json::json!(format_currency(twap.twap_price)))
-                .with_detail("pool_id", serde_json::json!(twap.pool_id));
+                .with_detail("pool_id", serde_json::json!(twap.pool_id))
+                .with_detail("twap_pools", serde_json::json!(pools))
+                .with_metric("twap_deviation_bps", twap.max_deviation_bps() as f64)
+                .with_metric("spot_price", spot_price as f64)
+                .with_metric("twap_price", twap_price as f64)
+                .with_metric("twap_update_count", twap_updates.len() as f64)
+                .with_metric("twap_deviated_pools", deviated_pools as f64);
         }
 
         Some(event)
     }
 
-    /// Extract TWAP information from oracle update events
-    fn extract_twap_info(&self, tx: &ExecutedTransaction) -> Option<TWAPInfo> {
-        let events = tx.events.as_ref()?;
+    /// Update per-pool price history and look for pump-and-dump completions
+    fn analyze_price_history(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let points = self.extract_price_points(tx, context);
+
+        let clusters: Vec<AddressCluster> = {
+            let clusterer = self.clusterer.lock().unwrap();
+            points
+                .iter()
+                .map(|(_, point)| clusterer.cluster_of(&point.sender, point.timestamp_ms))
+                .collect()
+        };
+
+        let mut history = self.history.lock().unwrap();
+        let mut events = Vec::new();
+
+        for ((pool_id, point), cluster) in points.into_iter().zip(clusters) {
+            let pool_history = history.pools.entry(pool_id.clone()).or_default();
+
+            if let Some(pattern) = self.detect_pump_and_dump(&pool_id, &point, &cluster, pool_history) {
+                events.push(self.create_pump_and_dump_event(&pattern));
+            }
+
+            // History is time-ordered: drop expired points from the front
+            while pool_history
+                .front()
+                .is_some_and(|oldest| point.timestamp_ms - oldest.timestamp_ms > self.pump_window_ms)
+            {
+                pool_history.pop_front();
+            }
+            if pool_history.len() >= self.max_history_per_pool {
+                pool_history.pop_front();
+            }
+            pool_history.push_back(point);
+        }
+
+        // Release pools that stopped trading
+        if context.timestamp_ms - history.last_sweep_ms > self.pump_window_ms {
+            history.last_sweep_ms = context.timestamp_ms;
+            let window = self.pump_window_ms;
+            let now_ms = context.timestamp_ms;
+            history.pools.retain(|_, pool_points| {
+                pool_points.back().is_some_and(|latest| now_ms - latest.timestamp_ms <= window)
+            });
+        }
+
+        events
+    }
+
+    /// Check whether `dump` reverses a sustained one-directional accumulation
+    ///
+    /// The cluster's swaps in the window must all push the pool the same way
+    /// (any earlier sell resets the run), span at least `min_pump_transactions`
+    /// transactions and move the pumped token's price by `min_pump_move_bps`.
+    fn detect_pump_and_dump(
+        &self,
+        pool_id: &str,
+        dump: &PricePoint,
+        cluster: &AddressCluster,
+        history: &VecDeque<PricePoint>,
+    ) -> Option<PumpAndDump> {
+        let pump_direction = !dump.a_to_b;
+        let window_start = dump.timestamp_ms - self.pump_window_ms;
+
+        let mut pump_swaps: Vec<&PricePoint> = Vec::new();
+        for point in history.iter().filter(|p| p.timestamp_ms >= window_start) {
+            if !cluster.contains(&point.sender) {
+                continue;
+            }
+            if point.a_to_b == pump_direction {
+                pump_swaps.push(point);
+            } else {
+                // Cluster already sold: the accumulation run restarts
+                pump_swaps.clear();
+            }
+        }
+
+        let mut pump_txs: Vec<&String> = pump_swaps.iter().map(|p| &p.tx_digest).collect();
+        pump_txs.dedup();
+        if pump_txs.len() < self.min_pump_transactions {
+            return None;
+        }
+
+        // Cumulative price move of the pumped token
+        let first = pump_swaps.first()?;
+        let price_before = pumped_token_price(first.reserves_before, pump_direction);
+        let peak_price = pump_swaps
+            .iter()
+            .map(|p| pumped_token_price(p.reserves_after, pump_direction))
+            .chain(std::iter::once(pumped_token_price(dump.reserves_before, pump_direction)))
+            .max()
+            .unwrap_or(0);
+
+        if price_before == 0 || peak_price <= price_before {
+            return None;
+        }
+        let cumulative_move_bps = ((peak_price - price_before) * 10000 / price_before) as u64;
+        if cumulative_move_bps < self.min_pump_move_bps {
+            return None;
+        }
+
+        // Reversal must unload a meaningful share of the accumulated tokens
+        let accumulated: u64 = pump_swaps.iter().map(|p| p.amount_out).sum();
+        let cost: u64 = pump_swaps.iter().map(|p| p.amount_in).sum();
+        if accumulated == 0
+            || (dump.amount_in as u128) * 10000 < accumulated as u128 * self.min_dump_share_bps as u128
+        {
+            return None;
+        }
+
+        let sold = dump.amount_in.min(accumulated);
+        let cost_basis = cost as u128 * sold as u128 / accumulated as u128;
+        let realized_profit = dump.amount_out as i128 - cost_basis as i128;
+
+        Some(PumpAndDump {
+            pool_id: pool_id.to_string(),
+            pump_swaps: pump_swaps.into_iter().cloned().collect(),
+            dump: dump.clone(),
+            price_before,
+            peak_price,
+            cumulative_move_bps,
+            realized_profit,
+            cluster: cluster.clone(),
+        })
+    }
+
+    /// Create risk event from detected pump-and-dump
+    fn create_pump_and_dump_event(&self, pattern: &PumpAndDump) -> RiskEvent {
+        // Pattern itself is already confirmed by thresholds
+        let mut signals = vec![Signal::new(
+            "pump_transactions",
+            pattern.pump_swaps.len() as f64,
+            self.min_pump_transactions as f64,
+            20,
+        )];
+
+        let price_move = pattern.cumulative_move_bps as f64;
+        if pattern.cumulative_move_bps >= 3000 {
+            signals.push(Signal::new("cumulative_move_bps", price_move, 3000.0, 40));
+        } else if pattern.cumulative_move_bps >= 2000 {
+            signals.push(Signal::new("cumulative_move_bps", price_move, 2000.0, 30));
+        } else {
+            signals.push(Signal::new("cumulative_move_bps", price_move, self.min_pump_move_bps as f64, 20));
+        }
+
+        if pattern.realized_profit > 0 {
+            signals.push(Signal::new("realized_profit", pattern.realized_profit as f64, 0.0, 20));
+        }
+
+        if pattern.pump_swaps.len() >= 5 {
+            signals.push(Signal::new("pump_swaps", pattern.pump_swaps.len() as f64, 5.0, 10));
+        }
+
+        // Spreading buys across addresses hides the accumulation
+        if pattern.cluster.is_multi_address() {
+            signals.push(Signal::new("cluster_size", pattern.cluster.members.len() as f64, 2.0, 10));
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        let risk_level = PUMP_SCORE_SCALE.level(risk_score);
+
+        let first = &pattern.pump_swaps[0];
+        let window_ms = pattern.dump.timestamp_ms - first.timestamp_ms;
+        let pump_direction = !pattern.dump.a_to_b;
+        let pumped_token = if pump_direction { "TokenB" } else { "TokenA" };
+        let profit_token = if pump_direction { "TokenA" } else { "TokenB" };
+        let pump_txs: Vec<&String> = pattern.pump_swaps.iter().map(|p| &p.tx_digest).collect();
+        let pump_senders: Vec<&String> = pattern.pump_swaps.iter().map(|p| &p.sender).collect();
+
+        let description = format!(
+            "Pump and dump: {} buys moved {} {:.2}% over {}ms, reversal sell realized {} {}",
+            pattern.pump_swaps.len(),
+            pumped_token,
+            pattern.cumulative_move_bps as f64 / 100.0,
+            window_ms,
+            format_amount(pattern.realized_profit as f64),
+            profit_token
+        );
+
+        RiskEvent::new(
+            RiskType::PumpAndDump,
+            risk_level,
+            pattern.dump.tx_digest.clone(),
+            pattern.dump.sender.clone(),
+            pattern.dump.checkpoint,
+            pattern.dump.timestamp_ms,
+            description,
+        )
+        .with_detail("pool_id", serde_json::json!(pattern.pool_id))
+        .with_detail("pumped_token", serde_json::json!(pumped_token))
+        .with_detail("pump_txs", serde_json::json!(pump_txs))
+        .with_detail("pump_senders", serde_json::json!(pump_senders))
+        .with_detail("dump_tx", serde_json::json!(pattern.dump.tx_digest))
+        .with_detail("profit_token", serde_json::json!(profit_token))
+        .with_detail("cluster", serde_json::json!(pattern.cluster.members))
+        .with_metric("pump_count", pattern.pump_swaps.len() as f64)
+        .with_metric("window_start_ms", first.timestamp_ms as f64)
+        .with_metric("window_end_ms", pattern.dump.timestamp_ms as f64)
+        .with_metric("window_ms", window_ms as f64)
+        .with_metric("window_start_checkpoint", first.checkpoint as f64)
+        .with_metric("price_before", pattern.price_before as f64)
+        .with_metric("peak_price", pattern.peak_price as f64)
+        .with_metric("cumulative_move_bps", pattern.cumulative_move_bps as f64)
+        .with_metric("realized_profit", pattern.realized_profit as f64)
+        .with_scaled_signals(signals, &PUMP_SCORE_SCALE)
+    }
+
+    /// Extract swaps with pre/post reserves for the price history
+    fn extract_price_points(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<(String, PricePoint)> {
+        let events = match &tx.events {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+
+        let mut points = Vec::new();
+
+        for event in &events.data {
+            if event.type_.name.as_str() == "SwapExecuted" {
+                if let Some(parsed) = SwapExecuted::from_event(event) {
+                    let reserves_after = Reserves::new(parsed.reserve_a, parsed.reserve_b);
+                    let reserves_before = Reserves::before_swap(
+                        reserves_after,
+                        parsed.token_in,
+                        parsed.amount_in,
+                        parsed.amount_out,
+                    );
+
+                    points.push((
+                        parsed.pool_id.to_string(),
+                        PricePoint {
+                            tx_digest: context.tx_digest.clone(),
+                            sender: parsed.sender.to_string(),
+                            checkpoint: context.checkpoint,
+                            timestamp_ms: context.timestamp_ms,
+                            a_to_b: parsed.token_in,
+                            amount_in: parsed.amount_in,
+                            amount_out: parsed.amount_out,
+                            reserves_before,
+                            reserves_after,
+                        },
+                    ));
+                }
+            }
+        }
+
+        points
+    }
+
+    /// Extract every TWAP update emitted by the transaction
+    fn extract_twap_updates(&self, tx: &ExecutedTransaction) -> Vec<TWAPInfo> {
+        let events = match &tx.events {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+
+        let mut updates = Vec::new();
 
         for event in &events.data {
             if event.type_.name.as_str() == "TWAPUpdated" {
                 if let Some(parsed) = TWAPUpdated::from_event(event) {
-                    return Some(TWAPInfo {
+                    updates.push(TWAPInfo {
                         pool_id: parsed.pool_id.to_string(),
-                        twap_price: parsed.twap_price_a,
-                        spot_price: parsed.spot_price_a,
-                        deviation_bps: parsed.price_deviation,
+                        twap_price_a: parsed.twap_price_a,
+                        spot_price_a: parsed.spot_price_a,
+                        twap_price_b: parsed.twap_price_b,
+                        spot_price_b: parsed.spot_price_b,
+                        reported_deviation_bps: parsed.price_deviation,
                     });
                 }
             }
         }
 
-        None
+        updates
+    }
+
+    /// Group TWAP updates by pool and attach the swaps on the same pool
+    ///
+    /// Pools are returned sorted by largest deviation first.
+    fn correlate_twap_deviations(
+        &self,
+        updates: &[TWAPInfo],
+        swaps: &[SwapImpact],
+    ) -> Vec<PoolTwapDeviation> {
+        let mut pools: Vec<PoolTwapDeviation> = Vec::new();
+
+        for update in updates {
+            match pools.iter_mut().find(|p| p.pool_id == update.pool_id) {
+                Some(pool) => {
+                    pool.update_count += 1;
+                    pool.deviation_a_bps = pool.deviation_a_bps.max(update.deviation_a_bps());
+                    pool.deviation_b_bps = pool.deviation_b_bps.max(update.deviation_b_bps());
+                    if update.max_deviation_bps() > pool.worst_update.max_deviation_bps() {
+                        pool.worst_update = update.clone();
+                    }
+                }
+                None => {
+                    let pool_swaps = swaps.iter().filter(|s| s.pool_id == update.pool_id);
+                    pools.push(PoolTwapDeviation {
+                        pool_id: update.pool_id.clone(),
+                        update_count: 1,
+                        deviation_a_bps: update.deviation_a_bps(),
+                        deviation_b_bps: update.deviation_b_bps(),
+                        worst_update: update.clone(),
+                        swap_count: pool_swaps.clone().count(),
+                        max_price_impact: pool_swaps.map(|s| s.price_impact).max().unwrap_or(0),
+                    });
+                }
+            }
+        }
+
+        pools.sort_by(|a, b| b.max_deviation_bps().cmp(&a.max_deviation_bps()));
+        pools
     }
 
     /// Extract swap impacts from swap events
@@ -256,20 +724,28 @@ impl PriceAnalyzer {
     }
 }
 
-fn format_currency(amount: u64) -> String {
-    let s = amount.to_string();
-    let mut res = String::new();
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            res.insert(0, ',');
-        }
-        res.insert(0, c);
+/// Relative deviation of `spot` from `twap` in basis points
+fn deviation_bps(spot: u64, twap: u64) -> u64 {
+    if twap == 0 {
+        return 0;
     }
-    res
+
+    (spot.abs_diff(twap) as u128 * 10000 / twap as u128).min(u64::MAX as u128) as u64
 }
 
-fn format_bps(bps: u64) -> String {
-    format!("{:.2}%", bps as f64 / 100.0)
+/// Price of the token bought by an `a_to_b` swap, in units of the token sold (scaled 1e9)
+fn pumped_token_price(reserves: Reserves, a_to_b: bool) -> u128 {
+    let (reserve_sold, reserve_bought) = if a_to_b {
+        (reserves.a, reserves.b)
+    } else {
+        (reserves.b, reserves.a)
+    };
+
+    if reserve_bought == 0 {
+        return 0;
+    }
+
+    reserve_sold as u128 * 1_000_000_000 / reserve_bought as u128
 }
 
 impl Default for PriceAnalyzer {
@@ -281,6 +757,7 @@ impl Default for PriceAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::amm;
 
     #[test]
     fn test_thresholds() {
@@ -342,4 +819,145 @@ mod tests {
 
         assert!(!analyzer.is_pump_pattern(&swaps));
     }
+
+    fn twap_update(pool_id: &str, spot_a: u64, twap_a: u64, spot_b: u64, twap_b: u64) -> TWAPInfo {
+        TWAPInfo {
+            pool_id: pool_id.to_string(),
+            twap_price_a: twap_a,
+            spot_price_a: spot_a,
+            twap_price_b: twap_b,
+            spot_price_b: spot_b,
+            reported_deviation_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_twap_deviation_both_directions() {
+        // A/B barely moved, B/A moved 25%
+        let update = twap_update("pool1", 1_010_000_000, 1_000_000_000, 1_250_000_000, 1_000_000_000);
+        assert_eq!(update.deviation_a_bps(), 100);
+        assert_eq!(update.deviation_b_bps(), 2500);
+        assert_eq!(update.max_deviation_bps(), 2500);
+    }
+
+    #[test]
+    fn test_twap_updates_correlated_per_pool() {
+        let analyzer = PriceAnalyzer::new();
+
+        let updates = vec![
+            twap_update("pool1", 1_050_000_000, 1_000_000_000, 950_000_000, 1_000_000_000),
+            // Later update on the same pool deviates further
+            twap_update("pool1", 1_300_000_000, 1_000_000_000, 770_000_000, 1_000_000_000),
+            twap_update("pool2", 1_000_000_000, 1_000_000_000, 1_120_000_000, 1_000_000_000),
+        ];
+        let swaps = vec![SwapImpact {
+            pool_id: "pool2".to_string(),
+            amount_in: 1000,
+            amount_out: 900,
+            price_impact: 700,
+            reserve_a: 10000,
+            reserve_b: 10000,
+        }];
+
+        let pools = analyzer.correlate_twap_deviations(&updates, &swaps);
+
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[0].pool_id, "pool1");
+        assert_eq!(pools[0].update_count, 2);
+        assert_eq!(pools[0].max_deviation_bps(), 3000);
+        assert_eq!(pools[0].swap_count, 0);
+        assert_eq!(pools[1].pool_id, "pool2");
+        assert_eq!(pools[1].deviation_b_bps, 1200);
+        assert_eq!(pools[1].swap_count, 1);
+        assert_eq!(pools[1].max_price_impact, 700);
+    }
+
+    /// Execute a swap on `reserves` and record it as a price point
+    fn swap_point(
+        reserves: &mut Reserves,
+        tx_digest: &str,
+        sender: &str,
+        timestamp_ms: i64,
+        a_to_b: bool,
+        amount_in: u64,
+    ) -> PricePoint {
+        let amount_out = amm::amount_out(*reserves, a_to_b, amount_in, amm::DEFAULT_FEE_BPS);
+        let reserves_before = *reserves;
+        *reserves = reserves.after_swap(a_to_b, amount_in, amount_out);
+
+        PricePoint {
+            tx_digest: tx_digest.to_string(),
+            sender: sender.to_string(),
+            checkpoint: timestamp_ms / 1000,
+            timestamp_ms,
+            a_to_b,
+            amount_in,
+            amount_out,
+            reserves_before,
+            reserves_after: *reserves,
+        }
+    }
+
+    fn single_address(address: &str) -> AddressCluster {
+        AddressCluster {
+            members: vec![address.to_string()],
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pump_and_dump_across_transactions() {
+        let analyzer = PriceAnalyzer::new();
+        let mut reserves = Reserves::new(1_000_000_000, 1_000_000_000);
+        let mut history = VecDeque::new();
+
+        // Three buys of TokenB spread over separate transactions
+        let mut accumulated = 0;
+        for i in 0..3 {
+            let buy = swap_point(&mut reserves, &format!("pump{}", i), "pumper", 1_000_000 + i * 60_000, true, 50_000_000);
+            accumulated += buy.amount_out;
+            history.push_back(buy);
+        }
+        // Retail buys in at the inflated price
+        history.push_back(swap_point(&mut reserves, "retail", "retail", 1_200_000, true, 100_000_000));
+
+        let dump = swap_point(&mut reserves, "dump", "pumper", 1_300_000, false, accumulated);
+        let pattern = analyzer
+            .detect_pump_and_dump("pool1", &dump, &single_address("pumper"), &history)
+            .unwrap();
+
+        assert_eq!(pattern.pump_swaps.len(), 3);
+        assert!(pattern.cumulative_move_bps >= analyzer.min_pump_move_bps);
+        assert!(pattern.realized_profit > 0);
+
+        let event = analyzer.create_pump_and_dump_event(&pattern);
+        assert_eq!(event.risk_type, RiskType::PumpAndDump);
+        assert_eq!(event.metrics["pump_count"], 3.0);
+        assert_eq!(event.signals[0].name, "pump_transactions");
+        assert_eq!(event.score, PUMP_SCORE_SCALE.score(event.risk_score()));
+        assert_eq!(RiskLevel::from_score(event.score), event.risk_level);
+    }
+
+    #[test]
+    fn test_pump_run_resets_on_earlier_sell() {
+        let analyzer = PriceAnalyzer::new();
+        let mut reserves = Reserves::new(1_000_000_000, 1_000_000_000);
+        let mut history = VecDeque::new();
+
+        let mut accumulated = 0;
+        for i in 0..3 {
+            let buy = swap_point(&mut reserves, &format!("pump{}", i), "pumper", 1_000_000 + i * 60_000, true, 50_000_000);
+            accumulated += buy.amount_out;
+            history.push_back(buy);
+            if i == 1 {
+                // Sell in the middle breaks the one-directional run
+                history.push_back(swap_point(&mut reserves, "sell", "pumper", 1_100_000, false, 1_000));
+            }
+        }
+
+        let dump = swap_point(&mut reserves, "dump", "pumper", 1_300_000, false, accumulated);
+        assert!(analyzer
+            .detect_pump_and_dump("pool1", &dump, &single_address("pumper"), &history)
+            .is_none());
+    }
 }
@@ -0,0 +1,324 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Failed-Transaction Probing Detection: Repeated Aborted Calls into Watched Packages
+
+use crate::models::{EsExecutionFailure, EsFlattener};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use super::governance::GovernanceWatchlist;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use sui_types::transaction::{Command, TransactionData, TransactionDataAPI, TransactionKind};
+
+/// Level thresholds in points; every signal at its top tier awards 100
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+    100,
+);
+
+/// Failed transaction calling a watched package
+#[derive(Debug, Clone)]
+struct FailedAttempt {
+    tx_digest: String,
+    timestamp_ms: i64,
+    /// Watched `package::module::function` targets of the PTB
+    targets: Vec<String>,
+    failure: EsExecutionFailure,
+}
+
+#[derive(Debug, Default)]
+struct ProbingState {
+    attempts: HashMap<String, VecDeque<FailedAttempt>>,
+    // Sender → timestamp of the last probing alert
+    last_alert_ms: HashMap<String, i64>,
+}
+
+/// Failed-transaction probing analyzer
+///
+/// Aborted transactions emit no events, so this analyzer works from the PTB's
+/// `MoveCall` commands and the `ExecutionStatus` in effects. It flags:
+/// 1. Repeated failed calls into watched packages from one sender (probing)
+/// 2. A successful call from a sender right after such probing
+pub struct ProbingAnalyzer {
+    watchlist: GovernanceWatchlist,
+    state: Mutex<ProbingState>,
+    /// Window in which failures from one sender are counted
+    window_ms: i64,
+    /// Failures in the window to raise a probing alert
+    min_failures: usize,
+    /// Failures kept per sender
+    max_attempts_per_sender: usize,
+}
+
+impl ProbingAnalyzer {
+    pub fn new(watchlist: GovernanceWatchlist) -> Self {
+        Self {
+            watchlist,
+            state: Mutex::new(ProbingState::default()),
+            window_ms: 10 * 60 * 1000, // 10 minutes
+            min_failures: 3,
+            max_attempts_per_sender: 64,
+        }
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let targets = self.watched_targets(&tx.transaction);
+        if targets.is_empty() {
+            return None;
+        }
+
+        match EsFlattener::extract_failure(&tx.effects) {
+            None => self.record_success(&targets, context),
+            Some(failure) => {
+                let attempt = FailedAttempt {
+                    tx_digest: context.tx_digest.clone(),
+                    timestamp_ms: context.timestamp_ms,
+                    targets,
+                    failure,
+                };
+                self.record_failure(attempt, context)
+            }
+        }
+    }
+
+    /// `MoveCall` targets in watched packages
+    fn watched_targets(&self, transaction_data: &TransactionData) -> Vec<String> {
+        let mut targets = Vec::new();
+
+        if let TransactionKind::ProgrammableTransaction(pt) = transaction_data.kind() {
+            for cmd in pt.commands.iter() {
+                if let Command::MoveCall(move_call) = cmd {
+                    if self.watchlist.is_watched_package(&move_call.package.to_string()) {
+                        targets.push(format!(
+                            "{}::{}::{}",
+                            move_call.package, move_call.module, move_call.function
+                        ));
+                    }
+                }
+            }
+        }
+
+        targets
+    }
+
+    /// Record a failure and alert once per window when the sender keeps failing
+    fn record_failure(&self, attempt: FailedAttempt, context: &DetectionContext) -> Option<RiskEvent> {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        Self::prune(state, context.timestamp_ms - self.window_ms);
+
+        let attempts = state.attempts.entry(context.sender.clone()).or_default();
+        if attempts.len() >= self.max_attempts_per_sender {
+            attempts.pop_front();
+        }
+        attempts.push_back(attempt);
+
+        if attempts.len() < self.min_failures || state.last_alert_ms.contains_key(&context.sender) {
+            return None;
+        }
+
+        let attempts: Vec<FailedAttempt> = state.attempts[&context.sender].iter().cloned().collect();
+        state.last_alert_ms.insert(context.sender.clone(), context.timestamp_ms);
+
+        self.create_probing_event(&attempts, None, context)
+    }
+
+    /// Alert when a sender succeeds right after probing
+    fn record_success(&self, targets: &[String], context: &DetectionContext) -> Option<RiskEvent> {
+        let mut state = self.state.lock().unwrap();
+        Self::prune(&mut state, context.timestamp_ms - self.window_ms);
+
+        // Probing ends with the success either way
+        let attempts: Vec<FailedAttempt> = state.attempts.remove(&context.sender)?.into();
+        state.last_alert_ms.remove(&context.sender);
+
+        if attempts.len() < self.min_failures {
+            return None;
+        }
+
+        self.create_probing_event(&attempts, Some(targets), context)
+    }
+
+    fn prune(state: &mut ProbingState, window_start: i64) {
+        state.attempts.retain(|_, attempts| {
+            while attempts.front().is_some_and(|a| a.timestamp_ms < window_start) {
+                attempts.pop_front();
+            }
+            !attempts.is_empty()
+        });
+        state.last_alert_ms.retain(|_, ts| *ts >= window_start);
+    }
+
+    /// Create risk event from a sender's failed attempts
+    fn create_probing_event(
+        &self,
+        attempts: &[FailedAttempt],
+        success_targets: Option<&[String]>,
+        context: &DetectionContext,
+    ) -> Option<RiskEvent> {
+        let abort_codes: HashSet<(Option<&str>, Option<&str>, u64)> = attempts
+            .iter()
+            .filter_map(|a| {
+                let f = &a.failure;
+                f.abort_code
+                    .map(|code| (f.abort_package.as_deref(), f.abort_module.as_deref(), code))
+            })
+            .collect();
+        let targets: HashSet<&str> = attempts
+            .iter()
+            .flat_map(|a| a.targets.iter().map(|t| t.as_str()))
+            .collect();
+
+        // Repeated failures already established
+        let failure_count = attempts.len() as f64;
+        let mut signals = vec![Signal::new("repeated_failures", failure_count, self.min_failures as f64, 30)];
+
+        if attempts.len() >= 10 {
+            signals.push(Signal::new("failure_count", failure_count, 10.0, 20));
+        } else if attempts.len() >= 5 {
+            signals.push(Signal::new("failure_count", failure_count, 5.0, 10));
+        }
+
+        // Working through different checks of the package
+        if abort_codes.len() >= 3 {
+            signals.push(Signal::new("varied_abort_codes", abort_codes.len() as f64, 3.0, 10));
+        }
+
+        if targets.len() >= 2 {
+            signals.push(Signal::new("multiple_targets", targets.len() as f64, 2.0, 10));
+        }
+
+        // The probe found a path that executes
+        if success_targets.is_some() {
+            signals.push(Signal::flag("success_after_failures", 30));
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let window_ms = attempts.last()?.timestamp_ms - attempts.first()?.timestamp_ms;
+        let description = match success_targets {
+            Some(_) => format!(
+                "Probing succeeded: {} failed attempts against watched packages in {}ms, then a successful call",
+                attempts.len(),
+                window_ms
+            ),
+            None => format!(
+                "Probing: {} failed attempts against watched packages in {}ms ({} distinct abort codes)",
+                attempts.len(),
+                window_ms,
+                abort_codes.len()
+            ),
+        };
+
+        let failures: Vec<serde_json::Value> = attempts
+            .iter()
+            .map(|a| serde_json::json!({
+                "tx_digest": a.tx_digest,
+                "timestamp_ms": a.timestamp_ms,
+                "targets": a.targets,
+                "failure": a.failure,
+            }))
+            .collect();
+
+        let mut targets: Vec<&str> = targets.into_iter().collect();
+        targets.sort();
+
+        let event = RiskEvent::new(
+            RiskType::FailedTransactionProbing,
+            risk_level,
+            context.tx_digest.clone(),
+            context.sender.clone(),
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("targets", serde_json::json!(targets))
+        .with_detail("failures", serde_json::json!(failures))
+        .with_detail("success_targets", serde_json::json!(success_targets))
+        .with_metric("failure_count", failure_count)
+        .with_metric("distinct_abort_codes", abort_codes.len() as f64)
+        .with_metric("window_ms", window_ms as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE);
+
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(tx: &str, timestamp_ms: i64, abort_code: u64) -> FailedAttempt {
+        FailedAttempt {
+            tx_digest: tx.to_string(),
+            timestamp_ms,
+            targets: vec!["0xpkg::compound_market::borrow".to_string()],
+            failure: EsExecutionFailure {
+                kind: "MoveAbort".to_string(),
+                command_index: Some(0),
+                abort_package: Some("0xpkg".to_string()),
+                abort_module: Some("compound_market".to_string()),
+                abort_function: Some("borrow".to_string()),
+                abort_function_index: Some(4),
+                abort_instruction: Some(17),
+                abort_code: Some(abort_code),
+            },
+        }
+    }
+
+    fn context(tx: &str, timestamp_ms: i64) -> DetectionContext {
+        DetectionContext::new(tx.to_string(), "0xa".to_string(), 1000, timestamp_ms)
+    }
+
+    #[test]
+    fn test_repeated_failures_alert_once() {
+        let analyzer = ProbingAnalyzer::new(GovernanceWatchlist::new());
+
+        let mut events = Vec::new();
+        for i in 0..5 {
+            let tx = format!("tx{}", i);
+            let ts = 1_000_000 + i * 1000;
+            events.extend(analyzer.record_failure(attempt(&tx, ts, i as u64), &context(&tx, ts)));
+        }
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].risk_type, RiskType::FailedTransactionProbing);
+        assert_eq!(events[0].metrics["failure_count"], 3.0);
+        let names: Vec<&str> = events[0].signals.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["repeated_failures", "varied_abort_codes"]);
+    }
+
+    #[test]
+    fn test_old_failures_expire() {
+        let analyzer = ProbingAnalyzer::new(GovernanceWatchlist::new());
+        analyzer.record_failure(attempt("tx0", 0, 1), &context("tx0", 0));
+        analyzer.record_failure(attempt("tx1", 1000, 1), &context("tx1", 1000));
+
+        let later = analyzer.window_ms + 2000;
+        assert!(analyzer.record_failure(attempt("tx2", later, 1), &context("tx2", later)).is_none());
+    }
+
+    #[test]
+    fn test_success_after_probing() {
+        let analyzer = ProbingAnalyzer::new(GovernanceWatchlist::new());
+        for i in 0..3 {
+            let tx = format!("tx{}", i);
+            analyzer.record_failure(attempt(&tx, 1000 * i, 2), &context(&tx, 1000 * i));
+        }
+
+        let targets = vec!["0xpkg::compound_market::borrow".to_string()];
+        let event = analyzer.record_success(&targets, &context("exploit", 5000)).unwrap();
+        assert_eq!(event.risk_level, RiskLevel::Medium);
+        assert!(event.signals.iter().any(|s| s.name == "success_after_failures"));
+
+        // History is consumed by the success
+        assert!(analyzer.record_success(&targets, &context("again", 6000)).is_none());
+    }
+}
@@ -2,10 +2,21 @@
 // Sandwich Attack Detection using Cross-Transaction Pattern Matching
 
 use sui_types::full_checkpoint_content::ExecutedTransaction;
-use std::collections::VecDeque;
-use std::sync::Mutex;
-use crate::risk::{RiskEvent, RiskLevel, RiskType, DetectionContext};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use crate::action::format::format_amount;
+use crate::risk::{RiskEvent, RiskLevel, RiskType, DetectionContext, ScoreScale, Signal};
 use crate::events::{SwapExecuted, EventParser};
+use super::amm::{self, Reserves};
+use super::cluster::{AddressCluster, AddressClusterer};
+use super::gas_price::{GasObservation, GasPriceMonitor};
+
+/// Level thresholds in points; the top profit and loss tiers plus every flag award 110
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 30), (RiskLevel::High, 50), (RiskLevel::Critical, 70)],
+    110,
+);
 
 /// Swap transaction pattern for sandwich detection
 #[derive(Debug, Clone)]
@@ -18,41 +29,148 @@ pub struct SwapPattern {
     pub token_in_direction: bool,  // true = A→B, false = B→A
     pub amount_in: u64,
     pub amount_out: u64,
+    pub fee_amount: u64,
     pub price_impact: u64,
+    pub reserve_a: u64,  // After swap
+    pub reserve_b: u64,  // After swap
+    pub gas_price: u64,
+    pub priority_gas: bool,  // Gas price outlier for the shared objects touched
+}
+
+impl SwapPattern {
+    /// Pool reserves right after this swap
+    pub fn reserves_after(&self) -> Reserves {
+        Reserves::new(self.reserve_a, self.reserve_b)
+    }
+
+    /// Pool reserves right before this swap
+    pub fn reserves_before(&self) -> Reserves {
+        Reserves::before_swap(
+            self.reserves_after(),
+            self.token_in_direction,
+            self.amount_in,
+            self.amount_out,
+        )
+    }
+
+    /// Pool fee rate inferred from the swap's fee amount
+    pub fn fee_bps(&self) -> u64 {
+        amm::infer_fee_bps(self.amount_in, self.fee_amount)
+    }
+}
+
+/// Attacker profit denominated in the token the front-run spent
+///
+/// Any leftover of the intermediate token (front-run output not sold back)
+/// is valued at the pool spot price after the back-run.
+#[derive(Debug, Clone, Default)]
+pub struct AttackerProfit {
+    /// true = profit in TokenA, false = profit in TokenB
+    pub denominated_in_a: bool,
+    /// Profit before swap fees
+    pub gross: i128,
+    /// Fee paid on the front-run
+    pub front_run_fee: u64,
+    /// Fee paid on the back-run, converted to the profit token
+    pub back_run_fee: u64,
+    /// Profit after fees
+    pub net: i128,
+}
+
+impl AttackerProfit {
+    pub fn total_fees(&self) -> u64 {
+        self.front_run_fee.saturating_add(self.back_run_fee)
+    }
+}
+
+/// Victim swap wrapped by a sandwich and its individual loss
+#[derive(Debug, Clone)]
+pub struct VictimImpact {
+    pub swap: SwapPattern,
+    /// What the victim would have received without the front-run
+    pub expected_out: u64,
+    /// Shortfall in the victim's output token
+    pub loss: u64,
+    pub loss_bps: u64,
 }
 
 /// Detected sandwich attack pattern
 #[derive(Debug, Clone)]
 pub struct SandwichMatch {
     pub front_run: SwapPattern,
-    pub victim: SwapPattern,
+    /// Victim swaps between front-run and back-run, in execution order
+    pub victims: Vec<VictimImpact>,
     pub back_run: SwapPattern,
-    pub attacker_profit: u64,
+    pub attacker_profit: AttackerProfit,
+    /// Sum of victim losses, in the victims' output token
+    pub total_extracted_value: u64,
+    /// Total loss relative to total expected output of all victims
     pub victim_loss_bps: u64,
+    /// Addresses linked to the back-run sender (includes the front-run sender)
+    pub attacker_cluster: AddressCluster,
+}
+
+/// Recent swaps indexed by pool, each pool kept in arrival order
+#[derive(Debug, Default)]
+struct SwapBuffer {
+    pools: HashMap<String, VecDeque<SwapPattern>>,
+    // Checkpoint of the last full sweep over all pools
+    last_sweep_checkpoint: i64,
+}
+
+impl SwapBuffer {
+    fn len(&self) -> usize {
+        self.pools.values().map(|swaps| swaps.len()).sum()
+    }
 }
 
-/// Sandwich attack analyzer with stateful transaction buffer
+/// Sandwich attack analyzer with stateful per-pool swap buffer
 pub struct SandwichAnalyzer {
-    // Circular buffer for recent transactions (uses interior mutability with Mutex for thread-safety)
-    transaction_buffer: Mutex<VecDeque<SwapPattern>>,
-    // Maximum buffer size
-    max_buffer_size: usize,
+    // Per-pool buffers of recent swaps (uses interior mutability with Mutex for thread-safety)
+    transaction_buffer: Mutex<SwapBuffer>,
+    // Maximum swaps kept per pool (a busy pool can't evict a quiet one)
+    max_swaps_per_pool: usize,
+    // Maximum age of a buffered swap
+    max_swap_age_ms: i64,
     // Maximum checkpoint distance for matching
     max_checkpoint_distance: i64,
+    // Checkpoints between full sweeps of idle pools
+    sweep_interval: i64,
     // Minimum price impact to be considered
     min_price_impact: u64,
+    // Links between addresses so front-run and back-run may come from different senders;
+    // shared and fed with every checkpoint transaction by the handler
+    clusterer: Arc<Mutex<AddressClusterer>>,
+    // Gas price baselines per shared object, shared with the gas price detector
+    gas_monitor: Arc<GasPriceMonitor>,
 }
 
 impl SandwichAnalyzer {
     pub fn new() -> Self {
         Self {
-            transaction_buffer: Mutex::new(VecDeque::with_capacity(1000)),
-            max_buffer_size: 1000,
+            transaction_buffer: Mutex::new(SwapBuffer::default()),
+            max_swaps_per_pool: 256,
+            max_swap_age_ms: 10 * 60 * 1000,  // 10 minutes
             max_checkpoint_distance: 100,  // Increased to 100 checkpoints to catch slower attacks/simulations
+            sweep_interval: 50,
             min_price_impact: 100,        // 1% minimum impact
+            clusterer: Arc::new(Mutex::new(AddressClusterer::new())),
+            gas_monitor: Arc::new(GasPriceMonitor::new()),
         }
     }
 
+    /// Use an address clusterer shared with other detectors
+    pub fn with_clusterer(mut self, clusterer: Arc<Mutex<AddressClusterer>>) -> Self {
+        self.clusterer = clusterer;
+        self
+    }
+
+    /// Use a gas price monitor shared with other detectors
+    pub fn with_gas_monitor(mut self, gas_monitor: Arc<GasPriceMonitor>) -> Self {
+        self.gas_monitor = gas_monitor;
+        self
+    }
+
     /// Analyze transaction and detect sandwich patterns
     pub fn analyze(
         &self,
@@ -60,7 +178,8 @@ impl SandwichAnalyzer {
         context: &DetectionContext,
     ) -> Vec<RiskEvent> {
         // Extract swap patterns from current transaction
-        let current_swaps = self.extract_swap_patterns(tx, context);
+        let gas = self.gas_monitor.observe(tx, context);
+        let current_swaps = self.extract_swap_patterns(tx, &gas, context);
 
         let mut detected_events = Vec::new();
 
@@ -79,8 +198,8 @@ impl SandwichAnalyzer {
             self.add_to_buffer(swap);
         }
 
-        // Clean old entries from buffer
-        self.cleanup_buffer(context.checkpoint);
+        // Periodically drop pools that have gone quiet
+        self.cleanup_buffer(context.checkpoint, context.timestamp_ms);
 
         detected_events
     }
@@ -89,6 +208,7 @@ impl SandwichAnalyzer {
     fn extract_swap_patterns(
         &self,
         tx: &ExecutedTransaction,
+        gas: &GasObservation,
         context: &DetectionContext,
     ) -> Vec<SwapPattern> {
         let events = match &tx.events {
@@ -106,6 +226,7 @@ impl SandwichAnalyzer {
                     let token_in = parsed.token_in;
                     let amount_in = parsed.amount_in;
                     let amount_out = parsed.amount_out;
+                    let fee_amount = parsed.fee_amount;
                     let price_impact = parsed.price_impact;
 
                     // Only track swaps with significant price impact
@@ -119,7 +240,12 @@ impl SandwichAnalyzer {
                             token_in_direction: token_in,
                             amount_in,
                             amount_out,
+                            fee_amount,
                             price_impact,
+                            reserve_a: parsed.reserve_a,
+                            reserve_b: parsed.reserve_b,
+                            gas_price: gas.gas_price,
+                            priority_gas: gas.is_outlier,
                         });
                     }
                 }
@@ -131,16 +257,25 @@ impl SandwichAnalyzer {
 
     /// Find sandwich pattern: Front-run → [Victim] → Back-run (new_swap)
     fn find_sandwich_pattern(&self, back_run: &SwapPattern) -> Option<SandwichMatch> {
+        // Attacker may split front-run and back-run across linked addresses
+        let attacker_cluster = self
+            .clusterer
+            .lock()
+            .unwrap()
+            .cluster_of(&back_run.sender, back_run.timestamp_ms);
+
         let buffer = self.transaction_buffer.lock().unwrap();
+        // Only this pool's recent activity is scanned
+        let pool_swaps = buffer.pools.get(&back_run.pool_id)?;
+
         // Look for front-run candidates (before current transaction)
-        let front_run_candidates: Vec<&SwapPattern> = buffer.iter()
-            .filter(|s| {
-                // Same pool
-                s.pool_id == back_run.pool_id &&
+        let front_run_candidates: Vec<(usize, &SwapPattern)> = pool_swaps.iter()
+            .enumerate()
+            .filter(|(_, s)| {
                 // Before back-run
                 s.checkpoint <= back_run.checkpoint &&
-                // Same sender as back-run (the attacker)
-                s.sender == back_run.sender &&
+                // Same sender as back-run or linked to it (the attacker)
+                attacker_cluster.contains(&s.sender) &&
                 // Opposite direction (Front-run buys, Back-run sells)
                 s.token_in_direction != back_run.token_in_direction &&
                 // Within checkpoint distance
@@ -149,11 +284,10 @@ impl SandwichAnalyzer {
             .collect();
 
         // For each front-run candidate, look for victim in between
-        for front_run in front_run_candidates {
-            let victim_candidates: Vec<&SwapPattern> = buffer.iter()
+        for (front_run_index, front_run) in front_run_candidates {
+            // Victims arrived after the front-run
+            let victim_candidates: Vec<&SwapPattern> = pool_swaps.range(front_run_index + 1..)
                 .filter(|s| {
-                    // Same pool
-                    s.pool_id == back_run.pool_id &&
                     // Between front-run and back-run
                     s.checkpoint >= front_run.checkpoint &&
                     s.checkpoint <= back_run.checkpoint &&
@@ -162,38 +296,34 @@ impl SandwichAnalyzer {
                     // So we relax timestamp check if checkpoints are different
                     (s.checkpoint > front_run.checkpoint || s.timestamp_ms >= front_run.timestamp_ms) &&
                     (s.checkpoint < back_run.checkpoint || s.timestamp_ms <= back_run.timestamp_ms) &&
-                    // Different sender (the victim)
-                    s.sender != back_run.sender &&
+                    // Sender outside the attacker cluster (the victim)
+                    !attacker_cluster.contains(&s.sender) &&
                     // Same direction as front-run (victim buys same token, pushing price further)
                     s.token_in_direction == front_run.token_in_direction
                 })
                 .collect();
 
-            // If we found a victim, we have a sandwich!
-            if let Some(&victim) = victim_candidates.first() {
-                // Calculate attacker profit
-                let attacker_profit = if back_run.amount_out > front_run.amount_in {
-                    back_run.amount_out - front_run.amount_in
-                } else {
-                    0
-                };
+            // If we found victims, we have a sandwich!
+            if !victim_candidates.is_empty() {
+                let attacker_profit = self.calculate_attacker_profit(front_run, back_run);
+                let victims = self.replay_victims(front_run, &victim_candidates);
 
-                // Calculate victim loss (in basis points)
-                // Victim should have gotten better price without sandwich
-                let expected_out = self.estimate_expected_output(victim, front_run);
-                let victim_loss_bps = if expected_out > victim.amount_out {
-                    let loss = expected_out - victim.amount_out;
-                    (loss * 10000) / expected_out
+                let total_extracted_value: u64 = victims.iter().map(|v| v.loss).sum();
+                let total_expected: u64 = victims.iter().map(|v| v.expected_out).sum();
+                let victim_loss_bps = if total_expected > 0 {
+                    (total_extracted_value as u128 * 10000 / total_expected as u128) as u64
                 } else {
                     0
                 };
 
                 return Some(SandwichMatch {
                     front_run: front_run.clone(),
-                    victim: victim.clone(),
+                    victims,
                     back_run: back_run.clone(),
                     attacker_profit,
+                    total_extracted_value,
                     victim_loss_bps,
+                    attacker_cluster: attacker_cluster.clone(),
                 });
             }
         }
@@ -201,81 +331,191 @@ impl SandwichAnalyzer {
         None
     }
 
+    /// Estimate what each victim should have received without front-running
+    ///
+    /// Re-executes the victims' swaps in order with constant-product math,
+    /// starting from the reserves the pool had right before the front-run.
+    /// Earlier victims still move the price for later ones.
+    fn replay_victims(&self, front_run: &SwapPattern, victims: &[&SwapPattern]) -> Vec<VictimImpact> {
+        let mut reserves = front_run.reserves_before();
+
+        victims
+            .iter()
+            .map(|victim| {
+                let expected_out = amm::amount_out(
+                    reserves,
+                    victim.token_in_direction,
+                    victim.amount_in,
+                    victim.fee_bps(),
+                );
+                reserves = reserves.after_swap(victim.token_in_direction, victim.amount_in, expected_out);
+
+                let loss = expected_out.saturating_sub(victim.amount_out);
+                let loss_bps = if expected_out > 0 {
+                    (loss as u128 * 10000 / expected_out as u128) as u64
+                } else {
+                    0
+                };
 
+                VictimImpact {
+                    swap: (*victim).clone(),
+                    expected_out,
+                    loss,
+                    loss_bps,
+                }
+            })
+            .collect()
+    }
 
-    /// Estimate what the victim should have received without front-running
-    fn estimate_expected_output(&self, victim: &SwapPattern, front_run: &SwapPattern) -> u64 {
-        // Simple estimation: victim would have gotten proportionally more
-        // if the pool wasn't moved by front-run
-        // This is approximate - real calculation would need pool reserves
-
-        // If front-run moved price by X%, victim lost roughly X%
-        let price_impact_factor = 10000 - front_run.price_impact;
-        (victim.amount_out * 10000) / price_impact_factor
+    /// Compute attacker profit in the front-run's input token
+    fn calculate_attacker_profit(
+        &self,
+        front_run: &SwapPattern,
+        back_run: &SwapPattern,
+    ) -> AttackerProfit {
+        // Base token: spent in the front-run, received in the back-run
+        let denominated_in_a = front_run.token_in_direction;
+        let spot = back_run.reserves_after();
+
+        // Net positions in both tokens over the round trip
+        let base_delta = back_run.amount_out as i128 - front_run.amount_in as i128;
+        let quote_delta = front_run.amount_out as i128 - back_run.amount_in as i128;
+        let quote_value = amm::spot_value(quote_delta, !denominated_in_a, spot);
+
+        let front_run_fee = front_run.fee_amount;
+        let back_run_fee =
+            amm::spot_value(back_run.fee_amount as i128, !denominated_in_a, spot).max(0) as u64;
+
+        let net = base_delta + quote_value;
+        let gross = net + front_run_fee as i128 + back_run_fee as i128;
+
+        AttackerProfit {
+            denominated_in_a,
+            gross,
+            front_run_fee,
+            back_run_fee,
+            net,
+        }
     }
 
-    /// Add swap pattern to buffer
+    /// Add swap pattern to its pool's buffer
     fn add_to_buffer(&self, pattern: SwapPattern) {
         let mut buffer = self.transaction_buffer.lock().unwrap();
-        if buffer.len() >= self.max_buffer_size {
-            buffer.pop_front(); // Remove oldest
+        let now_ms = pattern.timestamp_ms;
+        let pool_swaps = buffer.pools.entry(pattern.pool_id.clone()).or_default();
+
+        // Buffers are time-ordered, so expired swaps sit at the front
+        while pool_swaps
+            .front()
+            .is_some_and(|oldest| now_ms - oldest.timestamp_ms > self.max_swap_age_ms)
+        {
+            pool_swaps.pop_front();
         }
-        buffer.push_back(pattern);
+
+        if pool_swaps.len() >= self.max_swaps_per_pool {
+            pool_swaps.pop_front(); // Remove oldest
+        }
+        pool_swaps.push_back(pattern);
     }
 
-    /// Remove old entries from buffer
-    fn cleanup_buffer(&self, current_checkpoint: i64) {
+    /// Remove old entries from all pool buffers
+    ///
+    /// Active pools are trimmed on insert; this sweep only needs to run every
+    /// `sweep_interval` checkpoints to release pools that stopped trading.
+    fn cleanup_buffer(&self, current_checkpoint: i64, now_ms: i64) {
         let mut buffer = self.transaction_buffer.lock().unwrap();
-        buffer.retain(|pattern| {
-            current_checkpoint - pattern.checkpoint <= self.max_checkpoint_distance * 2
+        if current_checkpoint - buffer.last_sweep_checkpoint < self.sweep_interval {
+            return;
+        }
+        buffer.last_sweep_checkpoint = current_checkpoint;
+
+        let max_distance = self.max_checkpoint_distance * 2;
+        let max_age = self.max_swap_age_ms;
+        buffer.pools.retain(|_, swaps| {
+            swaps.retain(|pattern| {
+                current_checkpoint - pattern.checkpoint <= max_distance
+                    && now_ms - pattern.timestamp_ms <= max_age
+            });
+            !swaps.is_empty()
         });
     }
 
     /// Create risk event from detected sandwich match
     fn create_sandwich_event(&self, sandwich: &SandwichMatch) -> RiskEvent {
         // Calculate risk score
-        let mut risk_score = 0u32;
+        let mut signals = Vec::new();
 
         // Attacker profit scoring
-        if sandwich.attacker_profit > 1_000_000_000 {  // > 1000 tokens
-            risk_score += 40;
-        } else if sandwich.attacker_profit > 100_000_000 {  // > 100 tokens
-            risk_score += 30;
-        } else if sandwich.attacker_profit > 0 {
-            risk_score += 20;
+        let net_profit = sandwich.attacker_profit.net;
+        if net_profit > 1_000_000_000 {  // > 1000 tokens
+            signals.push(Signal::new("attacker_profit", net_profit as f64, 1_000_000_000.0, 40));
+        } else if net_profit > 100_000_000 {  // > 100 tokens
+            signals.push(Signal::new("attacker_profit", net_profit as f64, 100_000_000.0, 30));
+        } else if net_profit > 0 {
+            signals.push(Signal::new("attacker_profit", net_profit as f64, 0.0, 20));
         }
 
         // Victim loss scoring
+        let victim_loss = sandwich.victim_loss_bps as f64;
         if sandwich.victim_loss_bps > 1000 {  // > 10%
-            risk_score += 30;
+            signals.push(Signal::new("victim_loss_bps", victim_loss, 1000.0, 30));
         } else if sandwich.victim_loss_bps > 500 {  // > 5%
-            risk_score += 20;
+            signals.push(Signal::new("victim_loss_bps", victim_loss, 500.0, 20));
         } else if sandwich.victim_loss_bps > 100 {  // > 1%
-            risk_score += 10;
+            signals.push(Signal::new("victim_loss_bps", victim_loss, 100.0, 10));
+        }
+
+        // Several victims wrapped by one sandwich
+        if sandwich.victims.len() > 1 {
+            signals.push(Signal::new("victim_count", sandwich.victims.len() as f64, 2.0, 10));
         }
 
         // Same checkpoint bonus (more certainty)
         if sandwich.front_run.checkpoint == sandwich.back_run.checkpoint {
-            risk_score += 10;
+            signals.push(Signal::flag("same_checkpoint", 10));
         }
 
         // Quick execution bonus (< 5 seconds)
         let time_diff = sandwich.back_run.timestamp_ms - sandwich.front_run.timestamp_ms;
         if time_diff < 5000 {
-            risk_score += 10;
+            signals.push(Signal::new("time_span_ms", time_diff as f64, 5000.0, 10));
+        }
+
+        // Attacker bid for ordering priority
+        if sandwich.front_run.priority_gas || sandwich.back_run.priority_gas {
+            signals.push(Signal::flag("priority_gas", 10));
         }
 
+        let risk_score = Signal::total(&signals);
+
         // Classify risk level
-        let risk_level = match risk_score {
-            0..=29 => RiskLevel::Low,
-            30..=49 => RiskLevel::Medium,
-            50..=69 => RiskLevel::High,
-            _ => RiskLevel::Critical,
-        };
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let profit = &sandwich.attacker_profit;
+        let profit_token = if profit.denominated_in_a { "TokenA" } else { "TokenB" };
+        // Victims swap in the front-run's direction, so they receive its output token
+        let victim_loss_token = if sandwich.front_run.token_in_direction { "TokenB" } else { "TokenA" };
+
+        let victims: Vec<serde_json::Value> = sandwich.victims.iter()
+            .map(|v| serde_json::json!({
+                "sender": v.swap.sender,
+                "tx_digest": v.swap.tx_digest,
+                "amount_in": v.swap.amount_in,
+                "actual_out": v.swap.amount_out,
+                "expected_out": v.expected_out,
+                "loss": v.loss,
+                "loss_bps": v.loss_bps,
+            }))
+            .collect();
+        let victim_txs: Vec<&String> = sandwich.victims.iter().map(|v| &v.swap.tx_digest).collect();
 
         let description = format!(
-            "Sandwich attack: attacker profit {}, victim loss {:.2}%, time span {}ms",
-            format_currency(sandwich.attacker_profit),
+            "Sandwich attack: attacker profit {} {}, {} victim(s) lost {} {} ({:.2}%), time span {}ms",
+            format_amount(profit.net as f64),
+            profit_token,
+            sandwich.victims.len(),
+            format_amount(sandwich.total_extracted_value as f64),
+            victim_loss_token,
             sandwich.victim_loss_bps as f64 / 100.0,
             time_diff
         );
@@ -290,15 +530,34 @@ impl SandwichAnalyzer {
             description,
         )
         .with_detail("attacker", serde_json::json!(sandwich.back_run.sender))
-        .with_detail("victim", serde_json::json!(sandwich.victim.sender))
+        .with_detail("front_run_sender", serde_json::json!(sandwich.front_run.sender))
+        .with_detail("attacker_cluster", serde_json::json!(sandwich.attacker_cluster.members))
+        .with_detail("attacker_cluster_links", serde_json::json!(sandwich.attacker_cluster.links))
+        .with_detail("multi_address_attacker", serde_json::json!(
+            sandwich.front_run.sender != sandwich.back_run.sender
+        ))
         .with_detail("pool_id", serde_json::json!(sandwich.back_run.pool_id))
         .with_detail("front_run_tx", serde_json::json!(sandwich.front_run.tx_digest))
-        .with_detail("victim_tx", serde_json::json!(sandwich.victim.tx_digest))
+        .with_detail("victim_txs", serde_json::json!(victim_txs))
         .with_detail("back_run_tx", serde_json::json!(sandwich.back_run.tx_digest))
-        .with_detail("attacker_profit", serde_json::json!(format_currency(sandwich.attacker_profit)))
-        .with_detail("victim_loss", serde_json::json!(format_bps(sandwich.victim_loss_bps)))
-        .with_detail("time_span_ms", serde_json::json!(time_diff))
-        .with_detail("risk_score", serde_json::json!(risk_score));
+        .with_detail("profit_token", serde_json::json!(profit_token))
+        .with_detail("victims", serde_json::json!(victims))
+        .with_detail("victim_loss_token", serde_json::json!(victim_loss_token))
+        .with_detail("priority_gas", serde_json::json!(
+            sandwich.front_run.priority_gas || sandwich.back_run.priority_gas
+        ))
+        .with_metric("attacker_profit", profit.net as f64)
+        .with_metric("attacker_gross_profit", profit.gross as f64)
+        .with_metric("front_run_fee", profit.front_run_fee as f64)
+        .with_metric("back_run_fee", profit.back_run_fee as f64)
+        .with_metric("total_fees", profit.total_fees() as f64)
+        .with_metric("victim_count", sandwich.victims.len() as f64)
+        .with_metric("total_extracted_value", sandwich.total_extracted_value as f64)
+        .with_metric("victim_loss_bps", sandwich.victim_loss_bps as f64)
+        .with_metric("time_span_ms", time_diff as f64)
+        .with_metric("front_run_gas_price", sandwich.front_run.gas_price as f64)
+        .with_metric("back_run_gas_price", sandwich.back_run.gas_price as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE);
 
         event
     }
@@ -307,22 +566,11 @@ impl SandwichAnalyzer {
     pub fn get_buffer_size(&self) -> usize {
         self.transaction_buffer.lock().unwrap().len()
     }
-}
 
-fn format_currency(amount: u64) -> String {
-    let s = amount.to_string();
-    let mut res = String::new();
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            res.insert(0, ',');
-        }
-        res.insert(0, c);
+    /// Get number of pools with buffered swaps (for monitoring)
+    pub fn get_pool_count(&self) -> usize {
+        self.transaction_buffer.lock().unwrap().pools.len()
     }
-    res
-}
-
-fn format_bps(bps: u64) -> String {
-    format!("{:.2}%", bps as f64 / 100.0)
 }
 
 impl Default for SandwichAnalyzer {
@@ -334,6 +582,7 @@ impl Default for SandwichAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::cluster::LinkReason;
 
     #[test]
     fn test_buffer_management() {
@@ -350,7 +599,12 @@ mod tests {
             token_in_direction: true,
             amount_in: 1000,
             amount_out: 990,
+            fee_amount: 3,
             price_impact: 100,
+            reserve_a: 101000,
+            reserve_b: 99010,
+            gas_price: 1000,
+            priority_gas: false,
         };
 
         analyzer.add_to_buffer(swap);
@@ -371,47 +625,233 @@ mod tests {
             token_in_direction: true,
             amount_in: 1000,
             amount_out: 990,
+            fee_amount: 3,
             price_impact: 100,
+            reserve_a: 101000,
+            reserve_b: 99010,
+            gas_price: 1000,
+            priority_gas: false,
         };
 
         analyzer.add_to_buffer(old_swap);
         assert_eq!(analyzer.get_buffer_size(), 1);
 
         // Cleanup with current checkpoint far in future
-        analyzer.cleanup_buffer(2000);
+        analyzer.cleanup_buffer(2000, 1000000);
         assert_eq!(analyzer.get_buffer_size(), 0);
+        assert_eq!(analyzer.get_pool_count(), 0);
+    }
+
+    fn pool_swap(pool_id: &str, checkpoint: i64, timestamp_ms: i64) -> SwapPattern {
+        SwapPattern {
+            tx_digest: format!("{}-{}", pool_id, timestamp_ms),
+            sender: "addr1".to_string(),
+            pool_id: pool_id.to_string(),
+            checkpoint,
+            timestamp_ms,
+            token_in_direction: true,
+            amount_in: 1000,
+            amount_out: 990,
+            fee_amount: 3,
+            price_impact: 100,
+            reserve_a: 101000,
+            reserve_b: 99010,
+            gas_price: 1000,
+            priority_gas: false,
+        }
     }
 
     #[test]
-    fn test_expected_output_estimation() {
+    fn test_busy_pool_does_not_evict_quiet_pool() {
         let analyzer = SandwichAnalyzer::new();
 
+        analyzer.add_to_buffer(pool_swap("quiet", 1000, 1000000));
+        for i in 0..(analyzer.max_swaps_per_pool as i64 + 10) {
+            analyzer.add_to_buffer(pool_swap("busy", 1000, 1000000 + i));
+        }
+
+        let buffer = analyzer.transaction_buffer.lock().unwrap();
+        assert_eq!(buffer.pools["quiet"].len(), 1);
+        assert_eq!(buffer.pools["busy"].len(), analyzer.max_swaps_per_pool);
+    }
+
+    #[test]
+    fn test_time_based_eviction() {
+        let analyzer = SandwichAnalyzer::new();
+
+        analyzer.add_to_buffer(pool_swap("pool1", 1000, 1000000));
+        analyzer.add_to_buffer(pool_swap("pool1", 1001, 1000000 + analyzer.max_swap_age_ms + 1));
+
+        // Expired swap dropped on insert into the same pool
+        assert_eq!(analyzer.get_buffer_size(), 1);
+    }
+
+    /// Pool 1,000,000 / 1,000,000 with 0.3% fee:
+    /// front-run 100,000 A → victim 10,000 A → back-run 90,661 B
+    fn sandwich_swaps() -> (SwapPattern, SwapPattern, SwapPattern) {
+        let front_run = SwapPattern {
+            tx_digest: "front".to_string(),
+            sender: "attacker".to_string(),
+            pool_id: "pool1".to_string(),
+            checkpoint: 1000,
+            timestamp_ms: 1000000,
+            token_in_direction: true,
+            amount_in: 100_000,
+            amount_out: 90_661,
+            fee_amount: 300,
+            price_impact: 906,
+            reserve_a: 1_100_000,
+            reserve_b: 909_339,
+            gas_price: 1000,
+            priority_gas: false,
+        };
+
         let victim = SwapPattern {
             tx_digest: "victim".to_string(),
             sender: "victim_addr".to_string(),
             pool_id: "pool1".to_string(),
-            checkpoint: 1001,
-            timestamp_ms: 1001000,
+            checkpoint: 1000,
+            timestamp_ms: 1000100,
             token_in_direction: true,
-            amount_in: 1000,
-            amount_out: 900,  // Got 900 tokens
-            price_impact: 200,
+            amount_in: 10_000,
+            amount_out: 8_167,
+            fee_amount: 30,
+            price_impact: 89,
+            reserve_a: 1_110_000,
+            reserve_b: 901_172,
+            gas_price: 1000,
+            priority_gas: false,
         };
 
-        let front_run = SwapPattern {
-            tx_digest: "front".to_string(),
+        let back_run = SwapPattern {
+            tx_digest: "back".to_string(),
             sender: "attacker".to_string(),
             pool_id: "pool1".to_string(),
             checkpoint: 1000,
-            timestamp_ms: 1000000,
-            token_in_direction: true,
-            amount_in: 500,
-            amount_out: 495,
-            price_impact: 500,  // 5% price impact
+            timestamp_ms: 1000200,
+            token_in_direction: false,
+            amount_in: 90_661,
+            amount_out: 101_185,
+            fee_amount: 271,
+            price_impact: 911,
+            reserve_a: 1_008_815,
+            reserve_b: 991_833,
+            gas_price: 1000,
+            priority_gas: false,
         };
 
-        let expected = analyzer.estimate_expected_output(&victim, &front_run);
-        // Should be more than 900 (what victim actually got)
-        assert!(expected > 900);
+        (front_run, victim, back_run)
+    }
+
+    #[test]
+    fn test_expected_output_estimation() {
+        let analyzer = SandwichAnalyzer::new();
+        let (front_run, victim, _) = sandwich_swaps();
+
+        assert_eq!(front_run.reserves_before(), Reserves::new(1_000_000, 1_000_000));
+
+        // Victim's swap replayed on the untouched pool
+        let impacts = analyzer.replay_victims(&front_run, &[&victim]);
+        assert_eq!(impacts[0].expected_out, 9_871);
+        assert_eq!(impacts[0].loss, 9_871 - 8_167);
+        assert!(impacts[0].expected_out > victim.amount_out);
+    }
+
+    #[test]
+    fn test_multi_victim_sandwich() {
+        let analyzer = SandwichAnalyzer::new();
+        let (front_run, victim, back_run) = sandwich_swaps();
+
+        // Second victim: 20,000 A after the first victim
+        let second_victim = SwapPattern {
+            tx_digest: "victim2".to_string(),
+            sender: "victim2_addr".to_string(),
+            timestamp_ms: 1000150,
+            amount_in: 20_000,
+            amount_out: 15_902,
+            fee_amount: 60,
+            price_impact: 176,
+            reserve_a: 1_130_000,
+            reserve_b: 885_270,
+            ..victim.clone()
+        };
+
+        analyzer.add_to_buffer(front_run);
+        analyzer.add_to_buffer(victim);
+        analyzer.add_to_buffer(second_victim);
+
+        let sandwich = analyzer.find_sandwich_pattern(&back_run).unwrap();
+        assert_eq!(sandwich.victims.len(), 2);
+        assert_eq!(sandwich.victims[0].loss, 1_704);
+        // Replayed after the first victim's counterfactual swap
+        assert_eq!(sandwich.victims[1].expected_out, 19_169);
+        assert_eq!(sandwich.victims[1].loss, 3_267);
+        assert_eq!(sandwich.total_extracted_value, 1_704 + 3_267);
+
+        let event = analyzer.create_sandwich_event(&sandwich);
+        assert_eq!(event.details["victim_txs"], serde_json::json!(["victim", "victim2"]));
+    }
+
+    #[test]
+    fn test_priority_gas_raises_score() {
+        let analyzer = SandwichAnalyzer::new();
+        let (front_run, victim, back_run) = sandwich_swaps();
+
+        analyzer.add_to_buffer(front_run.clone());
+        analyzer.add_to_buffer(victim.clone());
+        let baseline = analyzer.find_sandwich_pattern(&back_run).unwrap();
+        let baseline_score = analyzer.create_sandwich_event(&baseline).risk_score();
+
+        let analyzer = SandwichAnalyzer::new();
+        analyzer.add_to_buffer(SwapPattern { gas_price: 50_000, priority_gas: true, ..front_run });
+        analyzer.add_to_buffer(victim);
+        let sandwich = analyzer.find_sandwich_pattern(&back_run).unwrap();
+        let event = analyzer.create_sandwich_event(&sandwich);
+
+        assert_eq!(event.risk_score(), baseline_score + 10);
+        assert!(event.signals.iter().any(|s| s.name == "priority_gas" && s.points == 10));
+        assert_eq!(event.details["priority_gas"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_attacker_profit_single_denomination() {
+        let analyzer = SandwichAnalyzer::new();
+        let (front_run, _, back_run) = sandwich_swaps();
+
+        let profit = analyzer.calculate_attacker_profit(&front_run, &back_run);
+
+        // Entire front-run output sold back, so profit is purely in TokenA
+        assert!(profit.denominated_in_a);
+        assert_eq!(profit.net, 1_185);
+        assert_eq!(profit.front_run_fee, 300);
+        // 271 TokenB at post back-run spot (1,008,815 / 991,833)
+        assert_eq!(profit.back_run_fee, 275);
+        assert_eq!(profit.gross, 1_185 + 300 + 275);
+    }
+
+    #[test]
+    fn test_cross_address_sandwich_via_cluster() {
+        let analyzer = SandwichAnalyzer::new();
+        let (front_run, victim, mut back_run) = sandwich_swaps();
+        back_run.sender = "attacker_exit".to_string();
+
+        analyzer.add_to_buffer(front_run);
+        analyzer.add_to_buffer(victim);
+
+        // Unlinked addresses: no match
+        assert!(analyzer.find_sandwich_pattern(&back_run).is_none());
+
+        // Front-run address funded the back-run address
+        analyzer.clusterer.lock().unwrap().add_link(
+            "attacker",
+            "attacker_exit",
+            LinkReason::FundingTransfer,
+            1000000,
+        );
+
+        let sandwich = analyzer.find_sandwich_pattern(&back_run).unwrap();
+        assert_eq!(sandwich.victims[0].swap.sender, "victim_addr");
+        assert!(sandwich.attacker_cluster.contains("attacker"));
+        assert!(sandwich.attacker_cluster.is_multi_address());
     }
 }
@@ -0,0 +1,459 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Wash Trading Detection: High-Volume Round Trips with Near-Zero Net Position
+
+use crate::action::format::format_amount;
+use crate::events::{EventParser, SwapExecuted};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, ScoreScale, Signal};
+use super::cluster::{AddressCluster, AddressClusterer};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Level thresholds in points; every signal at its top tier awards 90
+const SCORE_SCALE: ScoreScale = ScoreScale::new(
+    RiskLevel::Low,
+    &[(RiskLevel::Medium, 50), (RiskLevel::High, 70)],
+    90,
+);
+
+/// Swap kept in the per-pool trading window
+#[derive(Debug, Clone)]
+struct TradeRecord {
+    tx_digest: String,
+    sender: String,
+    timestamp_ms: i64,
+    a_to_b: bool,
+    amount_in: u64,
+    amount_out: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+}
+
+/// Aggregated trading of one address cluster on one pool
+#[derive(Debug, Clone, Default)]
+struct WashTradingStats {
+    pool_id: String,
+    trade_count: usize,
+    a_to_b_count: usize,
+    b_to_a_count: usize,
+    /// TokenA moved in either direction
+    volume_a: u64,
+    /// TokenB moved in either direction
+    volume_b: u64,
+    /// Cluster's net TokenA position change (received - spent)
+    net_a: i128,
+    /// Cluster's net TokenB position change (received - spent)
+    net_b: i128,
+    /// Latest pool reserves, for volume relative to depth
+    reserve_a: u64,
+    reserve_b: u64,
+    window_start_ms: i64,
+    window_end_ms: i64,
+    tx_digests: Vec<String>,
+    cluster: AddressCluster,
+}
+
+impl WashTradingStats {
+    /// Completed buy/sell pairs
+    fn round_trips(&self) -> usize {
+        self.a_to_b_count.min(self.b_to_a_count)
+    }
+
+    /// Worst net change relative to volume across both tokens (bps)
+    fn net_change_bps(&self) -> u64 {
+        relative_bps(self.net_a.unsigned_abs(), self.volume_a as u128)
+            .max(relative_bps(self.net_b.unsigned_abs(), self.volume_b as u128))
+    }
+
+    /// TokenA volume relative to TokenA reserve (bps)
+    fn volume_to_depth_bps(&self) -> u64 {
+        relative_bps(self.volume_a as u128, self.reserve_a as u128)
+    }
+}
+
+/// Per-pool trading window shared by all clusters
+#[derive(Debug, Default)]
+struct WashTradingState {
+    pools: HashMap<String, VecDeque<TradeRecord>>,
+    // (pool, sender) → timestamp of the last alert, to avoid re-alerting every swap
+    last_alert_ms: HashMap<(String, String), i64>,
+}
+
+/// Wash trading analyzer
+///
+/// Tracks swaps per pool by address cluster over a rolling window and flags
+/// clusters that trade back and forth at high volume while their net position
+/// barely changes, the signature of fake volume or incentive farming.
+pub struct WashTradingAnalyzer {
+    state: Mutex<WashTradingState>,
+    /// Shared address links, fed with every checkpoint transaction by the handler
+    clusterer: Arc<Mutex<AddressClusterer>>,
+    /// Rolling window length
+    window_ms: i64,
+    /// Maximum trades kept per pool
+    max_trades_per_pool: usize,
+    /// Minimum trades by the cluster in the window
+    min_trade_count: usize,
+    /// Minimum completed buy/sell pairs
+    min_round_trips: usize,
+    /// Maximum net position change relative to volume (bps)
+    max_net_change_bps: u64,
+    /// Minimum TokenA volume relative to pool reserve (bps)
+    min_volume_to_depth_bps: u64,
+}
+
+impl WashTradingAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(WashTradingState::default()),
+            clusterer: Arc::new(Mutex::new(AddressClusterer::new())),
+            window_ms: 60 * 60 * 1000, // 1 hour
+            max_trades_per_pool: 1024,
+            min_trade_count: 6,
+            min_round_trips: 3,
+            max_net_change_bps: 500,        // Net change within 5% of volume
+            min_volume_to_depth_bps: 2000,  // Volume >= 20% of pool reserve
+        }
+    }
+
+    /// Use an address clusterer shared with other detectors
+    pub fn with_clusterer(mut self, clusterer: Arc<Mutex<AddressClusterer>>) -> Self {
+        self.clusterer = clusterer;
+        self
+    }
+
+    /// Main analysis function
+    pub fn analyze(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let trades = self.extract_trades(tx, context);
+        if trades.is_empty() {
+            return Vec::new();
+        }
+
+        let clusterer = self.clusterer.lock().unwrap();
+        self.process_trades(trades, &clusterer, context)
+    }
+
+    /// Extract swaps from transaction events
+    fn extract_trades(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<(String, TradeRecord)> {
+        let events = match &tx.events {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+
+        let mut trades = Vec::new();
+
+        for event in &events.data {
+            if event.type_.name.as_str() == "SwapExecuted" {
+                if let Some(parsed) = SwapExecuted::from_event(event) {
+                    trades.push((
+                        parsed.pool_id.to_string(),
+                        TradeRecord {
+                            tx_digest: context.tx_digest.clone(),
+                            sender: parsed.sender.to_string(),
+                            timestamp_ms: context.timestamp_ms,
+                            a_to_b: parsed.token_in,
+                            amount_in: parsed.amount_in,
+                            amount_out: parsed.amount_out,
+                            reserve_a: parsed.reserve_a,
+                            reserve_b: parsed.reserve_b,
+                        },
+                    ));
+                }
+            }
+        }
+
+        trades
+    }
+
+    /// Add trades to the window and evaluate each sender's cluster
+    fn process_trades(
+        &self,
+        trades: Vec<(String, TradeRecord)>,
+        clusterer: &AddressClusterer,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        let mut state = self.state.lock().unwrap();
+        let mut events = Vec::new();
+        let window_start = context.timestamp_ms - self.window_ms;
+
+        // Drop stale alert cooldowns and idle pools
+        state.last_alert_ms.retain(|_, ts| *ts >= window_start);
+        state.pools.retain(|_, pool_trades| {
+            pool_trades.back().is_some_and(|t| t.timestamp_ms >= window_start)
+        });
+
+        let mut evaluated = Vec::new();
+        for (pool_id, trade) in trades {
+            let pool_trades = state.pools.entry(pool_id.clone()).or_default();
+            while pool_trades.front().is_some_and(|t| t.timestamp_ms < window_start) {
+                pool_trades.pop_front();
+            }
+            if pool_trades.len() >= self.max_trades_per_pool {
+                pool_trades.pop_front();
+            }
+
+            let key = (pool_id, trade.sender.clone());
+            pool_trades.push_back(trade);
+            if !evaluated.contains(&key) {
+                evaluated.push(key);
+            }
+        }
+
+        for (pool_id, sender) in evaluated {
+            if state.last_alert_ms.contains_key(&(pool_id.clone(), sender.clone())) {
+                continue;
+            }
+
+            let cluster = clusterer.cluster_of(&sender, context.timestamp_ms);
+            let Some(pool_trades) = state.pools.get(&pool_id) else {
+                continue;
+            };
+            let stats = self.aggregate(&pool_id, pool_trades, cluster);
+
+            if self.is_wash_trading(&stats) {
+                // Cool down every member so the same cluster isn't reported per swap
+                for member in &stats.cluster.members {
+                    state
+                        .last_alert_ms
+                        .insert((pool_id.clone(), member.clone()), context.timestamp_ms);
+                }
+                events.push(self.create_wash_trading_event(&stats, &sender, context));
+            }
+        }
+
+        events
+    }
+
+    /// Aggregate the cluster's trades on one pool
+    fn aggregate(
+        &self,
+        pool_id: &str,
+        pool_trades: &VecDeque<TradeRecord>,
+        cluster: AddressCluster,
+    ) -> WashTradingStats {
+        let mut stats = WashTradingStats {
+            pool_id: pool_id.to_string(),
+            ..Default::default()
+        };
+
+        for trade in pool_trades.iter().filter(|t| cluster.contains(&t.sender)) {
+            if stats.trade_count == 0 {
+                stats.window_start_ms = trade.timestamp_ms;
+            }
+            stats.trade_count += 1;
+            stats.window_end_ms = trade.timestamp_ms;
+            stats.reserve_a = trade.reserve_a;
+            stats.reserve_b = trade.reserve_b;
+            if stats.tx_digests.last() != Some(&trade.tx_digest) {
+                stats.tx_digests.push(trade.tx_digest.clone());
+            }
+
+            if trade.a_to_b {
+                stats.a_to_b_count += 1;
+                stats.volume_a += trade.amount_in;
+                stats.volume_b += trade.amount_out;
+                stats.net_a -= trade.amount_in as i128;
+                stats.net_b += trade.amount_out as i128;
+            } else {
+                stats.b_to_a_count += 1;
+                stats.volume_b += trade.amount_in;
+                stats.volume_a += trade.amount_out;
+                stats.net_b -= trade.amount_in as i128;
+                stats.net_a += trade.amount_out as i128;
+            }
+        }
+
+        stats.cluster = cluster;
+        stats
+    }
+
+    fn is_wash_trading(&self, stats: &WashTradingStats) -> bool {
+        stats.trade_count >= self.min_trade_count
+            && stats.round_trips() >= self.min_round_trips
+            && stats.net_change_bps() <= self.max_net_change_bps
+            && stats.volume_to_depth_bps() >= self.min_volume_to_depth_bps
+    }
+
+    /// Create risk event from wash trading statistics
+    fn create_wash_trading_event(
+        &self,
+        stats: &WashTradingStats,
+        sender: &str,
+        context: &DetectionContext,
+    ) -> RiskEvent {
+        // Round trips with flat net position already established
+        let mut signals = vec![Signal::new(
+            "round_trips",
+            stats.round_trips() as f64,
+            self.min_round_trips as f64,
+            30,
+        )];
+
+        let trades = stats.trade_count as f64;
+        if stats.trade_count >= 20 {
+            signals.push(Signal::new("trade_count", trades, 20.0, 20));
+        } else if stats.trade_count >= 10 {
+            signals.push(Signal::new("trade_count", trades, 10.0, 10));
+        }
+
+        // Net change fires at or below its threshold
+        if stats.net_change_bps() <= 100 {
+            signals.push(Signal::new("net_change_bps", stats.net_change_bps() as f64, 100.0, 10));
+        }
+
+        let volume_to_depth = stats.volume_to_depth_bps() as f64;
+        if stats.volume_to_depth_bps() >= 10000 {
+            signals.push(Signal::new("volume_to_depth_bps", volume_to_depth, 10000.0, 20));
+        } else if stats.volume_to_depth_bps() >= 5000 {
+            signals.push(Signal::new("volume_to_depth_bps", volume_to_depth, 5000.0, 10));
+        }
+
+        // Volume spread across linked addresses
+        if stats.cluster.is_multi_address() {
+            signals.push(Signal::new("cluster_size", stats.cluster.members.len() as f64, 2.0, 10));
+        }
+
+        let risk_score = Signal::total(&signals);
+
+        let risk_level = SCORE_SCALE.level(risk_score);
+
+        let window_ms = stats.window_end_ms - stats.window_start_ms;
+        let description = format!(
+            "Wash trading: {} trades ({} round trips) moving {} A over {}ms with {:.2}% net change",
+            stats.trade_count,
+            stats.round_trips(),
+            format_amount(stats.volume_a as f64),
+            window_ms,
+            stats.net_change_bps() as f64 / 100.0
+        );
+
+        RiskEvent::new(
+            RiskType::WashTrading,
+            risk_level,
+            context.tx_digest.clone(),
+            sender.to_string(),
+            context.checkpoint,
+            context.timestamp_ms,
+            description,
+        )
+        .with_detail("pool_id", serde_json::json!(stats.pool_id))
+        .with_detail("tx_digests", serde_json::json!(stats.tx_digests))
+        .with_detail("cluster", serde_json::json!(stats.cluster.members))
+        .with_metric("trade_count", stats.trade_count as f64)
+        .with_metric("round_trips", stats.round_trips() as f64)
+        .with_metric("volume_a", stats.volume_a as f64)
+        .with_metric("volume_b", stats.volume_b as f64)
+        .with_metric("net_change_a", stats.net_a as f64)
+        .with_metric("net_change_b", stats.net_b as f64)
+        .with_metric("net_change_bps", stats.net_change_bps() as f64)
+        .with_metric("volume_to_depth_bps", stats.volume_to_depth_bps() as f64)
+        .with_metric("window_start_ms", stats.window_start_ms as f64)
+        .with_metric("window_end_ms", stats.window_end_ms as f64)
+        .with_metric("window_ms", window_ms as f64)
+        .with_scaled_signals(signals, &SCORE_SCALE)
+    }
+}
+
+/// `part / whole` in basis points (`u64::MAX` if whole is zero and part isn't)
+fn relative_bps(part: u128, whole: u128) -> u64 {
+    if whole == 0 {
+        return if part == 0 { 0 } else { u64::MAX };
+    }
+    (part * 10000 / whole).min(u64::MAX as u128) as u64
+}
+
+impl Default for WashTradingAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::cluster::LinkReason;
+
+    fn trade(tx: &str, sender: &str, timestamp_ms: i64, a_to_b: bool) -> (String, TradeRecord) {
+        // 100,000 in each direction on a 1,000,000 / 1,000,000 pool
+        (
+            "pool1".to_string(),
+            TradeRecord {
+                tx_digest: tx.to_string(),
+                sender: sender.to_string(),
+                timestamp_ms,
+                a_to_b,
+                amount_in: 100_000,
+                amount_out: 99_000,
+                reserve_a: 1_000_000,
+                reserve_b: 1_000_000,
+            },
+        )
+    }
+
+    fn context(tx_digest: &str, timestamp_ms: i64) -> DetectionContext {
+        DetectionContext::new(tx_digest.to_string(), "trader".to_string(), 1000, timestamp_ms)
+    }
+
+    #[test]
+    fn test_round_trips_flagged_once() {
+        let analyzer = WashTradingAnalyzer::new();
+        let clusterer = AddressClusterer::new();
+
+        let mut events = Vec::new();
+        for i in 0..8 {
+            let tx = format!("tx{}", i);
+            let ts = 1_000_000 + i * 1000;
+            events.extend(analyzer.process_trades(
+                vec![trade(&tx, "trader", ts, i % 2 == 0)],
+                &clusterer,
+                &context(&tx, ts),
+            ));
+        }
+
+        // Flagged at the 6th trade, then cooled down
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].risk_type, RiskType::WashTrading);
+        assert_eq!(events[0].metrics["trade_count"], 6.0);
+        assert_eq!(events[0].metrics["round_trips"], 3.0);
+    }
+
+    #[test]
+    fn test_one_directional_trading_not_flagged() {
+        let analyzer = WashTradingAnalyzer::new();
+        let clusterer = AddressClusterer::new();
+
+        for i in 0..10 {
+            let tx = format!("tx{}", i);
+            let ts = 1_000_000 + i * 1000;
+            let events = analyzer.process_trades(vec![trade(&tx, "trader", ts, true)], &clusterer, &context(&tx, ts));
+            assert!(events.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_round_trips_across_cluster() {
+        let analyzer = WashTradingAnalyzer::new();
+        let mut clusterer = AddressClusterer::new();
+        clusterer.add_link("buyer", "seller", LinkReason::FundingTransfer, 1_000_000);
+
+        let mut events = Vec::new();
+        for i in 0..6 {
+            let tx = format!("tx{}", i);
+            let ts = 1_000_000 + i * 1000;
+            // Buys from one address, sells from the other
+            let sender = if i % 2 == 0 { "buyer" } else { "seller" };
+            events.extend(analyzer.process_trades(vec![trade(&tx, sender, ts, i % 2 == 0)], &clusterer, &context(&tx, ts)));
+        }
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].details["cluster"], serde_json::json!(["seller", "buyer"]));
+    }
+}
@@ -117,6 +117,18 @@ impl EsClient {
                     "timestamp_ms": { "type": "date", "format": "epoch_millis" },
                     "sender": { "type": "keyword" },
                     "execution_status": { "type": "keyword" },
+                    "failure": {
+                        "properties": {
+                            "kind": { "type": "keyword" },
+                            "command_index": { "type": "integer" },
+                            "abort_package": { "type": "keyword" },
+                            "abort_module": { "type": "keyword" },
+                            "abort_function": { "type": "keyword" },
+                            "abort_function_index": { "type": "integer" },
+                            "abort_instruction": { "type": "integer" },
+                            "abort_code": { "type": "unsigned_long" }
+                        }
+                    },
                     "kind": { "type": "keyword" },
                     "is_system_tx": { "type": "boolean" },
                     "is_sponsored_tx": { "type": "boolean" },
@@ -42,6 +42,16 @@ pub struct LiquidityAdded {
     pub liquidity_minted: u64,
 }
 
+/// Liquidity removal event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityRemoved {
+    pub pool_id: ObjectID,
+    pub provider: SuiAddress,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub liquidity_burned: u64,
+}
+
 // ============================================================================
 // Flash Loan Events (flash_loan_pool.move)
 // ============================================================================
@@ -208,6 +218,7 @@ macro_rules! impl_event_parser {
 impl_event_parser!(PoolCreated, "PoolCreated");
 impl_event_parser!(SwapExecuted, "SwapExecuted");
 impl_event_parser!(LiquidityAdded, "LiquidityAdded");
+impl_event_parser!(LiquidityRemoved, "LiquidityRemoved");
 impl_event_parser!(FlashLoanTaken, "FlashLoanTaken");
 impl_event_parser!(FlashLoanRepaid, "FlashLoanRepaid");
 impl_event_parser!(TWAPUpdated, "TWAPUpdated");
@@ -218,6 +229,30 @@ impl_event_parser!(RepayEvent, "RepayEvent");
 impl_event_parser!(LiquidationEvent, "LiquidationEvent");
 impl_event_parser!(AccrueInterestEvent, "AccrueInterestEvent");
 
+/// Decode a known event into its fields as JSON (`None` for unknown events)
+pub fn decode_event_fields(event: &Event) -> Option<serde_json::Value> {
+    fn to_fields<T: EventParser + Serialize>(event: &Event) -> Option<serde_json::Value> {
+        T::from_event(event).and_then(|parsed| serde_json::to_value(parsed).ok())
+    }
+
+    match event.type_.name.as_str() {
+        "PoolCreated" => to_fields::<PoolCreated>(event),
+        "SwapExecuted" => to_fields::<SwapExecuted>(event),
+        "LiquidityAdded" => to_fields::<LiquidityAdded>(event),
+        "LiquidityRemoved" => to_fields::<LiquidityRemoved>(event),
+        "FlashLoanTaken" => to_fields::<FlashLoanTaken>(event),
+        "FlashLoanRepaid" => to_fields::<FlashLoanRepaid>(event),
+        "TWAPUpdated" => to_fields::<TWAPUpdated>(event),
+        "PriceDeviationDetected" => to_fields::<PriceDeviationDetected>(event),
+        "SupplyEvent" => to_fields::<SupplyEvent>(event),
+        "BorrowEvent" => to_fields::<BorrowEvent>(event),
+        "RepayEvent" => to_fields::<RepayEvent>(event),
+        "LiquidationEvent" => to_fields::<LiquidationEvent>(event),
+        "AccrueInterestEvent" => to_fields::<AccrueInterestEvent>(event),
+        _ => None,
+    }
+}
+
 // ============================================================================
 // Multi-Event Parser
 // ============================================================================
@@ -230,12 +265,15 @@ pub struct ParsedEvents {
     pub flash_loan_taken: Vec<FlashLoanTaken>,
     pub flash_loan_repaid: Vec<FlashLoanRepaid>,
     pub swaps: Vec<SwapExecuted>,
+    pub liquidity_added: Vec<LiquidityAdded>,
+    pub liquidity_removed: Vec<LiquidityRemoved>,
     pub twap_updates: Vec<TWAPUpdated>,
     pub price_deviations: Vec<PriceDeviationDetected>,
     pub borrows: Vec<BorrowEvent>,
     pub repays: Vec<RepayEvent>,
     pub liquidations: Vec<LiquidationEvent>,
     pub supplies: Vec<SupplyEvent>,
+    pub accruals: Vec<AccrueInterestEvent>,
 }
 
 impl ParsedEvents {
@@ -267,6 +305,16 @@ impl ParsedEvents {
                         parsed.swaps.push(e);
                     }
                 }
+                "LiquidityAdded" => {
+                    if let Some(e) = LiquidityAdded::from_event(event) {
+                        parsed.liquidity_added.push(e);
+                    }
+                }
+                "LiquidityRemoved" => {
+                    if let Some(e) = LiquidityRemoved::from_event(event) {
+                        parsed.liquidity_removed.push(e);
+                    }
+                }
                 "TWAPUpdated" => {
                     if let Some(e) = TWAPUpdated::from_event(event) {
                         parsed.twap_updates.push(e);
@@ -297,6 +345,11 @@ impl ParsedEvents {
                         parsed.supplies.push(e);
                     }
                 }
+                "AccrueInterestEvent" => {
+                    if let Some(e) = AccrueInterestEvent::from_event(event) {
+                        parsed.accruals.push(e);
+                    }
+                }
                 _ => {}  // Ignore unknown events
             }
         }
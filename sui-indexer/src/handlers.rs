@@ -1,7 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::json;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use sui_indexer_alt_framework::{
     pipeline::sequential::Handler,
     pipeline::Processor,
@@ -9,17 +9,23 @@ use sui_indexer_alt_framework::{
 };
 use sui_types::effects::{TransactionEffectsAPI, TransactionEvents};
 use sui_types::full_checkpoint_content::{Checkpoint, CheckpointTransaction};
-use sui_types::transaction::TransactionDataAPI;
+use sui_types::transaction::{Command, TransactionData, TransactionDataAPI, TransactionKind};
 
+use crate::action::format::{format_metrics, format_signal};
 use crate::action::{ActionPipeline, AlertAction, LogAction, MockDefenseAction};
+use crate::analyzer::{AddressClusterer, GasPriceMonitor, GovernanceWatchlist};
 use crate::constants::SIMULATION_PACKAGE_ID;
 use crate::elasticsearch::SharedEsClient;
+use crate::incident::IncidentCorrelator;
 use crate::models::{EsFlattener, Transaction, TransactionWithEs};
+use crate::reputation::ReputationStore;
+use crate::rule::RuleEngine;
 use crate::pipeline::{
-    DetectionPipeline, FlashLoanDetector, OracleManipulationDetector, PriceManipulationDetector,
-    SandwichDetector,
+    BaselineDetector, DetectionPipeline, EventMerger, FlashLoanDetector, GasPriceDetector, GovernanceDetector, InterestRateDetector,
+    JitLiquidityDetector, LiquidationDetector, LiquidityDrainDetector, ProbingDetector, OracleManipulationDetector, PluginDetector, PriceManipulationDetector, RuleDetector,
+    SandwichDetector, WashTradingDetector,
 };
-use crate::risk::{DetectionContext, RiskLevel};
+use crate::risk::DetectionContext;
 
 // Type alias for the transaction type from checkpoint
 // Checkpoint.transactions yields ExecutedTransaction which is the same as CheckpointTransaction
@@ -28,34 +34,79 @@ type TxType = CheckpointTransaction;
 pub struct TransactionHandler {
     es_client: SharedEsClient,
     detection_pipeline: DetectionPipeline,
+    governance_watchlist: GovernanceWatchlist,
+    clusterer: Arc<Mutex<AddressClusterer>>,
+    incident_correlator: IncidentCorrelator,
+    reputation: Arc<ReputationStore>,
     action_pipeline: ActionPipeline,
 }
 
 impl TransactionHandler {
-    pub fn new(es_client: SharedEsClient) -> Self {
+    pub fn new(
+        es_client: SharedEsClient,
+        governance_watchlist: GovernanceWatchlist,
+        reputation: Arc<ReputationStore>,
+        rules: RuleEngine,
+        plugins: PluginDetector,
+        alerts: AlertAction,
+    ) -> Self {
+        // Gas baselines shared so sandwich scoring sees priority-gas outliers
+        let gas_monitor = Arc::new(GasPriceMonitor::new());
+        // Address links shared by detectors; fed with every transaction in `process`
+        let clusterer = Arc::new(Mutex::new(AddressClusterer::new()));
+
         let detection_pipeline = DetectionPipeline::new()
             .add_detector(FlashLoanDetector::new())
-            .add_detector(PriceManipulationDetector::new())
-            .add_detector(SandwichDetector::new())
-            .add_detector(OracleManipulationDetector::new());
+            .add_detector(PriceManipulationDetector::new(clusterer.clone()))
+            .add_detector(SandwichDetector::new(gas_monitor.clone(), clusterer.clone()))
+            .add_detector(OracleManipulationDetector::new())
+            .add_detector(LiquidationDetector::new())
+            .add_detector(JitLiquidityDetector::new())
+            .add_detector(WashTradingDetector::new(clusterer.clone()))
+            .add_detector(InterestRateDetector::new())
+            .add_detector(LiquidityDrainDetector::new())
+            .add_detector(GasPriceDetector::new(gas_monitor))
+            .add_detector(BaselineDetector::new())
+            .add_detector(GovernanceDetector::new(governance_watchlist.clone()))
+            .add_detector(ProbingDetector::new(governance_watchlist.clone()))
+            .add_detector(RuleDetector::new(rules))
+            .add_detector(plugins)
+            .with_reputation(reputation.clone())
+            .with_merger(EventMerger::from_env());
 
-        let webhook_url = std::env::var("ALERT_WEBHOOK_URL").ok();
         let action_pipeline = ActionPipeline::new()
             .add_handler(LogAction::new())
-            .add_handler(AlertAction::new(webhook_url, RiskLevel::Low))
+            .add_handler(alerts)
             .add_handler(MockDefenseAction::new(true));
 
         Self {
             es_client,
             detection_pipeline,
+            governance_watchlist,
+            clusterer,
+            incident_correlator: IncidentCorrelator::from_env(),
+            reputation,
             action_pipeline,
         }
     }
 
     /// Check if a transaction involves the target package ID
-    /// Returns true if any event emitted is from the target package
-    /// Currently checks events only (sufficient for our detection purposes)
-    fn involves_target_package(events: Option<&TransactionEvents>) -> bool {
+    /// Returns true if any MoveCall command or emitted event is from the target package
+    /// MoveCalls are needed for aborted transactions, which emit no events
+    fn involves_target_package(
+        transaction_data: &TransactionData,
+        events: Option<&TransactionEvents>,
+    ) -> bool {
+        if let TransactionKind::ProgrammableTransaction(pt) = transaction_data.kind() {
+            for cmd in pt.commands.iter() {
+                if let Command::MoveCall(move_call) = cmd {
+                    if move_call.package.to_string() == SIMULATION_PACKAGE_ID {
+                        return true;
+                    }
+                }
+            }
+        }
+
         if let Some(event_wrapper) = events {
             for event in &event_wrapper.data {
                 let package_id: String = event.package_id.to_string();
@@ -118,8 +169,19 @@ impl Processor for TransactionHandler {
                 &tx_digest,
             );
 
+            // Funding transfers to a second attacker address rarely touch the
+            // target package, so links are recorded before filtering
+            self.clusterer
+                .lock()
+                .unwrap()
+                .record_transaction(tx, checkpoint_ts);
+
             // Only run detection for transactions involving the target package
-            if Self::involves_target_package(tx.events.as_ref()) {
+            // or calling / touching watched packages and capabilities (upgrades and
+            // aborted calls emit no events)
+            if Self::involves_target_package(transaction_data, tx.events.as_ref())
+                || self.governance_watchlist.touches(tx)
+            {
                 println!(
                     "🎯 Target package transaction detected: {}",
                     &tx_digest[..16]
@@ -133,6 +195,9 @@ impl Processor for TransactionHandler {
                 );
 
                 let risk_events = self.detection_pipeline.run(tx, &context).await;
+                for event in &risk_events {
+                    self.reputation.record_detection(event);
+                }
 
                 if !risk_events.is_empty() {
                     println!("╔════════════════════════════════════════════════════════════╗");
@@ -148,8 +213,24 @@ impl Processor for TransactionHandler {
                     for (i, event) in risk_events.iter().enumerate() {
                         println!("\n📋 Event {}/{}", i + 1, risk_events.len());
                         println!("   Type:        {:?}", event.risk_type);
+                        if !event.secondary_types.is_empty() {
+                            println!("   Also:        {:?}", event.secondary_types);
+                        }
                         println!("   Level:       {:?}", event.risk_level);
+                        println!("   Score:       {}/100", event.score);
                         println!("   Description: {}", event.description);
+                        if !event.signals.is_empty() {
+                            println!("   Signals:");
+                            for signal in &event.signals {
+                                println!("     - {}", format_signal(signal));
+                            }
+                        }
+                        if !event.metrics.is_empty() {
+                            println!("   Metrics:");
+                            for metric in format_metrics(event) {
+                                println!("     {}", metric);
+                            }
+                        }
                         if !event.details.is_empty() {
                             println!(
                                 "   Details:     {}",
@@ -160,8 +241,9 @@ impl Processor for TransactionHandler {
                     println!("");
                 }
 
-                for event in risk_events {
-                    self.action_pipeline.run(&event).await;
+                // Actions run per incident: only when one opens or escalates
+                for incident in self.incident_correlator.correlate(risk_events) {
+                    self.action_pipeline.run_incident(&incident).await;
                 }
             }
 
@@ -171,6 +253,10 @@ impl Processor for TransactionHandler {
             });
         }
 
+        for incident in self.incident_correlator.close_idle(checkpoint_ts) {
+            self.action_pipeline.run_incident(&incident).await;
+        }
+
         Ok(txs)
     }
 }
@@ -184,7 +270,11 @@ impl Handler for TransactionHandler {
         batch.extend(values);
     }
 
-    async fn commit<'a>(&self, batch: &Self::Batch, _conn: &mut Connection<'a>) -> Result<usize> {
+    async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
+        // Reputation updates from detections are persisted even while storage is disabled
+        if let Err(e) = self.reputation.flush(conn).await {
+            eprintln!("⚠ Warning: Failed to persist address reputation: {}", e);
+        }
 
         if batch.is_empty() {
             return Ok(0);
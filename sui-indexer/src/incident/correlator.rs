@@ -0,0 +1,434 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Incident Correlation: Grouping Risk Events by Tx, Attacker, Pool and Time
+
+use super::models::{Incident, IncidentState};
+use crate::risk::{RiskEvent, RiskType};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Keys an event can be correlated on
+#[derive(Debug, Default)]
+struct CorrelationKeys {
+    tx_digests: Vec<String>,
+    /// Sender plus attacker / cluster members named in the details
+    addresses: Vec<String>,
+    pools: Vec<String>,
+}
+
+impl CorrelationKeys {
+    fn from_event(event: &RiskEvent) -> Self {
+        let mut keys = Self::default();
+        push_unique(&mut keys.tx_digests, &event.tx_digest);
+        push_unique(&mut keys.addresses, &event.sender);
+
+        for key in ["front_run_tx", "back_run_tx", "add_tx", "remove_tx", "dump_tx", "tx_digests"] {
+            collect_strings(event.details.get(key), &mut keys.tx_digests);
+        }
+        for key in ["attacker", "front_run_sender", "attacker_cluster", "cluster", "pool_creator"] {
+            collect_strings(event.details.get(key), &mut keys.addresses);
+        }
+        for key in ["pool_id", "market_id"] {
+            collect_strings(event.details.get(key), &mut keys.pools);
+        }
+
+        keys
+    }
+}
+
+/// Detail value as one string or an array of strings
+fn collect_strings(value: Option<&serde_json::Value>, out: &mut Vec<String>) {
+    match value {
+        Some(serde_json::Value::String(s)) if !s.is_empty() => push_unique(out, s),
+        Some(serde_json::Value::Array(items)) => {
+            for item in items {
+                if let serde_json::Value::String(s) = item {
+                    push_unique(out, s);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !values.iter().any(|v| v == value) {
+        values.push(value.to_string());
+    }
+}
+
+fn overlaps(a: &[String], b: &[String]) -> bool {
+    a.iter().any(|v| b.contains(v))
+}
+
+/// Primary classification followed by merged secondary ones
+fn event_types(event: &RiskEvent) -> Vec<RiskType> {
+    let mut types = vec![event.risk_type.clone()];
+    types.extend(event.secondary_types.iter().cloned());
+    types
+}
+
+#[derive(Debug, Default)]
+struct CorrelatorState {
+    next_id: u64,
+    open: Vec<Incident>,
+}
+
+/// Incident correlation engine
+///
+/// One exploit usually triggers several detectors (flash loan, price and
+/// oracle manipulation) in the same tx and follow-up events in later
+/// checkpoints. Events are grouped into one incident when they share:
+/// 1. A transaction digest
+/// 2. An attacker address or cluster member
+/// 3. A pool or market, if the incident had activity within `pool_window_ms`
+///
+/// Incidents close after `idle_close_ms` without related events, or after
+/// `max_open_ms` so a sender flagged every few minutes starts a new incident
+/// instead of keeping one open forever. Keys are kept for the retained events
+/// only, which bounds them with `max_events_per_incident`. Only state changes
+/// (opened, escalated, closed) are returned, so actions run per incident
+/// rather than per event.
+pub struct IncidentCorrelator {
+    state: Mutex<CorrelatorState>,
+    /// Incidents without new events for this long are closed
+    idle_close_ms: i64,
+    /// Pool-only matches need activity this recent
+    pool_window_ms: i64,
+    /// Incidents open this long are closed even while active
+    max_open_ms: i64,
+    /// Events (and their correlation keys) kept per incident
+    max_events_per_incident: usize,
+}
+
+impl IncidentCorrelator {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(CorrelatorState { next_id: 1, open: Vec::new() }),
+            idle_close_ms: 10 * 60 * 1000, // 10 minutes
+            pool_window_ms: 2 * 60 * 1000, // 2 minutes
+            max_open_ms: 60 * 60 * 1000,   // 1 hour
+            max_events_per_incident: 100,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let mut correlator = Self::new();
+
+        if let Some(ms) = std::env::var("INCIDENT_IDLE_CLOSE_MS").ok().and_then(|v| v.parse().ok()) {
+            correlator = correlator.with_idle_close_ms(ms);
+        }
+        if let Some(ms) = std::env::var("INCIDENT_POOL_WINDOW_MS").ok().and_then(|v| v.parse().ok()) {
+            correlator = correlator.with_pool_window_ms(ms);
+        }
+        if let Some(ms) = std::env::var("INCIDENT_MAX_OPEN_MS").ok().and_then(|v| v.parse().ok()) {
+            correlator = correlator.with_max_open_ms(ms);
+        }
+
+        correlator
+    }
+
+    /// Set how long an incident stays open without related events
+    pub fn with_idle_close_ms(mut self, idle_close_ms: i64) -> Self {
+        self.idle_close_ms = idle_close_ms;
+        self
+    }
+
+    /// Set how recent incident activity must be for a pool-only match
+    pub fn with_pool_window_ms(mut self, pool_window_ms: i64) -> Self {
+        self.pool_window_ms = pool_window_ms;
+        self
+    }
+
+    /// Set how long an incident may stay open while related events keep arriving
+    pub fn with_max_open_ms(mut self, max_open_ms: i64) -> Self {
+        self.max_open_ms = max_open_ms;
+        self
+    }
+
+    /// Add events to incidents and return the incidents that opened or escalated
+    pub fn correlate(&self, events: Vec<RiskEvent>) -> Vec<Incident> {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+
+        let mut opened: HashSet<u64> = HashSet::new();
+        let mut changed: Vec<u64> = Vec::new();
+
+        for event in events {
+            let keys = CorrelationKeys::from_event(&event);
+            let matches: Vec<usize> = state
+                .open
+                .iter()
+                .enumerate()
+                .filter(|(_, incident)| self.matches(incident, &keys, event.timestamp_ms))
+                .map(|(i, _)| i)
+                .collect();
+
+            let Some((&target, others)) = matches.split_first() else {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.open.push(self.open_incident(id, event, keys));
+                opened.insert(id);
+                changed.push(id);
+                continue;
+            };
+
+            // Related to several incidents: fold them into the oldest
+            let mut escalated = !others.is_empty();
+            for &i in others.iter().rev() {
+                let other = state.open.remove(i);
+                opened.remove(&other.id);
+                changed.retain(|id| *id != other.id);
+                self.merge(&mut state.open[target], other);
+            }
+
+            let incident = &mut state.open[target];
+            escalated |= self.add_event(incident, event, keys);
+
+            if escalated && !opened.contains(&incident.id) {
+                incident.state = IncidentState::Escalating;
+                if !changed.contains(&incident.id) {
+                    changed.push(incident.id);
+                }
+            }
+        }
+
+        changed
+            .iter()
+            .filter_map(|id| state.open.iter().find(|i| i.id == *id).cloned())
+            .collect()
+    }
+
+    /// Close and return incidents without related events since `now_ms - idle_close_ms`
+    /// or first seen before `now_ms - max_open_ms`
+    pub fn close_idle(&self, now_ms: i64) -> Vec<Incident> {
+        let mut state = self.state.lock().unwrap();
+        let idle_cutoff = now_ms - self.idle_close_ms;
+        let age_cutoff = now_ms - self.max_open_ms;
+
+        let (closed, open): (Vec<Incident>, Vec<Incident>) = state
+            .open
+            .drain(..)
+            .partition(|incident| incident.last_seen_ms < idle_cutoff || incident.first_seen_ms <= age_cutoff);
+        state.open = open;
+
+        closed
+            .into_iter()
+            .map(|mut incident| {
+                incident.state = IncidentState::Closed;
+                incident
+            })
+            .collect()
+    }
+
+    fn matches(&self, incident: &Incident, keys: &CorrelationKeys, timestamp_ms: i64) -> bool {
+        overlaps(&incident.tx_digests, &keys.tx_digests)
+            || overlaps(&incident.addresses, &keys.addresses)
+            || (timestamp_ms - incident.last_seen_ms <= self.pool_window_ms
+                && overlaps(&incident.pools, &keys.pools))
+    }
+
+    fn open_incident(&self, id: u64, event: RiskEvent, keys: CorrelationKeys) -> Incident {
+        Incident {
+            id,
+            state: IncidentState::Open,
+            risk_level: event.risk_level,
+            risk_types: event_types(&event),
+            event_count: 1,
+            tx_digests: keys.tx_digests,
+            addresses: keys.addresses,
+            pools: keys.pools,
+            first_seen_ms: event.timestamp_ms,
+            last_seen_ms: event.timestamp_ms,
+            first_checkpoint: event.checkpoint,
+            last_checkpoint: event.checkpoint,
+            events: vec![event],
+        }
+    }
+
+    /// Add an event; returns true if it raised the level or brought a new risk type
+    fn add_event(&self, incident: &mut Incident, event: RiskEvent, keys: CorrelationKeys) -> bool {
+        let mut escalated = false;
+
+        if event.risk_level > incident.risk_level {
+            incident.risk_level = event.risk_level;
+            escalated = true;
+        }
+        for risk_type in event_types(&event) {
+            if !incident.risk_types.contains(&risk_type) {
+                incident.risk_types.push(risk_type);
+                escalated = true;
+            }
+        }
+
+        incident.first_seen_ms = incident.first_seen_ms.min(event.timestamp_ms);
+        incident.last_seen_ms = incident.last_seen_ms.max(event.timestamp_ms);
+        incident.first_checkpoint = incident.first_checkpoint.min(event.checkpoint);
+        incident.last_checkpoint = incident.last_checkpoint.max(event.checkpoint);
+
+        incident.event_count += 1;
+        if incident.events.len() < self.max_events_per_incident {
+            for tx in &keys.tx_digests {
+                push_unique(&mut incident.tx_digests, tx);
+            }
+            for address in &keys.addresses {
+                push_unique(&mut incident.addresses, address);
+            }
+            for pool in &keys.pools {
+                push_unique(&mut incident.pools, pool);
+            }
+            incident.events.push(event);
+        }
+
+        escalated
+    }
+
+    fn merge(&self, incident: &mut Incident, other: Incident) {
+        let other_count = other.event_count;
+        let kept = other.events.len();
+
+        for event in other.events {
+            let keys = CorrelationKeys::from_event(&event);
+            self.add_event(incident, event, keys);
+        }
+        // Events beyond the cap were already counted by the other incident
+        incident.event_count += other_count - kept;
+    }
+}
+
+impl Default for IncidentCorrelator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::{RiskLevel, RiskType};
+
+    fn event(risk_type: RiskType, level: RiskLevel, tx: &str, sender: &str, timestamp_ms: i64) -> RiskEvent {
+        RiskEvent::new(
+            risk_type,
+            level,
+            tx.to_string(),
+            sender.to_string(),
+            timestamp_ms / 1000,
+            timestamp_ms,
+            "test".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_same_tx_events_form_one_incident() {
+        let correlator = IncidentCorrelator::new();
+        let incidents = correlator.correlate(vec![
+            event(RiskType::FlashLoanAttack, RiskLevel::High, "tx1", "0xa", 1000),
+            event(RiskType::PriceManipulation, RiskLevel::Critical, "tx1", "0xa", 1000),
+            event(RiskType::OracleManipulation, RiskLevel::High, "tx1", "0xa", 1000),
+        ]);
+
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].state, IncidentState::Open);
+        assert_eq!(incidents[0].event_count, 3);
+        assert_eq!(incidents[0].risk_level, RiskLevel::Critical);
+        assert_eq!(incidents[0].primary_event().risk_type, RiskType::PriceManipulation);
+    }
+
+    #[test]
+    fn test_follow_up_escalates_only_on_change() {
+        let correlator = IncidentCorrelator::new();
+        correlator.correlate(vec![event(RiskType::PriceManipulation, RiskLevel::Medium, "tx1", "0xa", 1000)]);
+
+        // Same type and level from the same attacker: absorbed silently
+        let repeat = correlator.correlate(vec![event(RiskType::PriceManipulation, RiskLevel::Medium, "tx2", "0xa", 5000)]);
+        assert!(repeat.is_empty());
+
+        let escalated = correlator.correlate(vec![event(RiskType::LiquidityDrain, RiskLevel::High, "tx3", "0xa", 9000)]);
+        assert_eq!(escalated.len(), 1);
+        assert_eq!(escalated[0].state, IncidentState::Escalating);
+        assert_eq!(escalated[0].event_count, 3);
+        assert_eq!(escalated[0].tx_digests, vec!["tx1", "tx2", "tx3"]);
+    }
+
+    #[test]
+    fn test_pool_match_needs_time_proximity() {
+        let correlator = IncidentCorrelator::new();
+        let pooled = |tx: &str, sender: &str, ts: i64| {
+            event(RiskType::StatisticalAnomaly, RiskLevel::Low, tx, sender, ts)
+                .with_detail("pool_id", serde_json::json!("0xpool"))
+        };
+
+        correlator.correlate(vec![pooled("tx1", "0xa", 0)]);
+        assert!(correlator.correlate(vec![pooled("tx2", "0xb", 60_000)]).is_empty());
+
+        let unrelated = correlator.correlate(vec![pooled("tx3", "0xc", 60_000 + correlator.pool_window_ms + 1)]);
+        assert_eq!(unrelated.len(), 1);
+        assert_eq!(unrelated[0].id, 2);
+    }
+
+    #[test]
+    fn test_shared_cluster_merges_incidents() {
+        let correlator = IncidentCorrelator::new();
+        correlator.correlate(vec![event(RiskType::SandwichAttack, RiskLevel::Medium, "tx1", "0xa", 1000)]);
+        correlator.correlate(vec![event(RiskType::SandwichAttack, RiskLevel::Medium, "tx2", "0xb", 2000)]);
+
+        let merged = correlator.correlate(vec![
+            event(RiskType::WashTrading, RiskLevel::Medium, "tx3", "0xc", 3000)
+                .with_detail("cluster", serde_json::json!(["0xa", "0xb", "0xc"])),
+        ]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].id, 1);
+        assert_eq!(merged[0].state, IncidentState::Escalating);
+        assert_eq!(merged[0].event_count, 3);
+    }
+
+    #[test]
+    fn test_busy_sender_bounded() {
+        let correlator = IncidentCorrelator::new();
+        let step = correlator.idle_close_ms / 2;
+        let bot = |i: i64| event(RiskType::StatisticalAnomaly, RiskLevel::Low, &format!("tx{}", i), "0xbot", i * step);
+
+        // Flagged more often than the idle window: closed at the lifetime cap
+        let mut closed_at = None;
+        for i in 0..20 {
+            if let Some(incident) = correlator.close_idle(i * step).pop() {
+                assert_eq!(incident.state, IncidentState::Closed);
+                closed_at = Some(i * step);
+                break;
+            }
+            correlator.correlate(vec![bot(i)]);
+        }
+        assert_eq!(closed_at, Some(correlator.max_open_ms));
+
+        let reopened = correlator.correlate(vec![bot(12)]);
+        assert_eq!(reopened[0].id, 2);
+        assert_eq!(reopened[0].state, IncidentState::Open);
+
+        // Keys stop growing with the retained events
+        let correlator = IncidentCorrelator::new();
+        for i in 0..150 {
+            correlator.correlate(vec![event(RiskType::StatisticalAnomaly, RiskLevel::Low, &format!("tx{}", i), "0xbot", i)]);
+        }
+        let state = correlator.state.lock().unwrap();
+        assert_eq!(state.open[0].event_count, 150);
+        assert_eq!(state.open[0].tx_digests.len(), correlator.max_events_per_incident);
+    }
+
+    #[test]
+    fn test_idle_incidents_close() {
+        let correlator = IncidentCorrelator::new();
+        correlator.correlate(vec![event(RiskType::FlashLoanAttack, RiskLevel::High, "tx1", "0xa", 1000)]);
+
+        assert!(correlator.close_idle(1000 + correlator.idle_close_ms).is_empty());
+
+        let closed = correlator.close_idle(2000 + correlator.idle_close_ms);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].state, IncidentState::Closed);
+
+        // A later event from the same attacker opens a new incident
+        let reopened = correlator.correlate(vec![event(RiskType::FlashLoanAttack, RiskLevel::High, "tx2", "0xa", 3_000_000)]);
+        assert_eq!(reopened[0].id, 2);
+        assert_eq!(reopened[0].state, IncidentState::Open);
+    }
+}
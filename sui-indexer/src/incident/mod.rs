@@ -0,0 +1,5 @@
+mod models;
+mod correlator;
+
+pub use models::{Incident, IncidentState};
+pub use correlator::IncidentCorrelator;
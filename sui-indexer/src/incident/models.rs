@@ -0,0 +1,98 @@
+use crate::risk::{RiskEvent, RiskLevel, RiskType};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IncidentState {
+    Open,       // First events seen
+    Escalating, // Higher risk level, new risk type or merged with another incident
+    Closed,     // No related events within the idle window
+}
+
+/// Group of risk events that belong to the same attack or campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: u64,
+    pub state: IncidentState,
+    /// Highest level among the events
+    pub risk_level: RiskLevel,
+    /// Risk types in the order they were first seen
+    pub risk_types: Vec<RiskType>,
+    /// Correlated events (capped, see `event_count` for the total)
+    pub events: Vec<RiskEvent>,
+    pub event_count: usize,
+    pub tx_digests: Vec<String>,
+    pub addresses: Vec<String>,
+    pub pools: Vec<String>,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+    pub first_checkpoint: i64,
+    pub last_checkpoint: i64,
+}
+
+impl Incident {
+    /// Event with the highest level, earliest first on ties
+    ///
+    /// Incidents are created from an event and keep their first events, so this never fails
+    pub fn primary_event(&self) -> &RiskEvent {
+        self.events
+            .iter()
+            .rev()
+            .max_by_key(|e| e.risk_level)
+            .expect("incident without events")
+    }
+
+    /// Single event summarizing the incident, for action handlers working on events
+    pub fn summary_event(&self) -> RiskEvent {
+        let primary = self.primary_event();
+        let root = &self.events[0];
+
+        let types: Vec<String> = self.risk_types.iter().map(|t| format!("{:?}", t)).collect();
+        let description = format!(
+            "Incident #{} ({:?}): {} events [{}] across {} txs - {}",
+            self.id,
+            self.state,
+            self.event_count,
+            types.join(", "),
+            self.tx_digests.len(),
+            primary.description
+        );
+
+        let events: Vec<serde_json::Value> = self
+            .events
+            .iter()
+            .map(|e| serde_json::json!({
+                "risk_type": e.risk_type,
+                "risk_level": e.risk_level,
+                "tx_digest": e.tx_digest,
+                "checkpoint": e.checkpoint,
+                "score": e.score,
+                "description": e.description,
+            }))
+            .collect();
+
+        // Score, signals and metrics are the primary event's
+        let mut summary = RiskEvent::new(
+            primary.risk_type.clone(),
+            self.risk_level,
+            root.tx_digest.clone(),
+            root.sender.clone(),
+            self.last_checkpoint,
+            self.last_seen_ms,
+            description,
+        )
+        .with_detail("incident_id", serde_json::json!(self.id))
+        .with_detail("incident_state", serde_json::json!(self.state))
+        .with_detail("risk_types", serde_json::json!(self.risk_types))
+        .with_detail("event_count", serde_json::json!(self.event_count))
+        .with_detail("tx_digests", serde_json::json!(self.tx_digests))
+        .with_detail("addresses", serde_json::json!(self.addresses))
+        .with_detail("pools", serde_json::json!(self.pools))
+        .with_detail("first_seen_ms", serde_json::json!(self.first_seen_ms))
+        .with_detail("last_seen_ms", serde_json::json!(self.last_seen_ms))
+        .with_detail("events", serde_json::json!(events));
+        summary.signals = primary.signals.clone();
+        summary.metrics = primary.metrics.clone();
+        summary.score = primary.score;
+        summary.with_score_in_level_band()
+    }
+}
@@ -0,0 +1,18 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Library target so benches can drive the analyzers directly
+
+pub mod models;
+pub mod handlers;
+pub mod elasticsearch;
+pub mod constants;
+pub mod risk;
+pub mod analyzer;
+pub mod pipeline;
+pub mod action;
+pub mod incident;
+pub mod reputation;
+pub mod rule;
+pub mod plugin;
+pub mod outbox;
+pub mod events;  // Strongly-typed event structs
+pub mod schema;
@@ -1,19 +1,15 @@
-mod models;
-mod handlers;
-mod elasticsearch;
-mod constants;
-mod risk;
-mod analyzer;
-mod pipeline;
-mod action;
-mod events;  // NEW: Strongly-typed event structs
-pub mod schema;
-
-use handlers::TransactionHandler;
-use elasticsearch::EsClient;
+use sui_indexer::action::AlertAction;
+use sui_indexer::analyzer::GovernanceWatchlist;
+use sui_indexer::elasticsearch::EsClient;
+use sui_indexer::handlers::TransactionHandler;
+use sui_indexer::outbox::{self, DeliveryWorker, Outbox};
+use sui_indexer::pipeline::PluginDetector;
+use sui_indexer::reputation::ReputationStore;
+use sui_indexer::rule::RuleEngine;
 
 use anyhow::Result;
 use clap::Parser;
+use diesel_async::{AsyncConnection, AsyncPgConnection};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
 use std::sync::Arc;
 use sui_indexer_alt_framework::{
@@ -33,6 +29,11 @@ async fn main() -> Result<()> {
         .parse::<Url>()
         .expect("Invalid database URL");
 
+    // `sui-indexer outbox <command>` manages alert deliveries instead of indexing
+    if std::env::args().nth(1).as_deref() == Some("outbox") {
+        return outbox::cli::run(std::env::args().skip(1), database_url.as_str()).await;
+    }
+
     // Initialize Elasticsearch client
     let es_url = std::env::var("ELASTICSEARCH_URL")
         .unwrap_or_else(|_| "http://localhost:9200".to_string());
@@ -49,13 +50,47 @@ async fn main() -> Result<()> {
 
     let mut cluster = IndexerCluster::builder()
         .with_args(args)
-        .with_database_url(database_url)
+        .with_database_url(database_url.clone())
         .with_migrations(&MIGRATIONS)
         .build()
         .await?;
 
+    // Stored reputation first, then curated files so their lists take precedence
+    let reputation = Arc::new(ReputationStore::from_env());
+    match AsyncPgConnection::establish(database_url.as_str()).await {
+        Ok(mut conn) => match reputation.load_from_db(&mut conn).await {
+            Ok(count) => println!("Loaded {} address reputations from database", count),
+            Err(e) => eprintln!("⚠ Warning: Failed to load address reputation: {}", e),
+        },
+        Err(e) => eprintln!("⚠ Warning: Failed to connect for address reputation: {}", e),
+    }
+    let loaded = reputation.load_env_files()?;
+    println!("Loaded {} address reputations from files", loaded);
+
+    let governance_watchlist = GovernanceWatchlist::from_env()?;
+
+    let mut rules = RuleEngine::new();
+    let loaded = rules.load_env_files()?;
+    println!("Loaded {} detection rules", loaded);
+
+    let plugins = PluginDetector::from_env()?;
+    println!("Loaded {} detector plugins", plugins.plugin_count());
+
+    // Alerts go through the Postgres outbox; without it they're sent directly
+    let mut alerts = AlertAction::from_env()?;
+    match Outbox::connect(database_url.as_str()).await {
+        Ok(outbox) => {
+            let outbox = Arc::new(outbox);
+            DeliveryWorker::from_env(outbox.clone(), alerts.channels()).start();
+            alerts = alerts.with_outbox(outbox);
+        }
+        Err(e) => eprintln!("⚠ Warning: Failed to open alert outbox, sending alerts directly: {}", e),
+    }
+    alerts.start_digest();
+    println!("Alert channels: {:?}", alerts.channel_names());
+
     cluster.sequential_pipeline(
-        TransactionHandler::new(es_client),
+        TransactionHandler::new(es_client, governance_watchlist, reputation, rules, plugins, alerts),
         SequentialConfig::default(),
     ).await?;
 
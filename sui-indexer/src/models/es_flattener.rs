@@ -9,7 +9,8 @@ use sui_types::{
 };
 
 use super::{
-    EsChangedObject, EsEffects, EsEvent, EsGas, EsMoveCall, EsObject, EsRemovedObject, EsTransaction,
+    EsChangedObject, EsEffects, EsEvent, EsExecutionFailure, EsGas, EsMoveCall, EsObject,
+    EsRemovedObject, EsTransaction,
 };
 
 /// Flatten Sui transaction data to Elasticsearch document (type-safe)
@@ -38,6 +39,7 @@ impl EsFlattener {
         let objects = Self::extract_objects(transaction_data);
         let events = Self::extract_events(events);
         let effects_data = Self::extract_effects(effects);
+        let failure = Self::extract_failure(effects);
 
         // Flatten for aggregation
         let packages = Self::extract_packages(&move_calls);
@@ -56,6 +58,7 @@ impl EsFlattener {
             timestamp_ms: timestamp,
             sender: transaction_data.sender().to_string(),
             execution_status: execution_status.to_string(),
+            failure,
             kind,
             is_system_tx,
             is_sponsored_tx,
@@ -102,7 +105,7 @@ impl EsFlattener {
         }
     }
 
-    fn extract_move_calls(transaction_data: &TransactionData) -> Vec<EsMoveCall> {
+    pub(crate) fn extract_move_calls(transaction_data: &TransactionData) -> Vec<EsMoveCall> {
         let mut calls = Vec::new();
 
         // Get transaction kind
@@ -180,7 +183,7 @@ impl EsFlattener {
         es_events
     }
 
-    fn extract_effects(effects: &TransactionEffects) -> EsEffects {
+    pub(crate) fn extract_effects(effects: &TransactionEffects) -> EsEffects {
         use sui_types::effects::TransactionEffectsAPI;
         use std::mem;
         use sui_types::effects::ObjectRemoveKind;
@@ -280,6 +283,54 @@ impl EsFlattener {
         }
     }
 
+    /// Extract failure kind, failing command and abort location from execution status
+    pub(crate) fn extract_failure(effects: &TransactionEffects) -> Option<EsExecutionFailure> {
+        use sui_types::effects::TransactionEffectsAPI;
+        use sui_types::execution_status::ExecutionStatus;
+
+        match effects.status() {
+            ExecutionStatus::Success { .. } => None,
+            ExecutionStatus::Failure { error, command } => Some(Self::failure_from_status(error, *command)),
+        }
+    }
+
+    fn failure_from_status(
+        error: &sui_types::execution_status::ExecutionFailureStatus,
+        command: Option<usize>,
+    ) -> EsExecutionFailure {
+        use sui_types::execution_status::{ExecutionFailureStatus, MoveLocation};
+
+        let mut failure = EsExecutionFailure {
+            kind: failure_kind(error),
+            command_index: command.map(|c| c as u64),
+            abort_package: None,
+            abort_module: None,
+            abort_function: None,
+            abort_function_index: None,
+            abort_instruction: None,
+            abort_code: None,
+        };
+
+        let location: Option<&MoveLocation> = match error {
+            ExecutionFailureStatus::MoveAbort(location, code) => {
+                failure.abort_code = Some(*code);
+                Some(location)
+            }
+            ExecutionFailureStatus::MovePrimitiveRuntimeError(location) => location.0.as_ref(),
+            _ => None,
+        };
+
+        if let Some(location) = location {
+            failure.abort_package = Some(location.module.address().to_hex_literal());
+            failure.abort_module = Some(location.module.name().to_string());
+            failure.abort_function = location.function_name.clone();
+            failure.abort_function_index = Some(location.function as u32);
+            failure.abort_instruction = Some(location.instruction as u32);
+        }
+
+        failure
+    }
+
     fn extract_packages(calls: &[EsMoveCall]) -> Vec<String> {
         calls
             .iter()
@@ -307,3 +358,64 @@ impl EsFlattener {
             .collect()
     }
 }
+
+/// Variant name of an execution failure, e.g. "MoveAbort" or "InsufficientGas"
+///
+/// Taken from the serde representation (externally tagged), which is the
+/// variant name for every shape of variant, unlike the Debug output.
+fn failure_kind(error: &sui_types::execution_status::ExecutionFailureStatus) -> String {
+    match serde_json::to_value(error) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(serde_json::Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        _ => "Unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::execution_status::{CommandArgumentError, ExecutionFailureStatus, MoveLocation};
+    use sui_types::parse_sui_struct_tag;
+
+    #[test]
+    fn test_move_abort_failure() {
+        let module = parse_sui_struct_tag("0xabc::pool::Pool").unwrap().module_id();
+        let error = ExecutionFailureStatus::MoveAbort(
+            MoveLocation {
+                module,
+                function: 3,
+                instruction: 12,
+                function_name: Some("swap".to_string()),
+            },
+            7,
+        );
+
+        let failure = EsFlattener::failure_from_status(&error, Some(1));
+        assert_eq!(failure.kind, "MoveAbort");
+        assert_eq!(failure.command_index, Some(1));
+        assert_eq!(failure.abort_code, Some(7));
+        assert_eq!(failure.abort_package.as_deref(), Some("0xabc"));
+        assert_eq!(failure.abort_module.as_deref(), Some("pool"));
+        assert_eq!(failure.abort_function.as_deref(), Some("swap"));
+        assert_eq!(failure.abort_function_index, Some(3));
+        assert_eq!(failure.abort_instruction, Some(12));
+    }
+
+    #[test]
+    fn test_non_abort_failures() {
+        let failure = EsFlattener::failure_from_status(&ExecutionFailureStatus::InsufficientGas, None);
+        assert_eq!(failure.kind, "InsufficientGas");
+        assert_eq!(failure.command_index, None);
+        assert!(failure.abort_code.is_none());
+        assert!(failure.abort_module.is_none());
+
+        let error = ExecutionFailureStatus::CommandArgumentError {
+            arg_idx: 0,
+            kind: CommandArgumentError::TypeMismatch,
+        };
+        let failure = EsFlattener::failure_from_status(&error, Some(2));
+        assert_eq!(failure.kind, "CommandArgumentError");
+        assert_eq!(failure.command_index, Some(2));
+        assert!(failure.abort_package.is_none());
+    }
+}
@@ -10,6 +10,8 @@ pub struct EsTransaction {
 
     pub sender: String,
     pub execution_status: String,
+    /// Failure details, None for successful transactions
+    pub failure: Option<EsExecutionFailure>,
     
     // Transaction type information
     pub kind: String,
@@ -40,6 +42,21 @@ pub struct EsGas {
     pub storage_rebate: Option<i64>,
 }
 
+/// Execution failure details from `ExecutionStatus::Failure`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EsExecutionFailure {
+    pub kind: String, // ExecutionFailureStatus variant, e.g. "MoveAbort", "InsufficientGas"
+    pub command_index: Option<u64>,
+    // Abort location - set for MoveAbort and MovePrimitiveRuntimeError
+    pub abort_package: Option<String>,
+    pub abort_module: Option<String>,
+    pub abort_function: Option<String>,
+    pub abort_function_index: Option<u32>,
+    pub abort_instruction: Option<u32>,
+    // Abort code - MoveAbort only
+    pub abort_code: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EsMoveCall {
     pub package: String,
@@ -0,0 +1,109 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Outbox CLI: Inspect and Retry Dead-Letter Alert Deliveries
+
+use super::models::OutboxRecord;
+use super::store::Outbox;
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+
+/// `sui-indexer outbox <command>`
+#[derive(Debug, Parser)]
+#[command(name = "outbox", about = "Inspect and retry alert deliveries")]
+pub struct OutboxCli {
+    #[command(subcommand)]
+    command: OutboxCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum OutboxCommand {
+    /// Delivery counts by status
+    Stats,
+    /// List dead letters, newest first
+    Dead {
+        #[arg(long)]
+        channel: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// Show one delivery with its payload
+    Show { id: i64 },
+    /// Move dead letters back to the queue
+    Retry {
+        ids: Vec<i64>,
+        /// Retry every dead letter
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+        /// With --all, only dead letters of this channel
+        #[arg(long, requires = "all")]
+        channel: Option<String>,
+    },
+    /// Delete delivered rows older than the given number of days
+    Prune {
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+    },
+}
+
+pub async fn run(args: impl IntoIterator<Item = String>, database_url: &str) -> Result<()> {
+    let cli = OutboxCli::parse_from(args);
+    let outbox = Outbox::connect(database_url).await?;
+
+    match cli.command {
+        OutboxCommand::Stats => {
+            for (status, count) in outbox.counts().await? {
+                println!("{:<10} {}", status, count);
+            }
+        }
+        OutboxCommand::Dead { channel, limit } => {
+            let records = outbox.dead_letters(channel.as_deref(), limit).await?;
+            if records.is_empty() {
+                println!("No dead letters");
+            }
+            for record in &records {
+                println!("{}", summary(record));
+            }
+        }
+        OutboxCommand::Show { id } => {
+            let Some(record) = outbox.get(id).await? else {
+                bail!("No delivery #{}", id);
+            };
+            println!("{}", summary(&record));
+            println!("Next attempt: {}", record.next_attempt_at.to_rfc3339());
+            if let Some(delivered_at) = record.delivered_at {
+                println!("Delivered:    {}", delivered_at.to_rfc3339());
+            }
+            println!("{}", serde_json::to_string_pretty(&record.payload)?);
+        }
+        OutboxCommand::Retry { ids, all, channel } => {
+            if !all && ids.is_empty() {
+                bail!("Give delivery ids or --all");
+            }
+            let count = if all {
+                outbox.retry_all(channel.as_deref()).await?
+            } else {
+                outbox.retry(&ids).await?
+            };
+            println!("Requeued {} dead letters", count);
+        }
+        OutboxCommand::Prune { days } => {
+            let before = chrono::Utc::now() - chrono::Duration::days(days);
+            let count = outbox.prune_delivered(before).await?;
+            println!("Deleted {} delivered rows", count);
+        }
+    }
+
+    Ok(())
+}
+
+fn summary(record: &OutboxRecord) -> String {
+    format!(
+        "#{} {} {} [{}] attempts={} created={} error={}",
+        record.id,
+        record.channel,
+        record.kind,
+        record.status,
+        record.attempts,
+        record.created_at.to_rfc3339(),
+        record.last_error.as_deref().unwrap_or("-")
+    )
+}
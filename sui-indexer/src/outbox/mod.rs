@@ -0,0 +1,8 @@
+pub mod cli;
+mod models;
+mod store;
+mod worker;
+
+pub use models::Delivery;
+pub use store::Outbox;
+pub use worker::DeliveryWorker;
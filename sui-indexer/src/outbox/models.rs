@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::action::channel::AlertChannel;
+use crate::risk::RiskEvent;
+use crate::schema::alert_outbox;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Dead, // Failed max_attempts times; retried only from the CLI
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+}
+
+/// Message for one channel
+#[derive(Debug, Clone)]
+pub enum Delivery {
+    Event(RiskEvent),
+    Digest(Vec<RiskEvent>),
+}
+
+impl Delivery {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Delivery::Event(_) => "event",
+            Delivery::Digest(_) => "digest",
+        }
+    }
+
+    pub fn payload(&self) -> serde_json::Result<serde_json::Value> {
+        match self {
+            Delivery::Event(event) => serde_json::to_value(event),
+            Delivery::Digest(events) => serde_json::to_value(events),
+        }
+    }
+
+    pub async fn send(&self, channel: &dyn AlertChannel) -> anyhow::Result<()> {
+        match self {
+            Delivery::Event(event) => channel.send(event).await,
+            Delivery::Digest(events) => channel.send_digest(events).await,
+        }
+    }
+}
+
+/// Alert outbox row - PostgreSQL model
+#[derive(Debug, Clone, Queryable, QueryableByName)]
+#[diesel(table_name = alert_outbox)]
+pub struct OutboxRecord {
+    pub id: i64,
+    pub channel: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl OutboxRecord {
+    pub fn delivery(&self) -> anyhow::Result<Delivery> {
+        match self.kind.as_str() {
+            "event" => Ok(Delivery::Event(serde_json::from_value(self.payload.clone())?)),
+            "digest" => Ok(Delivery::Digest(serde_json::from_value(self.payload.clone())?)),
+            other => anyhow::bail!("Unknown delivery kind {}", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = alert_outbox)]
+pub struct NewOutboxRecord {
+    pub channel: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+}
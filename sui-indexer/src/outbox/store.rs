@@ -0,0 +1,213 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Alert Outbox: Persistent Queue of Channel Deliveries with a Dead-Letter State
+
+use super::models::{Delivery, DeliveryStatus, NewOutboxRecord, OutboxRecord};
+use crate::schema::alert_outbox;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::sql_types::Double;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::pooled_connection::bb8::Pool;
+use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+/// Alert outbox in the `alert_outbox` table
+///
+/// Actions only insert; the delivery worker claims due rows, sends them and
+/// records the outcome. Rows failing `max_attempts` times become dead letters.
+pub struct Outbox {
+    pool: Pool<AsyncPgConnection>,
+}
+
+impl Outbox {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
+        let pool = Pool::builder().max_size(4).build(manager).await?;
+        Ok(Self { pool })
+    }
+
+    pub async fn enqueue(&self, channel: &str, delivery: &Delivery) -> Result<i64> {
+        let record = NewOutboxRecord {
+            channel: channel.to_string(),
+            kind: delivery.kind().to_string(),
+            payload: delivery.payload()?,
+        };
+
+        let mut conn = self.pool.get().await?;
+        let id = diesel::insert_into(alert_outbox::table)
+            .values(&record)
+            .returning(alert_outbox::id)
+            .get_result(&mut conn)
+            .await?;
+        Ok(id)
+    }
+
+    /// Claim the oldest due row, pushing its next attempt `lease_ms` out so a
+    /// crashed worker's row comes back and concurrent workers skip it
+    ///
+    /// The returned `next_attempt_at` is the lease: outcomes are only recorded
+    /// while the row still carries it.
+    pub async fn claim_next(&self, lease_ms: u64) -> Result<Option<OutboxRecord>> {
+        let mut conn = self.pool.get().await?;
+        let record = diesel::sql_query(
+            "UPDATE alert_outbox SET next_attempt_at = NOW() + make_interval(secs => $1)
+             WHERE id = (
+                 SELECT id FROM alert_outbox
+                 WHERE status = 'pending' AND next_attempt_at <= NOW()
+                 ORDER BY next_attempt_at, id
+                 LIMIT 1
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING *",
+        )
+        .bind::<Double, _>(lease_ms as f64 / 1000.0)
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+        Ok(record)
+    }
+
+    pub async fn mark_delivered(&self, id: i64, lease: DateTime<Utc>, attempts: i32) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let count = diesel::update(
+            alert_outbox::table
+                .find(id)
+                .filter(alert_outbox::status.eq(DeliveryStatus::Pending.as_str()))
+                .filter(alert_outbox::next_attempt_at.eq(lease)),
+        )
+        .set((
+            alert_outbox::status.eq(DeliveryStatus::Delivered.as_str()),
+            alert_outbox::attempts.eq(attempts),
+            alert_outbox::delivered_at.eq(Some(Utc::now())),
+        ))
+        .execute(&mut conn)
+        .await?;
+        lease_held(id, count)
+    }
+
+    pub async fn reschedule(
+        &self,
+        id: i64,
+        lease: DateTime<Utc>,
+        attempts: i32,
+        next_attempt_at: DateTime<Utc>,
+        error: &str,
+    ) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let count = diesel::update(
+            alert_outbox::table
+                .find(id)
+                .filter(alert_outbox::status.eq(DeliveryStatus::Pending.as_str()))
+                .filter(alert_outbox::next_attempt_at.eq(lease)),
+        )
+        .set((
+            alert_outbox::attempts.eq(attempts),
+            alert_outbox::next_attempt_at.eq(next_attempt_at),
+            alert_outbox::last_error.eq(Some(error)),
+        ))
+        .execute(&mut conn)
+        .await?;
+        lease_held(id, count)
+    }
+
+    pub async fn mark_dead(&self, id: i64, lease: DateTime<Utc>, attempts: i32, error: &str) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let count = diesel::update(
+            alert_outbox::table
+                .find(id)
+                .filter(alert_outbox::status.eq(DeliveryStatus::Pending.as_str()))
+                .filter(alert_outbox::next_attempt_at.eq(lease)),
+        )
+        .set((
+            alert_outbox::status.eq(DeliveryStatus::Dead.as_str()),
+            alert_outbox::attempts.eq(attempts),
+            alert_outbox::last_error.eq(Some(error)),
+        ))
+        .execute(&mut conn)
+        .await?;
+        lease_held(id, count)
+    }
+
+    /// Row count per status
+    pub async fn counts(&self) -> Result<Vec<(String, i64)>> {
+        let mut conn = self.pool.get().await?;
+        let counts = alert_outbox::table
+            .group_by(alert_outbox::status)
+            .select((alert_outbox::status, diesel::dsl::count_star()))
+            .order_by(alert_outbox::status)
+            .load(&mut conn)
+            .await?;
+        Ok(counts)
+    }
+
+    /// Dead letters, newest first
+    pub async fn dead_letters(&self, channel: Option<&str>, limit: i64) -> Result<Vec<OutboxRecord>> {
+        let mut conn = self.pool.get().await?;
+        let mut query = alert_outbox::table
+            .filter(alert_outbox::status.eq(DeliveryStatus::Dead.as_str()))
+            .order_by(alert_outbox::id.desc())
+            .limit(limit)
+            .into_boxed();
+        if let Some(channel) = channel {
+            query = query.filter(alert_outbox::channel.eq(channel.to_string()));
+        }
+        Ok(query.load(&mut conn).await?)
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<OutboxRecord>> {
+        let mut conn = self.pool.get().await?;
+        let record = alert_outbox::table.find(id).first(&mut conn).await.optional()?;
+        Ok(record)
+    }
+
+    /// Move dead letters back to pending with a fresh attempt count
+    pub async fn retry(&self, ids: &[i64]) -> Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let count = diesel::update(
+            alert_outbox::table
+                .filter(alert_outbox::id.eq_any(ids))
+                .filter(alert_outbox::status.eq(DeliveryStatus::Dead.as_str())),
+        )
+        .set((
+            alert_outbox::status.eq(DeliveryStatus::Pending.as_str()),
+            alert_outbox::attempts.eq(0),
+            alert_outbox::next_attempt_at.eq(Utc::now()),
+        ))
+        .execute(&mut conn)
+        .await?;
+        Ok(count)
+    }
+
+    /// Retry every dead letter, optionally of one channel
+    pub async fn retry_all(&self, channel: Option<&str>) -> Result<usize> {
+        let ids: Vec<i64> = self
+            .dead_letters(channel, i64::MAX)
+            .await?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        self.retry(&ids).await
+    }
+
+    /// Delete delivered rows created before `before`
+    pub async fn prune_delivered(&self, before: DateTime<Utc>) -> Result<usize> {
+        let mut conn = self.pool.get().await?;
+        let count = diesel::delete(
+            alert_outbox::table
+                .filter(alert_outbox::status.eq(DeliveryStatus::Delivered.as_str()))
+                .filter(alert_outbox::created_at.lt(before)),
+        )
+        .execute(&mut conn)
+        .await?;
+        Ok(count)
+    }
+}
+
+/// Fail when a row no longer carries the worker's lease: it expired and the
+/// row was claimed again or changed from the CLI, so the outcome is dropped
+fn lease_held(id: i64, count: usize) -> Result<()> {
+    if count == 0 {
+        anyhow::bail!("lease on alert #{} expired before its outcome was recorded", id);
+    }
+    Ok(())
+}
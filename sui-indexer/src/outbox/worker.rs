@@ -0,0 +1,189 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Delivery Worker: Sends Outbox Rows with Exponential Backoff and 429 Handling
+
+use super::models::OutboxRecord;
+use super::store::Outbox;
+use crate::action::channel::{AlertChannel, RateLimited, MAX_RETRY_AFTER};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Delivery worker
+///
+/// Polls the outbox for due rows and sends them through their channel:
+/// 1. Success marks the row delivered
+/// 2. HTTP 429 pauses the channel for `Retry-After`; not counted as an attempt
+/// 3. Other failures retry after `base_backoff_ms * 2^(attempts - 1)`, capped
+///    at `max_backoff_ms`
+/// 4. After `max_attempts` failures the row becomes a dead letter
+///
+/// Rows are claimed one at a time, right before they are sent, and a send is
+/// cut off at `send_timeout_ms`, well inside the lease. An outcome is only
+/// recorded while the row still carries this worker's lease.
+pub struct DeliveryWorker {
+    outbox: Arc<Outbox>,
+    channels: HashMap<&'static str, Arc<dyn AlertChannel>>,
+    poll_interval_ms: u64,
+    /// Rows processed per poll
+    batch_size: usize,
+    /// Time a claimed row is hidden from other workers
+    lease_ms: u64,
+    send_timeout_ms: u64,
+    max_attempts: i32,
+    base_backoff_ms: u64,
+    max_backoff_ms: u64,
+}
+
+impl DeliveryWorker {
+    pub fn new(outbox: Arc<Outbox>, channels: Vec<Arc<dyn AlertChannel>>) -> Self {
+        Self {
+            outbox,
+            channels: channels.into_iter().map(|c| (c.name(), c)).collect(),
+            poll_interval_ms: 1_000,
+            batch_size: 20,
+            lease_ms: 60_000,
+            send_timeout_ms: 30_000,
+            max_attempts: 8,
+            base_backoff_ms: 5_000,      // 5s
+            max_backoff_ms: 60 * 60_000, // 1 hour
+        }
+    }
+
+    pub fn from_env(outbox: Arc<Outbox>, channels: Vec<Arc<dyn AlertChannel>>) -> Self {
+        let mut worker = Self::new(outbox, channels);
+
+        if let Some(ms) = std::env::var("OUTBOX_POLL_INTERVAL_MS").ok().and_then(|v| v.parse().ok()) {
+            worker.poll_interval_ms = ms;
+        }
+        if let Some(attempts) = std::env::var("OUTBOX_MAX_ATTEMPTS").ok().and_then(|v| v.parse().ok()) {
+            worker = worker.with_max_attempts(attempts);
+        }
+        if let Some(ms) = std::env::var("OUTBOX_BASE_BACKOFF_MS").ok().and_then(|v| v.parse().ok()) {
+            worker.base_backoff_ms = ms;
+        }
+        if let Some(ms) = std::env::var("OUTBOX_MAX_BACKOFF_MS").ok().and_then(|v| v.parse().ok()) {
+            worker.max_backoff_ms = ms;
+        }
+
+        worker
+    }
+
+    /// Set the failures after which a row becomes a dead letter
+    pub fn with_max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Spawn the polling loop
+    pub fn start(self) {
+        tokio::spawn(async move {
+            // Channels answering 429, paused until the given time
+            let mut paused: HashMap<String, DateTime<Utc>> = HashMap::new();
+            loop {
+                let delivered = match self.run_once(&mut paused).await {
+                    Ok(count) => count,
+                    Err(e) => {
+                        eprintln!("⚠ Warning: Alert outbox poll failed: {}", e);
+                        0
+                    }
+                };
+                if delivered == 0 {
+                    tokio::time::sleep(Duration::from_millis(self.poll_interval_ms)).await;
+                }
+            }
+        });
+    }
+
+    /// Claim and process up to `batch_size` due rows; returns the number claimed
+    async fn run_once(&self, paused: &mut HashMap<String, DateTime<Utc>>) -> Result<usize> {
+        let mut claimed = 0;
+        while claimed < self.batch_size {
+            let Some(record) = self.outbox.claim_next(self.lease_ms).await? else {
+                break;
+            };
+            claimed += 1;
+            if let Err(e) = self.process(&record, paused).await {
+                // Row comes back when its lease expires
+                eprintln!("⚠ Warning: Failed to record outcome of alert #{}: {}", record.id, e);
+            }
+        }
+        Ok(claimed)
+    }
+
+    async fn process(&self, record: &OutboxRecord, paused: &mut HashMap<String, DateTime<Utc>>) -> Result<()> {
+        let lease = record.next_attempt_at;
+        let delivery = match record.delivery() {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                let error = format!("Invalid payload: {}", e);
+                return self.outbox.mark_dead(record.id, lease, record.attempts, &error).await;
+            }
+        };
+        let Some(channel) = self.channels.get(record.channel.as_str()) else {
+            let error = format!("Channel {} is not configured", record.channel);
+            return self.outbox.mark_dead(record.id, lease, record.attempts, &error).await;
+        };
+
+        let now = Utc::now();
+        if let Some(until) = paused.get(&record.channel).filter(|until| **until > now) {
+            return self.outbox.reschedule(record.id, lease, record.attempts, *until, "Channel rate limited").await;
+        }
+
+        let timeout = Duration::from_millis(self.send_timeout_ms);
+        let error = match tokio::time::timeout(timeout, delivery.send(channel.as_ref())).await {
+            Ok(Ok(())) => {
+                println!("✅ Alert #{} sent to {}", record.id, record.channel);
+                return self.outbox.mark_delivered(record.id, lease, record.attempts + 1).await;
+            }
+            Ok(Err(e)) => e,
+            Err(_) => anyhow::anyhow!("Send timed out after {}ms", self.send_timeout_ms),
+        };
+
+        if let Some(limited) = error.downcast_ref::<RateLimited>() {
+            let wait = limited.retry_after.min(MAX_RETRY_AFTER);
+            let until = now + chrono::Duration::from_std(wait).unwrap_or_else(|_| chrono::Duration::hours(1));
+            paused.insert(record.channel.clone(), until);
+            println!("⏳ {} {}; alert #{} waits", record.channel, limited, record.id);
+            return self.outbox.reschedule(record.id, lease, record.attempts, until, &error.to_string()).await;
+        }
+
+        let attempts = record.attempts + 1;
+        if attempts >= self.max_attempts {
+            eprintln!(
+                "⚠ Warning: Alert #{} to {} moved to dead letters after {} attempts: {}",
+                record.id, record.channel, attempts, error
+            );
+            return self.outbox.mark_dead(record.id, lease, attempts, &error.to_string()).await;
+        }
+
+        let delay_ms = backoff_ms(attempts, self.base_backoff_ms, self.max_backoff_ms);
+        println!(
+            "❌ Failed to send alert #{} to {} (attempt {}), retrying in {}ms: {}",
+            record.id, record.channel, attempts, delay_ms, error
+        );
+        let next = now + chrono::Duration::milliseconds(delay_ms as i64);
+        self.outbox.reschedule(record.id, lease, attempts, next, &error.to_string()).await
+    }
+}
+
+/// Delay before the retry following the `attempts`-th failure
+pub fn backoff_ms(attempts: i32, base_ms: u64, max_ms: u64) -> u64 {
+    let exponent = (attempts.max(1) - 1).min(32) as u32;
+    base_ms.saturating_mul(1u64 << exponent).min(max_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        assert_eq!(backoff_ms(1, 5_000, 3_600_000), 5_000);
+        assert_eq!(backoff_ms(2, 5_000, 3_600_000), 10_000);
+        assert_eq!(backoff_ms(4, 5_000, 3_600_000), 40_000);
+        assert_eq!(backoff_ms(20, 5_000, 3_600_000), 3_600_000);
+        assert_eq!(backoff_ms(1_000, 5_000, 3_600_000), 3_600_000);
+    }
+}
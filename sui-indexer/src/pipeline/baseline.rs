@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::BaselineAnalyzer;
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct BaselineDetector {
+    analyzer: BaselineAnalyzer,
+}
+
+impl BaselineDetector {
+    pub fn new() -> Self {
+        Self {
+            analyzer: BaselineAnalyzer::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for BaselineDetector {
+    fn name(&self) -> &'static str {
+        "BaselineDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context)
+    }
+}
+
+impl Default for BaselineDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,8 @@
 use async_trait::async_trait;
+use std::sync::Arc;
 use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::pipeline::EventMerger;
+use crate::reputation::ReputationStore;
 use crate::risk::{RiskEvent, DetectionContext};
 
 #[async_trait]
@@ -15,15 +18,31 @@ pub trait RiskDetector: Send + Sync {
 
 pub struct DetectionPipeline {
     detectors: Vec<Box<dyn RiskDetector>>,
+    reputation: Option<Arc<ReputationStore>>,
+    merger: Option<EventMerger>,
 }
 
 impl DetectionPipeline {
     pub fn new() -> Self {
         Self {
             detectors: Vec::new(),
+            reputation: None,
+            merger: None,
         }
     }
 
+    /// Adjust detector results by the sender's reputation
+    pub fn with_reputation(mut self, reputation: Arc<ReputationStore>) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
+    /// Merge events several detectors raised for the same transaction
+    pub fn with_merger(mut self, merger: EventMerger) -> Self {
+        self.merger = Some(merger);
+        self
+    }
+
     pub fn add_detector<D: RiskDetector + 'static>(mut self, detector: D) -> Self {
         self.detectors.push(Box::new(detector));
         self
@@ -38,10 +57,16 @@ impl DetectionPipeline {
 
         for detector in &self.detectors {
             let detector_events = detector.detect(tx, context).await;
-            events.extend(detector_events);
+            match &self.reputation {
+                Some(reputation) => events.extend(detector_events.into_iter().filter_map(|e| reputation.adjust(e))),
+                None => events.extend(detector_events),
+            }
         }
 
-        events
+        match &self.merger {
+            Some(merger) => merger.merge(events),
+            None => events,
+        }
     }
 }
 
@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::{GasPriceAnalyzer, GasPriceMonitor};
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct GasPriceDetector {
+    analyzer: GasPriceAnalyzer,
+}
+
+impl GasPriceDetector {
+    pub fn new(monitor: Arc<GasPriceMonitor>) -> Self {
+        Self {
+            analyzer: GasPriceAnalyzer::new(monitor),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for GasPriceDetector {
+    fn name(&self) -> &'static str {
+        "GasPriceDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context).into_iter().collect()
+    }
+}
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::{GovernanceAnalyzer, GovernanceWatchlist};
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct GovernanceDetector {
+    analyzer: GovernanceAnalyzer,
+}
+
+impl GovernanceDetector {
+    pub fn new(watchlist: GovernanceWatchlist) -> Self {
+        Self {
+            analyzer: GovernanceAnalyzer::new(watchlist),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for GovernanceDetector {
+    fn name(&self) -> &'static str {
+        "GovernanceDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context).into_iter().collect()
+    }
+}
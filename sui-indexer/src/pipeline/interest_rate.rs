@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::InterestRateAnalyzer;
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct InterestRateDetector {
+    analyzer: InterestRateAnalyzer,
+}
+
+impl InterestRateDetector {
+    pub fn new() -> Self {
+        Self {
+            analyzer: InterestRateAnalyzer::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for InterestRateDetector {
+    fn name(&self) -> &'static str {
+        "InterestRateDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context)
+    }
+}
+
+impl Default for InterestRateDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::JitLiquidityAnalyzer;
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct JitLiquidityDetector {
+    analyzer: JitLiquidityAnalyzer,
+}
+
+impl JitLiquidityDetector {
+    pub fn new() -> Self {
+        Self {
+            analyzer: JitLiquidityAnalyzer::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for JitLiquidityDetector {
+    fn name(&self) -> &'static str {
+        "JitLiquidityDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context)
+    }
+}
+
+impl Default for JitLiquidityDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
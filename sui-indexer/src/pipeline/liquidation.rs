@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::LiquidationAnalyzer;
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct LiquidationDetector {
+    analyzer: LiquidationAnalyzer,
+}
+
+impl LiquidationDetector {
+    pub fn new() -> Self {
+        Self {
+            analyzer: LiquidationAnalyzer::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for LiquidationDetector {
+    fn name(&self) -> &'static str {
+        "LiquidationDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context).into_iter().collect()
+    }
+}
+
+impl Default for LiquidationDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
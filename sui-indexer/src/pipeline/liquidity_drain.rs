@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::LiquidityDrainAnalyzer;
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct LiquidityDrainDetector {
+    analyzer: LiquidityDrainAnalyzer,
+}
+
+impl LiquidityDrainDetector {
+    pub fn new() -> Self {
+        Self {
+            analyzer: LiquidityDrainAnalyzer::from_env(),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for LiquidityDrainDetector {
+    fn name(&self) -> &'static str {
+        "LiquidityDrainDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context)
+    }
+}
+
+impl Default for LiquidityDrainDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
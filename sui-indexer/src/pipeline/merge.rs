@@ -0,0 +1,257 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Cross-Detector Merging: One Primary Classification per Transaction
+
+use crate::risk::{RiskEvent, RiskType};
+
+/// Classifications from most to least specific
+///
+/// An oracle attack is usually funded by a flash loan and moves prices, so the
+/// oracle classification explains the other two and takes precedence.
+const DEFAULT_PRECEDENCE: [RiskType; 14] = [
+    RiskType::OracleManipulation,
+    RiskType::LiquidationAnomaly,
+    RiskType::SandwichAttack,
+    RiskType::JitLiquidity,
+    RiskType::InterestRateManipulation,
+    RiskType::LiquidityDrain,
+    RiskType::PumpAndDump,
+    RiskType::WashTrading,
+    RiskType::GovernanceChange,
+    RiskType::FlashLoanAttack,
+    RiskType::PriceManipulation,
+    RiskType::FailedTransactionProbing,
+    RiskType::PriorityGasBidding,
+    RiskType::StatisticalAnomaly,
+];
+
+/// Merges events that several detectors raised for the same transaction
+///
+/// The event whose type comes first in `precedence` becomes the primary
+/// classification; types not listed rank last, then by level and score. The
+/// merged event keeps the primary's signals and score and gets:
+/// 1. The highest risk level of the group, with the score raised into its band
+/// 2. The other types as `secondary_types`
+/// 3. Details and metrics of the other events the primary doesn't have
+/// 4. A `secondary_events` detail summarizing each merged event
+/// 5. A `pool_ids` detail when the merged events name different pools
+///
+/// Events of the same type on different pools are separate findings and
+/// stay separate events.
+pub struct EventMerger {
+    precedence: Vec<RiskType>,
+}
+
+impl EventMerger {
+    pub fn new() -> Self {
+        Self {
+            precedence: DEFAULT_PRECEDENCE.to_vec(),
+        }
+    }
+
+    /// Read `MERGE_PRECEDENCE` (comma-separated risk types, most specific first)
+    pub fn from_env() -> Self {
+        let merger = Self::new();
+        match std::env::var("MERGE_PRECEDENCE") {
+            Ok(value) => merger.with_precedence(parse_precedence(&value)),
+            Err(_) => merger,
+        }
+    }
+
+    /// Set the classification order, most specific first
+    pub fn with_precedence(mut self, precedence: Vec<RiskType>) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Merge events per transaction, keeping the order of first appearance
+    pub fn merge(&self, events: Vec<RiskEvent>) -> Vec<RiskEvent> {
+        let mut groups: Vec<Vec<RiskEvent>> = Vec::new();
+        for event in events {
+            let group = groups.iter_mut().find(|g| {
+                g[0].tx_digest == event.tx_digest && !g.iter().any(|e| same_type_other_pool(e, &event))
+            });
+            match group {
+                Some(group) => group.push(event),
+                None => groups.push(vec![event]),
+            }
+        }
+
+        groups.into_iter().map(|group| self.merge_group(group)).collect()
+    }
+
+    fn merge_group(&self, mut group: Vec<RiskEvent>) -> RiskEvent {
+        if group.len() == 1 {
+            return group.remove(0);
+        }
+
+        // Lowest rank first; on equal rank the higher level and score win
+        group.sort_by(|a, b| {
+            self.rank(&a.risk_type)
+                .cmp(&self.rank(&b.risk_type))
+                .then(b.risk_level.cmp(&a.risk_level))
+                .then(b.score.cmp(&a.score))
+        });
+
+        let mut primary = group.remove(0);
+        let mut secondary_events = Vec::new();
+        let mut pool_ids: Vec<&str> = Vec::new();
+        for event in std::iter::once(&primary).chain(group.iter()) {
+            if let Some(pool_id) = pool_id(event) {
+                if !pool_ids.contains(&pool_id) {
+                    pool_ids.push(pool_id);
+                }
+            }
+        }
+        let pool_ids = serde_json::json!(pool_ids);
+
+        for event in group {
+            primary.risk_level = primary.risk_level.max(event.risk_level);
+            if event.risk_type != primary.risk_type && !primary.secondary_types.contains(&event.risk_type) {
+                primary.secondary_types.push(event.risk_type.clone());
+            }
+            for secondary_type in &event.secondary_types {
+                if *secondary_type != primary.risk_type && !primary.secondary_types.contains(secondary_type) {
+                    primary.secondary_types.push(secondary_type.clone());
+                }
+            }
+
+            secondary_events.push(serde_json::json!({
+                "risk_type": event.risk_type,
+                "risk_level": event.risk_level,
+                "score": event.score,
+                "description": event.description,
+                "signals": event.signals,
+            }));
+
+            for (key, value) in event.details {
+                primary.details.entry(key).or_insert(value);
+            }
+            for (key, value) in event.metrics {
+                primary.metrics.entry(key).or_insert(value);
+            }
+        }
+
+        if pool_ids.as_array().is_some_and(|ids| ids.len() > 1) {
+            primary = primary.with_detail("pool_ids", pool_ids);
+        }
+        primary
+            .with_detail("secondary_events", serde_json::json!(secondary_events))
+            .with_score_in_level_band()
+    }
+
+    fn rank(&self, risk_type: &RiskType) -> usize {
+        self.precedence
+            .iter()
+            .position(|t| t == risk_type)
+            .unwrap_or(self.precedence.len())
+    }
+}
+
+impl Default for EventMerger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn pool_id(event: &RiskEvent) -> Option<&str> {
+    event.details.get("pool_id").and_then(|v| v.as_str())
+}
+
+/// Same classification on a different pool: a separate finding, not a duplicate
+fn same_type_other_pool(a: &RiskEvent, b: &RiskEvent) -> bool {
+    a.risk_type == b.risk_type
+        && matches!((pool_id(a), pool_id(b)), (Some(x), Some(y)) if x != y)
+}
+
+/// Parse risk type names, skipping unknown ones
+fn parse_precedence(value: &str) -> Vec<RiskType> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| match serde_json::from_value(serde_json::json!(name)) {
+            Ok(risk_type) => Some(risk_type),
+            Err(_) => {
+                eprintln!("⚠ Warning: Unknown risk type in MERGE_PRECEDENCE: {}", name);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::{RiskLevel, Signal};
+
+    fn event(risk_type: RiskType, level: RiskLevel, tx: &str) -> RiskEvent {
+        RiskEvent::new(risk_type, level, tx.to_string(), "0xa".to_string(), 1000, 0, "test".to_string())
+    }
+
+    #[test]
+    fn test_oracle_attack_merged_into_one_event() {
+        let flash_loan = event(RiskType::FlashLoanAttack, RiskLevel::Critical, "tx")
+            .with_detail("circular_trading", true)
+            .with_metric("total_borrowed", 1e10);
+        let price = event(RiskType::PriceManipulation, RiskLevel::High, "tx")
+            .with_detail("pool_id", "0xpool");
+        let oracle = event(RiskType::OracleManipulation, RiskLevel::High, "tx")
+            .with_metric("total_borrowed", 5e9)
+            .with_signals(vec![Signal::flag("flash_loan", 20)]);
+
+        let merged = EventMerger::new().merge(vec![flash_loan, price, oracle]);
+        assert_eq!(merged.len(), 1);
+
+        let event = &merged[0];
+        assert_eq!(event.risk_type, RiskType::OracleManipulation);
+        assert_eq!(event.risk_level, RiskLevel::Critical);
+        assert_eq!(event.secondary_types, vec![RiskType::FlashLoanAttack, RiskType::PriceManipulation]);
+        assert_eq!(event.score, 85);
+        assert_eq!(event.metrics["total_borrowed"], 5e9);
+        assert_eq!(event.details["pool_id"], serde_json::json!("0xpool"));
+        assert_eq!(event.details["secondary_events"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_other_transactions_untouched() {
+        let events = vec![
+            event(RiskType::FlashLoanAttack, RiskLevel::High, "tx1"),
+            event(RiskType::SandwichAttack, RiskLevel::Medium, "tx2"),
+        ];
+
+        let merged = EventMerger::new().merge(events);
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().all(|e| e.secondary_types.is_empty()));
+        assert!(!merged[0].details.contains_key("secondary_events"));
+    }
+
+    #[test]
+    fn test_configured_precedence() {
+        let events = vec![
+            event(RiskType::OracleManipulation, RiskLevel::High, "tx"),
+            event(RiskType::FlashLoanAttack, RiskLevel::High, "tx"),
+        ];
+
+        let merger = EventMerger::new().with_precedence(parse_precedence("FlashLoanAttack, Bogus"));
+        let merged = merger.merge(events);
+        assert_eq!(merged[0].risk_type, RiskType::FlashLoanAttack);
+        assert_eq!(merged[0].secondary_types, vec![RiskType::OracleManipulation]);
+    }
+
+    #[test]
+    fn test_same_type_on_other_pools_kept_separate() {
+        let events = vec![
+            event(RiskType::LiquidityDrain, RiskLevel::High, "tx").with_detail("pool_id", "0xpool1"),
+            event(RiskType::LiquidityDrain, RiskLevel::Critical, "tx").with_detail("pool_id", "0xpool2"),
+            event(RiskType::WashTrading, RiskLevel::Medium, "tx").with_detail("pool_id", "0xpool3"),
+        ];
+
+        let merged = EventMerger::new().merge(events);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].details["pool_id"], serde_json::json!("0xpool1"));
+        assert_eq!(merged[0].secondary_types, vec![RiskType::WashTrading]);
+        assert_eq!(merged[0].details["pool_ids"], serde_json::json!(["0xpool1", "0xpool3"]));
+        assert_eq!(merged[1].details["pool_id"], serde_json::json!("0xpool2"));
+        assert!(merged[1].secondary_types.is_empty());
+    }
+}
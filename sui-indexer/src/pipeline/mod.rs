@@ -1,11 +1,35 @@
 mod detector;
+mod merge;
 mod flash_loan;
 mod price_manipulation;
 mod sandwich;
 mod oracle_manipulation;
+mod liquidation;
+mod jit_liquidity;
+mod wash_trading;
+mod governance;
+mod interest_rate;
+mod probing;
+mod liquidity_drain;
+mod gas_price;
+mod baseline;
+mod rule;
+mod plugin;
 
 pub use detector::{RiskDetector, DetectionPipeline};
+pub use merge::EventMerger;
 pub use flash_loan::FlashLoanDetector;
 pub use price_manipulation::PriceManipulationDetector;
 pub use sandwich::SandwichDetector;
 pub use oracle_manipulation::OracleManipulationDetector;
+pub use liquidation::LiquidationDetector;
+pub use jit_liquidity::JitLiquidityDetector;
+pub use wash_trading::WashTradingDetector;
+pub use governance::GovernanceDetector;
+pub use interest_rate::InterestRateDetector;
+pub use probing::ProbingDetector;
+pub use liquidity_drain::LiquidityDrainDetector;
+pub use gas_price::GasPriceDetector;
+pub use baseline::BaselineDetector;
+pub use rule::RuleDetector;
+pub use plugin::PluginDetector;
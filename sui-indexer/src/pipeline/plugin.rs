@@ -0,0 +1,90 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::pipeline::RiskDetector;
+use crate::plugin::{PluginRuntime, TxView, WasmPlugin};
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct PluginDetector {
+    runtime: Arc<PluginRuntime>,
+    plugins: Arc<Vec<WasmPlugin>>,
+}
+
+impl PluginDetector {
+    pub fn new(runtime: PluginRuntime, plugins: Vec<WasmPlugin>) -> Self {
+        Self {
+            runtime: Arc::new(runtime),
+            plugins: Arc::new(plugins),
+        }
+    }
+
+    /// Load the modules in `PLUGIN_DIR`, if set
+    pub fn from_env() -> Result<Self> {
+        let runtime = PluginRuntime::from_env()?;
+        let plugins = match std::env::var("PLUGIN_DIR") {
+            Ok(dir) => runtime.load_dir(std::path::Path::new(&dir))?,
+            Err(_) => Vec::new(),
+        };
+        Ok(Self::new(runtime, plugins))
+    }
+
+    pub fn plugin_count(&self) -> usize {
+        self.plugins.len()
+    }
+}
+
+#[async_trait]
+impl RiskDetector for PluginDetector {
+    fn name(&self) -> &'static str {
+        "PluginDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        if self.plugins.is_empty() {
+            return Vec::new();
+        }
+
+        let input = match serde_json::to_vec(&TxView::new(tx, context)) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("⚠ Warning: Failed to serialize plugin input: {}", e);
+                return Vec::new();
+            }
+        };
+
+        // WASM calls block for up to the time limit, so they run off the async workers
+        let runtime = self.runtime.clone();
+        let plugins = self.plugins.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            plugins
+                .iter()
+                .map(|plugin| (plugin.name.clone(), runtime.run(plugin, &input)))
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+        let results = match results {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("⚠ Warning: Plugin task failed on {}: {}", context.tx_digest, e);
+                return Vec::new();
+            }
+        };
+
+        let mut events = Vec::new();
+        for (name, result) in results {
+            match result {
+                Ok(output) => events.extend(
+                    output.findings.into_iter().map(|finding| finding.into_event(&name, context)),
+                ),
+                Err(e) => eprintln!("⚠ Warning: Plugin {} failed on {}: {}", name, context.tx_digest, e),
+            }
+        }
+        events
+    }
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
 use sui_types::full_checkpoint_content::ExecutedTransaction;
-use crate::analyzer::PriceAnalyzer;
+use crate::analyzer::{AddressClusterer, PriceAnalyzer};
 use crate::pipeline::RiskDetector;
 use crate::risk::{RiskEvent, DetectionContext};
 
@@ -9,9 +10,9 @@ pub struct PriceManipulationDetector {
 }
 
 impl PriceManipulationDetector {
-    pub fn new() -> Self {
+    pub fn new(clusterer: Arc<Mutex<AddressClusterer>>) -> Self {
         Self {
-            analyzer: PriceAnalyzer::new(),
+            analyzer: PriceAnalyzer::new().with_clusterer(clusterer),
         }
     }
 }
@@ -33,6 +34,6 @@ impl RiskDetector for PriceManipulationDetector {
 
 impl Default for PriceManipulationDetector {
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(Mutex::new(AddressClusterer::new())))
     }
 }
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::{ProbingAnalyzer, GovernanceWatchlist};
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct ProbingDetector {
+    analyzer: ProbingAnalyzer,
+}
+
+impl ProbingDetector {
+    pub fn new(watchlist: GovernanceWatchlist) -> Self {
+        Self {
+            analyzer: ProbingAnalyzer::new(watchlist),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for ProbingDetector {
+    fn name(&self) -> &'static str {
+        "ProbingDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context).into_iter().collect()
+    }
+}
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+use crate::rule::{decode_events, RuleEngine};
+
+pub struct RuleDetector {
+    engine: RuleEngine,
+}
+
+impl RuleDetector {
+    pub fn new(engine: RuleEngine) -> Self {
+        Self { engine }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for RuleDetector {
+    fn name(&self) -> &'static str {
+        "RuleDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        if self.engine.is_empty() {
+            return Vec::new();
+        }
+        self.engine.evaluate(&decode_events(tx), context)
+    }
+}
+
+impl Default for RuleDetector {
+    fn default() -> Self {
+        Self::new(RuleEngine::new())
+    }
+}
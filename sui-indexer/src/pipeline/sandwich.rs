@@ -1,6 +1,7 @@
 use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
 use sui_types::full_checkpoint_content::ExecutedTransaction;
-use crate::analyzer::SandwichAnalyzer;
+use crate::analyzer::{AddressClusterer, GasPriceMonitor, SandwichAnalyzer};
 use crate::pipeline::RiskDetector;
 use crate::risk::{RiskEvent, DetectionContext};
 
@@ -9,9 +10,11 @@ pub struct SandwichDetector {
 }
 
 impl SandwichDetector {
-    pub fn new() -> Self {
+    pub fn new(gas_monitor: Arc<GasPriceMonitor>, clusterer: Arc<Mutex<AddressClusterer>>) -> Self {
         Self {
-            analyzer: SandwichAnalyzer::new(),
+            analyzer: SandwichAnalyzer::new()
+                .with_gas_monitor(gas_monitor)
+                .with_clusterer(clusterer),
         }
     }
 }
@@ -33,6 +36,9 @@ impl RiskDetector for SandwichDetector {
 
 impl Default for SandwichDetector {
     fn default() -> Self {
-        Self::new()
+        Self::new(
+            Arc::new(GasPriceMonitor::new()),
+            Arc::new(Mutex::new(AddressClusterer::new())),
+        )
     }
 }
@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+use crate::analyzer::{AddressClusterer, WashTradingAnalyzer};
+use crate::pipeline::RiskDetector;
+use crate::risk::{RiskEvent, DetectionContext};
+
+pub struct WashTradingDetector {
+    analyzer: WashTradingAnalyzer,
+}
+
+impl WashTradingDetector {
+    pub fn new(clusterer: Arc<Mutex<AddressClusterer>>) -> Self {
+        Self {
+            analyzer: WashTradingAnalyzer::new().with_clusterer(clusterer),
+        }
+    }
+}
+
+#[async_trait]
+impl RiskDetector for WashTradingDetector {
+    fn name(&self) -> &'static str {
+        "WashTradingDetector"
+    }
+
+    async fn detect(
+        &self,
+        tx: &ExecutedTransaction,
+        context: &DetectionContext,
+    ) -> Vec<RiskEvent> {
+        self.analyzer.analyze(tx, context)
+    }
+}
+
+impl Default for WashTradingDetector {
+    fn default() -> Self {
+        Self::new(Arc::new(Mutex::new(AddressClusterer::new())))
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Plugin ABI: Versioned Transaction View and Findings Exchanged with WASM Modules
+//
+// A module is loaded if it exports:
+//   memory                                  linear memory
+//   abi_version() -> i32                    must equal ABI_VERSION
+//   alloc(len: i32) -> i32                  buffer for the input
+//   detect(ptr: i32, len: i32) -> i64       (out_ptr << 32) | out_len
+//
+// The input is a JSON `TxView`, the output a JSON `PluginOutput`. Modules get
+// no imports: no WASI, clock, network or filesystem. Fields are only ever
+// added within a version; removing or changing one bumps ABI_VERSION.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+use crate::events::decode_event_fields;
+use crate::models::{EsExecutionFailure, EsFlattener};
+use crate::risk::{DetectionContext, RiskEvent, RiskLevel, RiskType, Signal};
+
+/// Version of the host ABI and the JSON schema
+pub const ABI_VERSION: i32 = 1;
+
+/// Event emitted by the transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventView {
+    pub package: String,
+    pub module: String,
+    pub name: String,
+    pub sender: String,
+    /// Decoded fields for known events
+    pub fields: Option<serde_json::Value>,
+    /// Raw BCS contents, hex-encoded
+    pub contents: String,
+}
+
+/// `MoveCall` command of the PTB
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveCallView {
+    pub package: String,
+    pub module: String,
+    pub function: String,
+}
+
+/// Why the transaction failed
+///
+/// Part of the ABI, so it doesn't follow changes to the Elasticsearch document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureView {
+    /// `ExecutionFailureStatus` variant, e.g. "MoveAbort", "InsufficientGas"
+    pub kind: String,
+    pub command_index: Option<u64>,
+    /// Abort location, set for MoveAbort and MovePrimitiveRuntimeError
+    pub abort_package: Option<String>,
+    pub abort_module: Option<String>,
+    pub abort_function: Option<String>,
+    pub abort_function_index: Option<u32>,
+    pub abort_instruction: Option<u32>,
+    /// MoveAbort only
+    pub abort_code: Option<u64>,
+}
+
+impl From<EsExecutionFailure> for FailureView {
+    fn from(failure: EsExecutionFailure) -> Self {
+        Self {
+            kind: failure.kind,
+            command_index: failure.command_index,
+            abort_package: failure.abort_package,
+            abort_module: failure.abort_module,
+            abort_function: failure.abort_function,
+            abort_function_index: failure.abort_function_index,
+            abort_instruction: failure.abort_instruction,
+            abort_code: failure.abort_code,
+        }
+    }
+}
+
+/// Summary of the transaction effects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectsView {
+    pub success: bool,
+    pub failure: Option<FailureView>,
+    pub gas_used: u64,
+    pub created: usize,
+    pub mutated: usize,
+    pub deleted: usize,
+}
+
+/// Per-transaction input of `detect`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxView {
+    pub abi_version: i32,
+    pub tx_digest: String,
+    pub sender: String,
+    pub checkpoint: i64,
+    pub timestamp_ms: i64,
+    pub events: Vec<EventView>,
+    pub move_calls: Vec<MoveCallView>,
+    pub effects: EffectsView,
+}
+
+impl TxView {
+    pub fn new(tx: &ExecutedTransaction, context: &DetectionContext) -> Self {
+        let events = tx
+            .events
+            .as_ref()
+            .map(|events| {
+                events
+                    .data
+                    .iter()
+                    .map(|event| EventView {
+                        package: event.type_.address.to_hex_literal(),
+                        module: event.type_.module.to_string(),
+                        name: event.type_.name.to_string(),
+                        sender: event.sender.to_string(),
+                        fields: decode_event_fields(event),
+                        contents: hex::encode(&event.contents),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let move_calls = EsFlattener::extract_move_calls(&tx.transaction)
+            .into_iter()
+            .map(|call| MoveCallView {
+                package: call.package,
+                module: call.module,
+                function: call.function,
+            })
+            .collect();
+
+        let failure = EsFlattener::extract_failure(&tx.effects).map(FailureView::from);
+        let effects = EffectsView {
+            success: failure.is_none(),
+            failure,
+            gas_used: tx.effects.gas_cost_summary().gas_used(),
+            created: tx.effects.created().len(),
+            mutated: tx.effects.mutated().len(),
+            deleted: tx.effects.deleted().len(),
+        };
+
+        Self {
+            abi_version: ABI_VERSION,
+            tx_digest: context.tx_digest.clone(),
+            sender: context.sender.clone(),
+            checkpoint: context.checkpoint,
+            timestamp_ms: context.timestamp_ms,
+            events,
+            move_calls,
+            effects,
+        }
+    }
+}
+
+/// Risk finding returned by a module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub risk_type: RiskType,
+    pub risk_level: RiskLevel,
+    pub description: String,
+    /// Defaults to the transaction sender
+    #[serde(default)]
+    pub sender: Option<String>,
+    #[serde(default)]
+    pub details: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub metrics: HashMap<String, f64>,
+    /// Points on the 0-100 scale; the score is kept within the level's band
+    #[serde(default)]
+    pub signals: Vec<Signal>,
+}
+
+impl Finding {
+    pub fn into_event(self, plugin: &str, context: &DetectionContext) -> RiskEvent {
+        let mut event = RiskEvent::new(
+            self.risk_type,
+            self.risk_level,
+            context.tx_digest.clone(),
+            self.sender.unwrap_or_else(|| context.sender.clone()),
+            context.checkpoint,
+            context.timestamp_ms,
+            self.description,
+        );
+        event.details = self.details;
+        event.metrics = self.metrics;
+        event
+            .with_detail("plugin", serde_json::json!(plugin))
+            .with_signals(self.signals)
+    }
+}
+
+/// Output of `detect`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginOutput {
+    #[serde(default)]
+    pub findings: Vec<Finding>,
+}
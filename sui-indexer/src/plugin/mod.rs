@@ -0,0 +1,5 @@
+mod abi;
+mod runtime;
+
+pub use abi::{EffectsView, EventView, FailureView, Finding, MoveCallView, PluginOutput, TxView, ABI_VERSION};
+pub use runtime::{PluginRuntime, WasmPlugin};
@@ -0,0 +1,305 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Plugin Runtime: Sandboxed WASM Detector Modules with Fuel, Time and Memory Limits
+
+use super::abi::{PluginOutput, ABI_VERSION};
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Once;
+use std::time::Duration;
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Interval of the epoch ticker that enforces time limits
+const EPOCH_TICK_MS: u64 = 5;
+
+/// Per-call store data
+struct HostState {
+    limits: StoreLimits,
+}
+
+/// Loaded detector module
+pub struct WasmPlugin {
+    pub name: String,
+    module: Module,
+    consecutive_failures: AtomicU32,
+    disabled: AtomicBool,
+}
+
+impl WasmPlugin {
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(Ordering::Relaxed)
+    }
+}
+
+/// Plugin runtime
+///
+/// Every call gets a fresh instance with no imports, so modules share no state
+/// between transactions and can't reach the host. Each call is bounded by:
+/// 1. Fuel (roughly one unit per instruction)
+/// 2. Wall-clock time, enforced by epoch interruption
+/// 3. Linear memory size and output size
+///
+/// A module failing `max_consecutive_failures` calls in a row is disabled.
+/// The epoch ticker thread only starts once a module is loaded.
+pub struct PluginRuntime {
+    engine: Engine,
+    ticker: Once,
+    fuel: u64,
+    timeout_ms: u64,
+    max_memory_bytes: usize,
+    max_output_bytes: usize,
+    max_consecutive_failures: u32,
+}
+
+impl PluginRuntime {
+    pub fn new() -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+
+        Ok(Self {
+            engine,
+            ticker: Once::new(),
+            fuel: 10_000_000,
+            timeout_ms: 50,
+            max_memory_bytes: 16 * 1024 * 1024, // 16 MiB
+            max_output_bytes: 1024 * 1024,      // 1 MiB
+            max_consecutive_failures: 10,
+        })
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let mut runtime = Self::new()?;
+
+        if let Some(fuel) = std::env::var("PLUGIN_FUEL").ok().and_then(|v| v.parse().ok()) {
+            runtime = runtime.with_fuel(fuel);
+        }
+        if let Some(ms) = std::env::var("PLUGIN_TIMEOUT_MS").ok().and_then(|v| v.parse().ok()) {
+            runtime = runtime.with_timeout_ms(ms);
+        }
+        if let Some(bytes) = std::env::var("PLUGIN_MAX_MEMORY_BYTES").ok().and_then(|v| v.parse().ok()) {
+            runtime = runtime.with_max_memory_bytes(bytes);
+        }
+
+        Ok(runtime)
+    }
+
+    /// Set the fuel available to one `detect` call
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Set the wall-clock limit of one `detect` call
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Set the maximum linear memory of a module instance
+    pub fn with_max_memory_bytes(mut self, bytes: usize) -> Self {
+        self.max_memory_bytes = bytes;
+        self
+    }
+
+    /// Load every `.wasm` file in a directory
+    pub fn load_dir(&self, dir: &Path) -> Result<Vec<WasmPlugin>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read plugin directory {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+            .collect();
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read plugin {}", path.display()))?;
+                self.load(&name, &bytes)
+                    .with_context(|| format!("Failed to load plugin {}", path.display()))
+            })
+            .collect()
+    }
+
+    /// Compile a module and check its exports and ABI version
+    pub fn load(&self, name: &str, bytes: &[u8]) -> Result<WasmPlugin> {
+        self.start_ticker()?;
+        let module = Module::new(&self.engine, bytes)?;
+        if module.imports().next().is_some() {
+            bail!("Plugin imports are not allowed");
+        }
+
+        let (mut store, instance) = self.instantiate(&module)?;
+        let version = instance
+            .get_typed_func::<(), i32>(&mut store, "abi_version")?
+            .call(&mut store, ())?;
+        if version != ABI_VERSION {
+            bail!("Plugin ABI version {} is not supported (host is {})", version, ABI_VERSION);
+        }
+        instance.get_memory(&mut store, "memory").context("Missing memory export")?;
+        instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        instance.get_typed_func::<(i32, i32), i64>(&mut store, "detect")?;
+
+        Ok(WasmPlugin {
+            name: name.to_string(),
+            module,
+            consecutive_failures: AtomicU32::new(0),
+            disabled: AtomicBool::new(false),
+        })
+    }
+
+    /// Run a plugin on one serialized `TxView`
+    pub fn run(&self, plugin: &WasmPlugin, input: &[u8]) -> Result<PluginOutput> {
+        if plugin.is_disabled() {
+            return Ok(PluginOutput::default());
+        }
+
+        match self.call(plugin, input) {
+            Ok(output) => {
+                plugin.consecutive_failures.store(0, Ordering::Relaxed);
+                Ok(output)
+            }
+            Err(e) => {
+                let failures = plugin.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failures >= self.max_consecutive_failures {
+                    plugin.disabled.store(true, Ordering::Relaxed);
+                    eprintln!("⚠ Warning: Plugin {} disabled after {} consecutive failures", plugin.name, failures);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn call(&self, plugin: &WasmPlugin, input: &[u8]) -> Result<PluginOutput> {
+        let (mut store, instance) = self.instantiate(&plugin.module)?;
+        let memory = instance.get_memory(&mut store, "memory").context("Missing memory export")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let detect = instance.get_typed_func::<(i32, i32), i64>(&mut store, "detect")?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input)?;
+
+        let packed = detect.call(&mut store, (ptr, len))?;
+        let out_ptr = (packed as u64 >> 32) as usize;
+        let out_len = (packed as u64 & 0xFFFF_FFFF) as usize;
+        if out_len > self.max_output_bytes {
+            bail!("Plugin output of {} bytes exceeds {} bytes", out_len, self.max_output_bytes);
+        }
+
+        let mut output = vec![0u8; out_len];
+        memory.read(&store, out_ptr, &mut output)?;
+        serde_json::from_slice(&output).map_err(|e| anyhow!("Invalid plugin output: {}", e))
+    }
+
+    /// Start the thread advancing the engine epoch, once
+    fn start_ticker(&self) -> Result<()> {
+        let mut result = Ok(());
+        self.ticker.call_once(|| {
+            let ticker = self.engine.clone();
+            result = std::thread::Builder::new()
+                .name("plugin-epoch".to_string())
+                .spawn(move || loop {
+                    std::thread::sleep(Duration::from_millis(EPOCH_TICK_MS));
+                    ticker.increment_epoch();
+                })
+                .map(|_| ())
+                .context("Failed to start plugin epoch thread");
+        });
+        result
+    }
+
+    fn instantiate(&self, module: &Module) -> Result<(Store<HostState>, wasmtime::Instance)> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, HostState { limits });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.fuel)?;
+        store.set_epoch_deadline(self.timeout_ms.div_ceil(EPOCH_TICK_MS).max(1));
+
+        // No host functions: modules with imports fail to instantiate
+        let linker: Linker<HostState> = Linker::new(&self.engine);
+        let instance = linker.instantiate(&mut store, module)?;
+        Ok((store, instance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Module that ignores its input and returns `output`
+    fn static_module(output: &str, version: i32) -> String {
+        format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "{}")
+                (func (export "abi_version") (result i32) (i32.const {}))
+                (func (export "alloc") (param i32) (result i32) (i32.const 4096))
+                (func (export "detect") (param i32 i32) (result i64) (i64.const {})))"#,
+            output.replace('"', "\\\""),
+            version,
+            output.len()
+        )
+    }
+
+    #[test]
+    fn test_static_finding_returned() {
+        let runtime = PluginRuntime::new().unwrap();
+        let wat = static_module(
+            r#"{"findings":[{"risk_type":"FlashLoanAttack","risk_level":"High","description":"from plugin"}]}"#,
+            ABI_VERSION,
+        );
+        let plugin = runtime.load("static", wat.as_bytes()).unwrap();
+
+        let output = runtime.run(&plugin, b"{}").unwrap();
+        assert_eq!(output.findings.len(), 1);
+        assert_eq!(output.findings[0].description, "from plugin");
+    }
+
+    #[test]
+    fn test_abi_version_checked() {
+        let runtime = PluginRuntime::new().unwrap();
+        let wat = static_module(r#"{"findings":[]}"#, ABI_VERSION + 1);
+        assert!(runtime.load("future", wat.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_runaway_plugin_stopped_and_disabled() {
+        let runtime = PluginRuntime::new().unwrap().with_fuel(100_000);
+        let wat = r#"(module
+            (memory (export "memory") 1)
+            (func (export "abi_version") (result i32) (i32.const 1))
+            (func (export "alloc") (param i32) (result i32) (i32.const 0))
+            (func (export "detect") (param i32 i32) (result i64) (loop $spin (br $spin)) (i64.const 0)))"#;
+        let plugin = runtime.load("spin", wat.as_bytes()).unwrap();
+
+        for _ in 0..runtime.max_consecutive_failures {
+            assert!(runtime.run(&plugin, b"{}").is_err());
+        }
+        assert!(plugin.is_disabled());
+        assert!(runtime.run(&plugin, b"{}").unwrap().findings.is_empty());
+    }
+
+    #[test]
+    fn test_imports_rejected() {
+        let runtime = PluginRuntime::new().unwrap();
+        let wat = r#"(module (import "env" "now" (func)) (memory (export "memory") 1))"#;
+        assert!(runtime.load("imports", wat.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_ticker_starts_with_first_plugin() {
+        let runtime = PluginRuntime::new().unwrap();
+        assert!(!runtime.ticker.is_completed());
+
+        let wat = static_module(r#"{"findings":[]}"#, ABI_VERSION);
+        runtime.load("static", wat.as_bytes()).unwrap();
+        assert!(runtime.ticker.is_completed());
+    }
+}
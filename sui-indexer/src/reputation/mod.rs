@@ -0,0 +1,5 @@
+mod models;
+mod store;
+
+pub use models::{AddressReputation, ListStatus, ReputationFileEntry, ReputationRecord};
+pub use store::ReputationStore;
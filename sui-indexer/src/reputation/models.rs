@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::address_reputation;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListStatus {
+    #[default]
+    Unlisted,
+    Allow, // Known arbitrage bots, keepers, protocol operators
+    Block, // Known attackers
+}
+
+impl ListStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListStatus::Unlisted => "unlisted",
+            ListStatus::Allow => "allow",
+            ListStatus::Block => "block",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "allow" => ListStatus::Allow,
+            "block" => ListStatus::Block,
+            _ => ListStatus::Unlisted,
+        }
+    }
+}
+
+/// Reputation of one address
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AddressReputation {
+    pub label: Option<String>,
+    pub list: ListStatus,
+    /// Detection score as of `score_updated_ms`; decays over time
+    pub score: f64,
+    pub score_updated_ms: i64,
+    pub detection_count: u64,
+    pub last_risk_type: Option<String>,
+}
+
+impl AddressReputation {
+    /// Score decayed to `now_ms` with the given half-life
+    pub fn decayed_score(&self, now_ms: i64, half_life_ms: i64) -> f64 {
+        let elapsed = (now_ms - self.score_updated_ms).max(0) as f64;
+        self.score * 0.5f64.powf(elapsed / half_life_ms.max(1) as f64)
+    }
+}
+
+/// Entry of a reputation file (JSON array)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReputationFileEntry {
+    pub address: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Keeps the stored list if omitted
+    #[serde(default)]
+    pub list: Option<ListStatus>,
+    /// Initial score; keeps the stored score if omitted
+    #[serde(default)]
+    pub score: Option<f64>,
+}
+
+/// Address reputation - PostgreSQL model
+#[derive(Debug, Clone, Queryable, Insertable, AsChangeset)]
+#[diesel(table_name = address_reputation)]
+pub struct ReputationRecord {
+    pub address: String,
+    pub label: Option<String>,
+    pub list_status: String,
+    pub score: f64,
+    pub score_updated_ms: i64,
+    pub detection_count: i64,
+    pub last_risk_type: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReputationRecord {
+    pub fn new(address: &str, reputation: &AddressReputation) -> Self {
+        Self {
+            address: address.to_string(),
+            label: reputation.label.clone(),
+            list_status: reputation.list.as_str().to_string(),
+            score: reputation.score,
+            score_updated_ms: reputation.score_updated_ms,
+            detection_count: reputation.detection_count as i64,
+            last_risk_type: reputation.last_risk_type.clone(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn into_reputation(self) -> (String, AddressReputation) {
+        let reputation = AddressReputation {
+            label: self.label,
+            list: ListStatus::parse(&self.list_status),
+            score: self.score,
+            score_updated_ms: self.score_updated_ms,
+            detection_count: self.detection_count.max(0) as u64,
+            last_risk_type: self.last_risk_type,
+        };
+        (self.address, reputation)
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Address Reputation: Labels, Allow/Blocklists and Decaying Detection Scores
+
+use super::models::{AddressReputation, ListStatus, ReputationFileEntry, ReputationRecord};
+use crate::risk::{RiskEvent, RiskLevel};
+use crate::schema::address_reputation;
+use anyhow::{Context, Result};
+use diesel::upsert::excluded;
+use diesel::ExpressionMethods;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+struct ReputationState {
+    entries: HashMap<String, AddressReputation>,
+    // Addresses changed since the last flush to Postgres
+    dirty: HashSet<String>,
+}
+
+/// Address reputation store
+///
+/// Kept in memory for detection and persisted to the `address_reputation`
+/// table. Curated labels and lists come from JSON files; the score is raised
+/// by every detection naming the address and halves every `half_life_ms`.
+///
+/// Detection results are adjusted by reputation:
+/// 1. Blocklisted senders: level raised one step
+/// 2. Allowlisted senders (bots, keepers): level lowered one step, Low dropped
+/// 3. Senders with a decayed score above `flag_score`: level raised one step
+pub struct ReputationStore {
+    state: Mutex<ReputationState>,
+    /// Time for a detection score to halve
+    half_life_ms: i64,
+    /// Decayed score at which an unlisted address counts as previously flagged
+    flag_score: f64,
+}
+
+impl ReputationStore {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(ReputationState::default()),
+            half_life_ms: 7 * 24 * 60 * 60 * 1000, // 7 days
+            flag_score: 50.0,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let mut store = Self::new();
+
+        if let Some(ms) = std::env::var("REPUTATION_HALF_LIFE_MS").ok().and_then(|v| v.parse().ok()) {
+            store = store.with_half_life_ms(ms);
+        }
+        if let Some(score) = std::env::var("REPUTATION_FLAG_SCORE").ok().and_then(|v| v.parse().ok()) {
+            store = store.with_flag_score(score);
+        }
+
+        store
+    }
+
+    /// Set the time for a detection score to halve
+    pub fn with_half_life_ms(mut self, half_life_ms: i64) -> Self {
+        self.half_life_ms = half_life_ms;
+        self
+    }
+
+    /// Set the decayed score at which an address counts as previously flagged
+    pub fn with_flag_score(mut self, flag_score: f64) -> Self {
+        self.flag_score = flag_score;
+        self
+    }
+
+    /// Load labels and lists from the comma-separated JSON files in `REPUTATION_FILES`
+    pub fn load_env_files(&self) -> Result<usize> {
+        let mut loaded = 0;
+        if let Ok(paths) = std::env::var("REPUTATION_FILES") {
+            for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                let content = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read reputation file {}", path))?;
+                loaded += self.load_json(&content)
+                    .with_context(|| format!("Failed to parse reputation file {}", path))?;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Load entries from a JSON array of `ReputationFileEntry`
+    ///
+    /// Labels, lists and scores from files override stored ones when given.
+    pub fn load_json(&self, content: &str) -> Result<usize> {
+        let file_entries: Vec<ReputationFileEntry> = serde_json::from_str(content)?;
+        let mut state = self.state.lock().unwrap();
+
+        for file_entry in &file_entries {
+            let address = normalize_address(&file_entry.address);
+            let entry = state.entries.entry(address.clone()).or_default();
+            entry.label = file_entry.label.clone().or(entry.label.take());
+            if let Some(list) = file_entry.list {
+                entry.list = list;
+            }
+            if let Some(score) = file_entry.score {
+                entry.score = score;
+            }
+            state.dirty.insert(address);
+        }
+
+        Ok(file_entries.len())
+    }
+
+    /// Load all stored entries; call before loading files so file lists take precedence
+    pub async fn load_from_db(&self, conn: &mut AsyncPgConnection) -> Result<usize> {
+        let records: Vec<ReputationRecord> = address_reputation::table
+            .load(conn)
+            .await?;
+
+        let count = records.len();
+        let mut state = self.state.lock().unwrap();
+        for record in records {
+            let (address, reputation) = record.into_reputation();
+            state.entries.insert(address, reputation);
+        }
+
+        Ok(count)
+    }
+
+    /// Upsert entries changed since the last flush
+    pub async fn flush(&self, conn: &mut AsyncPgConnection) -> Result<usize> {
+        let (addresses, records): (Vec<String>, Vec<ReputationRecord>) = {
+            let mut state = self.state.lock().unwrap();
+            let dirty: Vec<String> = state.dirty.drain().collect();
+            let records = dirty
+                .iter()
+                .filter_map(|address| {
+                    state.entries.get(address).map(|r| ReputationRecord::new(address, r))
+                })
+                .collect();
+            (dirty, records)
+        };
+
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        let result = diesel::insert_into(address_reputation::table)
+            .values(&records)
+            .on_conflict(address_reputation::address)
+            .do_update()
+            .set((
+                address_reputation::label.eq(excluded(address_reputation::label)),
+                address_reputation::list_status.eq(excluded(address_reputation::list_status)),
+                address_reputation::score.eq(excluded(address_reputation::score)),
+                address_reputation::score_updated_ms.eq(excluded(address_reputation::score_updated_ms)),
+                address_reputation::detection_count.eq(excluded(address_reputation::detection_count)),
+                address_reputation::last_risk_type.eq(excluded(address_reputation::last_risk_type)),
+                address_reputation::updated_at.eq(excluded(address_reputation::updated_at)),
+            ))
+            .execute(conn)
+            .await;
+
+        match result {
+            Ok(count) => Ok(count),
+            Err(e) => {
+                // Keep the changes for the next flush
+                self.state.lock().unwrap().dirty.extend(addresses);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Reputation of an address with its score decayed to `now_ms`
+    pub fn lookup(&self, address: &str, now_ms: i64) -> Option<AddressReputation> {
+        let state = self.state.lock().unwrap();
+        state.entries.get(&normalize_address(address)).map(|entry| AddressReputation {
+            score: entry.decayed_score(now_ms, self.half_life_ms),
+            score_updated_ms: now_ms.max(entry.score_updated_ms),
+            ..entry.clone()
+        })
+    }
+
+    /// Adjust a detection by the sender's reputation; `None` drops the event
+    pub fn adjust(&self, mut event: RiskEvent) -> Option<RiskEvent> {
+        let Some(reputation) = self.lookup(&event.sender, event.timestamp_ms) else {
+            return Some(event);
+        };
+
+        let original_level = event.risk_level;
+        let adjustment = match reputation.list {
+            ListStatus::Block => {
+                event.risk_level = raise(original_level);
+                Some("blocklisted")
+            }
+            ListStatus::Allow => {
+                event.risk_level = lower(original_level)?;
+                Some("allowlisted")
+            }
+            ListStatus::Unlisted if reputation.score >= self.flag_score => {
+                event.risk_level = raise(original_level);
+                Some("previously_flagged")
+            }
+            ListStatus::Unlisted => None,
+        };
+
+        // A raised or lowered level moves the score into the new band
+        let event = event.with_score_in_level_band();
+        Some(event.with_detail("reputation", serde_json::json!({
+            "label": reputation.label,
+            "list": reputation.list,
+            "score": (reputation.score * 100.0).round() / 100.0,
+            "detection_count": reputation.detection_count,
+            "last_risk_type": reputation.last_risk_type,
+            "adjustment": adjustment,
+            "original_level": original_level,
+        })))
+    }
+
+    /// Raise the score of the sender (and named attacker) of a detection
+    ///
+    /// Points follow the level before `adjust`, so reputation doesn't feed on itself.
+    pub fn record_detection(&self, event: &RiskEvent) {
+        let points = match detected_level(event) {
+            RiskLevel::Low => 5.0,
+            RiskLevel::Medium => 15.0,
+            RiskLevel::High => 30.0,
+            RiskLevel::Critical => 50.0,
+        };
+
+        let mut addresses = vec![normalize_address(&event.sender)];
+        if let Some(attacker) = event.details.get("attacker").and_then(|v| v.as_str()) {
+            let attacker = normalize_address(attacker);
+            if !addresses.contains(&attacker) {
+                addresses.push(attacker);
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        for address in addresses {
+            let entry = state.entries.entry(address.clone()).or_default();
+            entry.score = entry.decayed_score(event.timestamp_ms, self.half_life_ms) + points;
+            entry.score_updated_ms = entry.score_updated_ms.max(event.timestamp_ms);
+            entry.detection_count += 1;
+            entry.last_risk_type = Some(format!("{:?}", event.risk_type));
+            state.dirty.insert(address);
+        }
+    }
+}
+
+impl Default for ReputationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn raise(level: RiskLevel) -> RiskLevel {
+    match level {
+        RiskLevel::Low => RiskLevel::Medium,
+        RiskLevel::Medium => RiskLevel::High,
+        RiskLevel::High | RiskLevel::Critical => RiskLevel::Critical,
+    }
+}
+
+fn lower(level: RiskLevel) -> Option<RiskLevel> {
+    match level {
+        RiskLevel::Low => None,
+        RiskLevel::Medium => Some(RiskLevel::Low),
+        RiskLevel::High => Some(RiskLevel::Medium),
+        RiskLevel::Critical => Some(RiskLevel::High),
+    }
+}
+
+/// Level the detector reported, before any reputation adjustment
+fn detected_level(event: &RiskEvent) -> RiskLevel {
+    event
+        .details
+        .get("reputation")
+        .and_then(|r| r.get("original_level"))
+        .and_then(|level| serde_json::from_value(level.clone()).ok())
+        .unwrap_or(event.risk_level)
+}
+
+/// Lowercase, `0x`-prefixed and zero-padded to 32 bytes, so `0x2` matches its full form
+fn normalize_address(address: &str) -> String {
+    let address = address.trim().to_lowercase();
+    let hex = address.strip_prefix("0x").unwrap_or(&address);
+    if !hex.is_empty() && hex.len() <= 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("0x{:0>64}", hex)
+    } else {
+        format!("0x{}", hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskType;
+
+    const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+    fn event(sender: &str, level: RiskLevel, timestamp_ms: i64) -> RiskEvent {
+        RiskEvent::new(
+            RiskType::SandwichAttack,
+            level,
+            "tx".to_string(),
+            sender.to_string(),
+            1000,
+            timestamp_ms,
+            "test".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_file_lists_adjust_levels() {
+        let store = ReputationStore::new();
+        let loaded = store
+            .load_json(r#"[
+                {"address": "0xBOT", "label": "arbitrage bot", "list": "allow"},
+                {"address": "0xbad", "label": "exploiter", "list": "block"}
+            ]"#)
+            .unwrap();
+        assert_eq!(loaded, 2);
+
+        let bot = store.adjust(event("0xbot", RiskLevel::High, 0)).unwrap();
+        assert_eq!(bot.risk_level, RiskLevel::Medium);
+        assert_eq!(bot.details["reputation"]["label"], serde_json::json!("arbitrage bot"));
+        assert!(store.adjust(event("0xbot", RiskLevel::Low, 0)).is_none());
+
+        let bad = store.adjust(event("0xbad", RiskLevel::High, 0)).unwrap();
+        assert_eq!(bad.risk_level, RiskLevel::Critical);
+
+        let unknown = store.adjust(event("0xnew", RiskLevel::Medium, 0)).unwrap();
+        assert!(!unknown.details.contains_key("reputation"));
+    }
+
+    #[test]
+    fn test_detection_score_decays() {
+        let store = ReputationStore::new();
+        store.record_detection(&event("0xa", RiskLevel::High, 0));
+        store.record_detection(&event("0xa", RiskLevel::High, 0));
+
+        let flagged = store.adjust(event("0xa", RiskLevel::Medium, DAY_MS)).unwrap();
+        assert_eq!(flagged.risk_level, RiskLevel::High);
+        assert_eq!(flagged.details["reputation"]["adjustment"], serde_json::json!("previously_flagged"));
+
+        // Two half-lives later 60 points are down to 15
+        let reputation = store.lookup("0xa", 14 * DAY_MS).unwrap();
+        assert!((reputation.score - 15.0).abs() < 1e-6);
+        assert_eq!(reputation.detection_count, 2);
+
+        let forgotten = store.adjust(event("0xa", RiskLevel::Medium, 14 * DAY_MS)).unwrap();
+        assert_eq!(forgotten.risk_level, RiskLevel::Medium);
+    }
+
+    #[test]
+    fn test_file_keeps_recorded_score() {
+        let store = ReputationStore::new();
+        store.record_detection(&event("0xa", RiskLevel::Critical, 0));
+        store.load_json(r#"[{"address": "0xa", "label": "keeper"}]"#).unwrap();
+
+        let reputation = store.lookup("0xa", 0).unwrap();
+        assert_eq!(reputation.label.as_deref(), Some("keeper"));
+        assert_eq!(reputation.list, ListStatus::Unlisted);
+        assert_eq!(reputation.score, 50.0);
+    }
+
+    #[test]
+    fn test_detection_points_use_original_level() {
+        let store = ReputationStore::new();
+        store.load_json(r#"[{"address": "0xbad", "list": "block"}]"#).unwrap();
+
+        // Raised from Medium to High by the blocklist, but scored as Medium
+        let adjusted = store.adjust(event("0xbad", RiskLevel::Medium, 0)).unwrap();
+        assert_eq!(adjusted.risk_level, RiskLevel::High);
+        store.record_detection(&adjusted);
+
+        assert_eq!(store.lookup("0xbad", 0).unwrap().score, 15.0);
+    }
+
+    #[test]
+    fn test_file_without_list_keeps_stored_list() {
+        let store = ReputationStore::new();
+        store.load_json(r#"[{"address": "0xbad", "list": "block"}]"#).unwrap();
+        store.load_json(r#"[{"address": "0xbad", "label": "exploiter"}]"#).unwrap();
+
+        let reputation = store.lookup("0xbad", 0).unwrap();
+        assert_eq!(reputation.list, ListStatus::Block);
+        assert_eq!(reputation.label.as_deref(), Some("exploiter"));
+    }
+
+    #[test]
+    fn test_short_addresses_match_full_form() {
+        let store = ReputationStore::new();
+        store.load_json(r#"[{"address": "0x2", "list": "allow"}]"#).unwrap();
+
+        let full = "0x0000000000000000000000000000000000000000000000000000000000000002";
+        assert_eq!(store.lookup(full, 0).unwrap().list, ListStatus::Allow);
+        assert_eq!(store.lookup("0X02", 0).unwrap().list, ListStatus::Allow);
+    }
+}
@@ -1,3 +1,3 @@
 mod models;
 
-pub use models::{RiskEvent, RiskLevel, RiskType, DetectionContext};
+pub use models::{RiskEvent, RiskLevel, RiskType, DetectionContext, ScoreScale, Signal};
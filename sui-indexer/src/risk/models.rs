@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -9,23 +9,144 @@ pub enum RiskLevel {
     Critical,
 }
 
+impl RiskLevel {
+    /// Band of the level on the shared 0-100 score
+    pub fn score_band(self) -> std::ops::RangeInclusive<u8> {
+        match self {
+            RiskLevel::Low => 0..=49,
+            RiskLevel::Medium => 50..=69,
+            RiskLevel::High => 70..=84,
+            RiskLevel::Critical => 85..=100,
+        }
+    }
+
+    /// Level whose band holds a 0-100 score
+    pub fn from_score(score: u8) -> Self {
+        [RiskLevel::Critical, RiskLevel::High, RiskLevel::Medium]
+            .into_iter()
+            .find(|level| score >= *level.score_band().start())
+            .unwrap_or(RiskLevel::Low)
+    }
+}
+
+/// How an analyzer's signal points map onto the shared 0-100 score
+///
+/// Each analyzer keeps its own point thresholds per level. A threshold lands
+/// on the start of its level's `score_band`, points up to the next threshold
+/// are spread linearly over the band, and the top level's band ends at
+/// `max_points`, the most the analyzer can award. The level of the points and
+/// the level of the score are therefore always the same.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreScale {
+    /// Level below every threshold
+    base: RiskLevel,
+    /// Points at which each higher level starts, ascending
+    thresholds: &'static [(RiskLevel, u32)],
+    max_points: u32,
+}
+
+impl ScoreScale {
+    pub const fn new(base: RiskLevel, thresholds: &'static [(RiskLevel, u32)], max_points: u32) -> Self {
+        Self { base, thresholds, max_points }
+    }
+
+    pub fn level(&self, points: u32) -> RiskLevel {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|(_, start)| points >= *start)
+            .map_or(self.base, |(level, _)| *level)
+    }
+
+    /// Normalized 0-100 score of a point total
+    pub fn score(&self, points: u32) -> u8 {
+        let level = self.level(points);
+        let start = self.thresholds.iter().find(|(l, _)| *l == level).map_or(0, |(_, s)| *s);
+        let next = self.thresholds.iter().map(|(_, s)| *s).find(|s| *s > points);
+        let band = level.score_band();
+        let (low, high) = (*band.start() as u32, *band.end() as u32);
+
+        let score = match next {
+            Some(next) => low + (points - start) * (high - low + 1) / (next - start),
+            None if self.max_points > start => {
+                low + (points.min(self.max_points) - start) * (high - low) / (self.max_points - start)
+            }
+            None => high,
+        };
+        score.min(high) as u8
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RiskType {
     FlashLoanAttack,
     PriceManipulation,
     SandwichAttack,
     OracleManipulation,  // NEW: Oracle manipulation via lending
+    PumpAndDump,         // Multi-transaction accumulation followed by reversal sell
+    LiquidationAnomaly,  // Bad debt, manipulated liquidations or cascades
+    JitLiquidity,        // Liquidity added right before and removed right after a large swap
+    WashTrading,         // Repeated round trips inflating volume with near-zero net position
+    GovernanceChange,    // Package upgrade or admin capability transfer / use
+    InterestRateManipulation, // Utilization-driven rate spike or inconsistent borrow index
+    FailedTransactionProbing, // Repeated aborted calls into watched packages
+    LiquidityDrain,      // Rapid pool liquidity loss or creator rug pull
+    PriorityGasBidding,  // Gas price far above the baseline for the shared objects touched
+    StatisticalAnomaly,  // Swap extreme relative to the pool's own EWMA baseline
+}
+
+/// Signal that contributed to a risk score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signal {
+    pub name: String,
+    /// Observed value (amounts in base units, ratios in bps, durations in ms)
+    pub value: f64,
+    /// Value at which the signal fires (1 for boolean signals)
+    pub threshold: f64,
+    /// Points added to the risk score
+    pub points: u32,
+}
+
+impl Signal {
+    pub fn new(name: impl Into<String>, value: f64, threshold: f64, points: u32) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            threshold,
+            points,
+        }
+    }
+
+    /// Boolean signal: fired because a pattern is present
+    pub fn flag(name: impl Into<String>, points: u32) -> Self {
+        Self::new(name, 1.0, 1.0, points)
+    }
+
+    /// Risk score from the signals that fired
+    pub fn total(signals: &[Signal]) -> u32 {
+        signals.iter().map(|s| s.points).sum()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskEvent {
+    /// Primary classification
     pub risk_type: RiskType,
+    /// Classifications of other detectors merged into this event
+    pub secondary_types: Vec<RiskType>,
     pub risk_level: RiskLevel,
     pub tx_digest: String,
     pub sender: String,
     pub checkpoint: i64,
     pub timestamp_ms: i64,
+    /// Identifiers and structured context (pools, addresses, per-tx breakdowns)
     pub details: HashMap<String, serde_json::Value>,
+    /// Signals that fired, in scoring order
+    pub signals: Vec<Signal>,
+    /// Raw numeric metrics (amounts in base units, `_bps` ratios, `_ms` durations)
+    pub metrics: HashMap<String, f64>,
+    /// Normalized 0-100 score; always within the level's `score_band`
+    pub score: u8,
     pub description: String,
 }
 
@@ -41,12 +162,16 @@ impl RiskEvent {
     ) -> Self {
         Self {
             risk_type,
+            secondary_types: Vec::new(),
             risk_level,
             tx_digest,
             sender,
             checkpoint,
             timestamp_ms,
             details: HashMap::new(),
+            signals: Vec::new(),
+            metrics: HashMap::new(),
+            score: *risk_level.score_band().start(),
             description,
         }
     }
@@ -57,6 +182,39 @@ impl RiskEvent {
         }
         self
     }
+
+    pub fn with_metric(mut self, key: impl Into<String>, value: f64) -> Self {
+        self.metrics.insert(key.into(), value);
+        self
+    }
+
+    /// Attach the signals behind the score; the score is their total capped at
+    /// 100, kept within the band of the event's level
+    pub fn with_signals(mut self, signals: Vec<Signal>) -> Self {
+        self.score = Signal::total(&signals).min(100) as u8;
+        self.signals = signals;
+        self.with_score_in_level_band()
+    }
+
+    /// Attach an analyzer's signals, scored on its scale
+    pub fn with_scaled_signals(mut self, signals: Vec<Signal>, scale: &ScoreScale) -> Self {
+        self.score = scale.score(Signal::total(&signals));
+        self.signals = signals;
+        self.with_score_in_level_band()
+    }
+
+    /// Keep the score within the band of the event's level, e.g. after the
+    /// level was raised or declared rather than scored
+    pub fn with_score_in_level_band(mut self) -> Self {
+        let band = self.risk_level.score_band();
+        self.score = self.score.clamp(*band.start(), *band.end());
+        self
+    }
+
+    /// Sum of the points of the signals that fired
+    pub fn risk_score(&self) -> u32 {
+        Signal::total(&self.signals)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -77,3 +235,50 @@ impl DetectionContext {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCALE: ScoreScale = ScoreScale::new(
+        RiskLevel::Low,
+        &[(RiskLevel::Medium, 50), (RiskLevel::High, 70), (RiskLevel::Critical, 85)],
+        115,
+    );
+
+    #[test]
+    fn test_thresholds_land_on_band_starts() {
+        assert_eq!(SCALE.score(0), 0);
+        assert_eq!(SCALE.score(49), 49);
+        assert_eq!(SCALE.score(50), 50);
+        assert_eq!(SCALE.score(84), 84);
+        assert_eq!(SCALE.score(85), 85);
+        assert_eq!(SCALE.score(100), 92);
+        assert_eq!(SCALE.score(115), 100);
+        assert_eq!(SCALE.score(500), 100);
+    }
+
+    #[test]
+    fn test_analyzer_bands_map_onto_shared_bands() {
+        // Oracle style: everything below 60 points is Medium
+        let medium_base = ScoreScale::new(RiskLevel::Medium, &[(RiskLevel::High, 60), (RiskLevel::Critical, 80)], 110);
+        assert_eq!(medium_base.score(40), 63);
+        // Highest level High: the most points end at the top of its band
+        let high_top = ScoreScale::new(RiskLevel::Low, &[(RiskLevel::Medium, 50), (RiskLevel::High, 70)], 70);
+        assert_eq!(high_top.score(70), 84);
+
+        for scale in [SCALE, medium_base, high_top] {
+            for points in 0..=200 {
+                assert_eq!(RiskLevel::from_score(scale.score(points)), scale.level(points), "{} points", points);
+            }
+        }
+    }
+
+    #[test]
+    fn test_declared_level_keeps_score_in_band() {
+        let event = RiskEvent::new(RiskType::WashTrading, RiskLevel::High, "tx".into(), "0xa".into(), 1, 0, "test".into())
+            .with_signals(vec![Signal::flag("rule", 20)]);
+        assert_eq!(event.score, 70);
+        assert_eq!(event.risk_score(), 20);
+    }
+}
@@ -0,0 +1,368 @@
+// Copyright (c) 2024 DeFi Protocol Indexer
+// Declarative Rules: Conditions over a Transaction's Decoded Events
+
+use super::models::{Aggregate, Condition, Quantifier, Rule, RuleFile};
+use crate::events::decode_event_fields;
+use crate::risk::{DetectionContext, RiskEvent, Signal};
+use anyhow::{bail, Context, Result};
+use sui_types::full_checkpoint_content::ExecutedTransaction;
+
+/// Points per satisfied condition; all conditions hold when a rule fires
+const CONDITION_POINTS: u32 = 10;
+
+/// Event decoded to its Move name and JSON fields, in emission order
+#[derive(Debug, Clone)]
+pub struct DecodedEvent {
+    pub name: String,
+    pub fields: serde_json::Value,
+}
+
+impl DecodedEvent {
+    fn field(&self, field: &str) -> Option<&serde_json::Value> {
+        self.fields.get(field)
+    }
+}
+
+/// Decode the known events of a transaction, keeping their order
+pub fn decode_events(tx: &ExecutedTransaction) -> Vec<DecodedEvent> {
+    let Some(events) = &tx.events else {
+        return Vec::new();
+    };
+
+    events
+        .data
+        .iter()
+        .filter_map(|event| {
+            Some(DecodedEvent {
+                name: event.type_.name.to_string(),
+                fields: decode_event_fields(event)?,
+            })
+        })
+        .collect()
+}
+
+/// Satisfied condition: signal for the breakdown and optional binding
+struct Outcome {
+    signal: Signal,
+    bind: Option<String>,
+}
+
+/// Rule engine
+///
+/// Rules come from YAML or TOML files so analysts can add detections without
+/// writing an analyzer. A rule fires when all its conditions hold over the
+/// transaction's decoded events; each condition adds a signal and bound
+/// counts and aggregates become metrics and description placeholders.
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+}
+
+impl RuleEngine {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Load rules from the comma-separated files in `RULE_FILES`
+    pub fn load_env_files(&mut self) -> Result<usize> {
+        let mut loaded = 0;
+        if let Ok(paths) = std::env::var("RULE_FILES") {
+            for path in paths.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                loaded += self.load_file(path)?;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Load a `.yaml` / `.yml` or `.toml` rule file
+    pub fn load_file(&mut self, path: &str) -> Result<usize> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rule file {}", path))?;
+
+        let result = if path.ends_with(".toml") {
+            self.load_toml(&content)
+        } else if path.ends_with(".yaml") || path.ends_with(".yml") {
+            self.load_yaml(&content)
+        } else {
+            bail!("Unsupported rule file extension (expected .yaml, .yml or .toml)");
+        };
+        result.with_context(|| format!("Failed to load rule file {}", path))
+    }
+
+    pub fn load_yaml(&mut self, content: &str) -> Result<usize> {
+        let file: RuleFile = serde_yaml::from_str(content)?;
+        self.add_rules(file.rules)
+    }
+
+    pub fn load_toml(&mut self, content: &str) -> Result<usize> {
+        let file: RuleFile = toml::from_str(content)?;
+        self.add_rules(file.rules)
+    }
+
+    fn add_rules(&mut self, rules: Vec<Rule>) -> Result<usize> {
+        for rule in &rules {
+            if rule.conditions.is_empty() {
+                bail!("Rule {} has no conditions", rule.id);
+            }
+            if self.rules.iter().any(|r| r.id == rule.id) {
+                bail!("Duplicate rule id {}", rule.id);
+            }
+        }
+
+        let count = rules.len();
+        self.rules.extend(rules);
+        Ok(count)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluate enabled rules against a transaction's decoded events
+    pub fn evaluate(&self, events: &[DecodedEvent], context: &DetectionContext) -> Vec<RiskEvent> {
+        if events.is_empty() {
+            return Vec::new();
+        }
+
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| self.evaluate_rule(rule, events, context))
+            .collect()
+    }
+
+    fn evaluate_rule(&self, rule: &Rule, events: &[DecodedEvent], context: &DetectionContext) -> Option<RiskEvent> {
+        let outcomes = rule
+            .conditions
+            .iter()
+            .map(|condition| evaluate_condition(condition, events))
+            .collect::<Option<Vec<Outcome>>>()?;
+
+        let mut description = rule
+            .description
+            .replace("{rule}", &rule.id)
+            .replace("{sender}", &context.sender)
+            .replace("{tx_digest}", &context.tx_digest);
+
+        let mut event = RiskEvent::new(
+            rule.risk_type.clone(),
+            rule.risk_level,
+            context.tx_digest.clone(),
+            context.sender.clone(),
+            context.checkpoint,
+            context.timestamp_ms,
+            String::new(),
+        )
+        .with_detail("rule_id", serde_json::json!(rule.id));
+
+        let mut signals = Vec::new();
+        for outcome in outcomes {
+            if let Some(bind) = &outcome.bind {
+                description = description.replace(&format!("{{{}}}", bind), &outcome.signal.value.to_string());
+                event = event.with_metric(bind.clone(), outcome.signal.value);
+            }
+            signals.push(outcome.signal);
+        }
+
+        // The rule declares its level; `with_signals` keeps the score within its band
+        event.description = description;
+        Some(event.with_signals(signals))
+    }
+}
+
+impl Default for RuleEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn named<'a>(events: &'a [DecodedEvent], name: &'a str) -> impl Iterator<Item = &'a DecodedEvent> + 'a {
+    events.iter().filter(move |e| e.name == name)
+}
+
+/// `None` if the condition doesn't hold
+fn evaluate_condition(condition: &Condition, events: &[DecodedEvent]) -> Option<Outcome> {
+    match condition {
+        Condition::Exists { event } => {
+            let count = named(events, event).count();
+            (count > 0).then(|| Outcome {
+                signal: Signal::new(format!("{}.exists", event), count as f64, 1.0, CONDITION_POINTS),
+                bind: None,
+            })
+        }
+        Condition::Absent { event } => (named(events, event).count() == 0).then(|| Outcome {
+            signal: Signal::flag(format!("{}.absent", event), CONDITION_POINTS),
+            bind: None,
+        }),
+        Condition::Count { event, op, value, bind } => {
+            let count = named(events, event).count() as f64;
+            op.compare(&serde_json::json!(count), &serde_json::json!(value)).then(|| Outcome {
+                signal: Signal::new(
+                    bind.clone().unwrap_or_else(|| format!("{}.count", event)),
+                    count,
+                    *value,
+                    CONDITION_POINTS,
+                ),
+                bind: bind.clone(),
+            })
+        }
+        Condition::Field { event, field, op, value, quantifier } => {
+            let values: Vec<&serde_json::Value> = named(events, event).filter_map(|e| e.field(field)).collect();
+            let matched: Vec<&serde_json::Value> = values.iter().copied().filter(|v| op.compare(v, value)).collect();
+            let holds = match quantifier {
+                Quantifier::Any => !matched.is_empty(),
+                Quantifier::All => !values.is_empty() && matched.len() == values.len(),
+            };
+            if !holds {
+                return None;
+            }
+
+            let name = format!("{}.{}", event, field);
+            let signal = match (matched[0].as_f64(), value.as_f64()) {
+                (Some(observed), Some(threshold)) => Signal::new(name, observed, threshold, CONDITION_POINTS),
+                _ => Signal::flag(name, CONDITION_POINTS),
+            };
+            Some(Outcome { signal, bind: None })
+        }
+        Condition::Aggregate { event, field, function, op, value, bind } => {
+            let values = named(events, event).filter_map(|e| e.field(field).and_then(|v| v.as_f64()));
+            let aggregate = match function {
+                Aggregate::Sum => Some(values.sum::<f64>()),
+                Aggregate::Max => values.reduce(f64::max),
+                Aggregate::Min => values.reduce(f64::min),
+            }?;
+
+            let function_name = serde_json::to_value(function).ok()?;
+            op.compare(&serde_json::json!(aggregate), &serde_json::json!(value)).then(|| Outcome {
+                signal: Signal::new(
+                    bind.clone().unwrap_or_else(|| {
+                        format!("{}.{}.{}", event, field, function_name.as_str().unwrap_or_default())
+                    }),
+                    aggregate,
+                    *value,
+                    CONDITION_POINTS,
+                ),
+                bind: bind.clone(),
+            })
+        }
+        Condition::Order { first, then } => {
+            let first_index = events.iter().position(|e| e.name == *first)?;
+            events[first_index + 1..]
+                .iter()
+                .any(|e| e.name == *then)
+                .then(|| Outcome {
+                    signal: Signal::flag(format!("{}.before.{}", first, then), CONDITION_POINTS),
+                    bind: None,
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::{RiskLevel, RiskType};
+
+    const ORACLE_RULES: &str = r#"
+rules:
+  - id: flash_funded_borrow
+    risk_type: OracleManipulation
+    risk_level: High
+    description: "{borrowed} borrowed after {swap_count} swaps by {sender}"
+    conditions:
+      - order: { first: FlashLoanTaken, then: BorrowEvent }
+      - count: { event: SwapExecuted, op: ">=", value: 2, bind: swap_count }
+      - field: { event: SwapExecuted, field: price_impact, op: ">=", value: 500 }
+      - aggregate: { event: BorrowEvent, field: amount, fn: sum, op: ">", value: 1000, bind: borrowed }
+      - absent: { event: LiquidationEvent }
+"#;
+
+    fn event(name: &str, fields: serde_json::Value) -> DecodedEvent {
+        DecodedEvent { name: name.to_string(), fields }
+    }
+
+    fn context() -> DetectionContext {
+        DetectionContext::new("tx".to_string(), "0xa".to_string(), 1000, 0)
+    }
+
+    fn attack() -> Vec<DecodedEvent> {
+        vec![
+            event("FlashLoanTaken", serde_json::json!({"amount": 1_000_000})),
+            event("SwapExecuted", serde_json::json!({"price_impact": 800})),
+            event("SwapExecuted", serde_json::json!({"price_impact": 100})),
+            event("BorrowEvent", serde_json::json!({"amount": 3000})),
+            event("FlashLoanRepaid", serde_json::json!({"amount": 1_000_000})),
+        ]
+    }
+
+    #[test]
+    fn test_yaml_rule_fires() {
+        let mut engine = RuleEngine::new();
+        assert_eq!(engine.load_yaml(ORACLE_RULES).unwrap(), 1);
+
+        let events = engine.evaluate(&attack(), &context());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].risk_type, RiskType::OracleManipulation);
+        assert_eq!(events[0].risk_level, RiskLevel::High);
+        assert_eq!(events[0].description, "3000 borrowed after 2 swaps by 0xa");
+        assert_eq!(events[0].metrics["borrowed"], 3000.0);
+        assert_eq!(events[0].signals.len(), 5);
+        assert_eq!(events[0].score, 70);
+    }
+
+    #[test]
+    fn test_ordering_and_absence_required() {
+        let mut engine = RuleEngine::new();
+        engine.load_yaml(ORACLE_RULES).unwrap();
+
+        // Borrow before the flash loan
+        let mut reordered = attack();
+        reordered.swap(0, 3);
+        assert!(engine.evaluate(&reordered, &context()).is_empty());
+
+        let mut liquidated = attack();
+        liquidated.push(event("LiquidationEvent", serde_json::json!({})));
+        assert!(engine.evaluate(&liquidated, &context()).is_empty());
+    }
+
+    #[test]
+    fn test_toml_rule_with_all_quantifier() {
+        let mut engine = RuleEngine::new();
+        engine
+            .load_toml(r#"
+[[rules]]
+id = "all_swaps_large"
+risk_type = "PriceManipulation"
+risk_level = "Medium"
+description = "Every swap moved the price: max {max_impact}"
+
+[[rules.conditions]]
+field = { event = "SwapExecuted", field = "price_impact", op = ">=", value = 500, match = "all" }
+
+[[rules.conditions]]
+aggregate = { event = "SwapExecuted", field = "price_impact", fn = "max", op = ">", value = 0, bind = "max_impact" }
+"#)
+            .unwrap();
+
+        assert!(engine.evaluate(&attack(), &context()).is_empty());
+
+        let large = vec![
+            event("SwapExecuted", serde_json::json!({"price_impact": 800})),
+            event("SwapExecuted", serde_json::json!({"price_impact": 600})),
+        ];
+        let events = engine.evaluate(&large, &context());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].description, "Every swap moved the price: max 800");
+    }
+
+    #[test]
+    fn test_duplicate_rule_rejected() {
+        let mut engine = RuleEngine::new();
+        engine.load_yaml(ORACLE_RULES).unwrap();
+        assert!(engine.load_yaml(ORACLE_RULES).is_err());
+        assert_eq!(engine.len(), 1);
+    }
+}
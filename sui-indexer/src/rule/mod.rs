@@ -0,0 +1,5 @@
+mod models;
+mod engine;
+
+pub use models::{Aggregate, Condition, Op, Quantifier, Rule, RuleFile};
+pub use engine::{decode_events, DecodedEvent, RuleEngine};
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::risk::{RiskLevel, RiskType};
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Op {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Gte,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Lte,
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+}
+
+impl Op {
+    /// Compare numbers; other JSON values only support `==` and `!=`
+    pub fn compare(&self, left: &serde_json::Value, right: &serde_json::Value) -> bool {
+        if let (Some(l), Some(r)) = (left.as_f64(), right.as_f64()) {
+            return match self {
+                Op::Gt => l > r,
+                Op::Gte => l >= r,
+                Op::Lt => l < r,
+                Op::Lte => l <= r,
+                Op::Eq => l == r,
+                Op::Ne => l != r,
+            };
+        }
+
+        match self {
+            Op::Eq => left == right,
+            Op::Ne => left != right,
+            _ => false,
+        }
+    }
+}
+
+/// How many of the matching events a field comparison must hold for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantifier {
+    #[default]
+    Any,
+    All,
+}
+
+/// Aggregate over a numeric field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregate {
+    Sum,
+    Max,
+    Min,
+}
+
+/// One condition of a rule; all conditions of a rule must hold
+///
+/// `event` is the Move event name (e.g. `SwapExecuted`). Conditions with
+/// `bind` expose their value to the description template and as a metric.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Condition {
+    /// At least one event of the type
+    Exists { event: String },
+    /// No event of the type
+    Absent { event: String },
+    /// Number of events of the type
+    Count {
+        event: String,
+        op: Op,
+        value: f64,
+        #[serde(default)]
+        bind: Option<String>,
+    },
+    /// Field of any (or all) events of the type
+    Field {
+        event: String,
+        field: String,
+        op: Op,
+        value: serde_json::Value,
+        #[serde(default, rename = "match")]
+        quantifier: Quantifier,
+    },
+    /// Sum, max or min of a numeric field over events of the type
+    Aggregate {
+        event: String,
+        field: String,
+        #[serde(rename = "fn")]
+        function: Aggregate,
+        op: Op,
+        value: f64,
+        #[serde(default)]
+        bind: Option<String>,
+    },
+    /// An event of type `first` emitted before an event of type `then`
+    Order { first: String, then: String },
+}
+
+/// Declarative detection rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    pub risk_type: RiskType,
+    pub risk_level: RiskLevel,
+    /// Template with `{sender}`, `{tx_digest}`, `{rule}` and bound values
+    pub description: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    pub conditions: Vec<Condition>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Contents of a rule file (`rules:` in YAML, `[[rules]]` in TOML)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleFile {
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
@@ -1,5 +1,33 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    address_reputation (address) {
+        address -> Text,
+        label -> Nullable<Text>,
+        list_status -> Text,
+        score -> Float8,
+        score_updated_ms -> Int8,
+        detection_count -> Int8,
+        last_risk_type -> Nullable<Text>,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    alert_outbox (id) {
+        id -> Int8,
+        channel -> Text,
+        kind -> Text,
+        payload -> Jsonb,
+        status -> Text,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        delivered_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     transactions (id) {
         id -> Int8,
@@ -27,4 +55,4 @@ diesel::table! {
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(transactions, watermarks,);
+diesel::allow_tables_to_appear_in_same_query!(address_reputation, alert_outbox, transactions, watermarks,);